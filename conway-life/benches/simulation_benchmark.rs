@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use conway_life::{Environment, SimCell};
+use conway_life::screensaver::random_soup;
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256StarStar;
+
+/// Previous implementation of the simulation, counting the neighbors in a HashMap
+fn simulate_hash_map(environment: &Environment) -> Environment {
+    let mut neighbors = HashMap::with_capacity(environment.get_living_count() * 9);
+    for cell in environment.living_cells() {
+        for x in (cell.x - 1)..=(cell.x + 1) {
+            for y in (cell.y - 1)..=(cell.y + 1) {
+                let n = SimCell::new(x, y);
+                if n != *cell {
+                    *neighbors.entry(n).or_insert(0usize) += 1;
+                }
+            }
+        }
+    }
+
+    let rule = environment.rule();
+    let living: Vec<SimCell> = neighbors.iter()
+        .filter(|(cell, &count)| if environment.get_cell(cell) {
+            rule.is_survival(count)
+        } else {
+            rule.is_birth(count)
+        })
+        .map(|(cell, _)| *cell)
+        .collect();
+
+    let mut next = Environment::with_rule(rule);
+    next.set_living(&living);
+    next
+}
+
+fn random_soups(c: &mut Criterion) {
+    let mut generator = Xoshiro256StarStar::seed_from_u64(42);
+    let mut group = c.benchmark_group("Simulate");
+
+    for size in [64, 128, 256] {
+        let soup = random_soup(&mut generator, size, size, 0.35);
+        group.bench_with_input(BenchmarkId::new("sort merge", size), &soup, |b, soup| {
+            b.iter_batched(|| soup.clone(), |mut environment| environment.simulate(), BatchSize::LargeInput);
+        });
+        group.bench_with_input(BenchmarkId::new("hash map", size), &soup, |b, soup| {
+            b.iter(|| simulate_hash_map(soup));
+        });
+    }
+}
+
+criterion_group!(benches, random_soups);
+criterion_main!(benches);
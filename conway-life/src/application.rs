@@ -1,106 +1,431 @@
 //! Contains the modules to show the user interface of the simulator.
 
 use std::{fs, io, thread};
-use std::io::{Read, Stdout, Write};
-use std::sync::mpsc;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
 use std::sync::mpsc::Sender;
 use std::time::{Duration, Instant};
 
-use crossterm::{event, execute};
-use crossterm::event::{Event, KeyCode, KeyEventKind};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
-use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
-use ratatui::Terminal;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::Style;
 use ratatui::widgets::{Block, Borders, Paragraph};
-use thiserror::Error;
+use rand::prelude::*;
+use rand_xoshiro::Xoshiro256StarStar as RandomGenerator;
+use tui_app::input::{self, InputEvent, Timing};
+use tui_app::keys::{self, KeyBindings};
+use tui_app::terminal::{self, Tui};
+
+use crate::{Environment, Rule, SimCell, Viewport};
+use crate::catalog::Catalog;
+use crate::config::{Config, ConfigError, Limits, Theme};
+use crate::export::{self, AnimationOptions};
+use crate::headless::{self, HeadlessError};
+use crate::image::{self, ImageOptions};
+use crate::pattern::{PatternError, PatternFormat};
+
+use session::{SavedSession, Session};
+use widgets::{EnvironmentView, StatusBar};
+use worker::{Simulated, Worker};
+
+pub(crate) mod commands;
+pub(crate) mod session;
+pub(crate) mod widgets;
+pub(crate) mod worker;
+
+pub use tui_app::ApplicationError;
+
+/// Represents an export of the simulation requested by the user
+pub(crate) enum Export {
+    Gif(fs::File),
+    Apng(fs::File),
+    PngFrames(PathBuf),
+}
 
-use crate::{Environment, SimCell, Viewport};
+/// Requested change of the simulation speed
+pub(crate) enum SpeedChange {
+    /// Simulate one generation after each interval
+    Interval(Duration),
+    /// Simulate as fast as possible, drawing only every given amount of generations
+    Max(usize),
+    Faster,
+    Slower,
+}
 
-#[derive(Error, Debug)]
-pub enum ApplicationError {
-    #[error("Error with terminal application")]
-    Terminal(#[from] io::Error),
+/// Requested change of the open tabs
+pub(crate) enum TabChange {
+    /// Open a copy of the current tab
+    New,
+    Close,
+    Next,
+    Previous,
+    /// Show the given tab, counting from 1
+    Select(usize),
+}
 
-    #[error("Error while transmitting information")]
-    Channel(#[from] std::sync::mpsc::RecvError),
+/// A limit of the simulation that can be changed
+pub(crate) enum Limit {
+    Population,
+    Size,
 }
 
 /// Represents an event happening within the application.
-enum AppEvent {
+pub(crate) enum AppEvent {
     ShowStats,
     ShowCoordinates,
-    PartialInput(String),
+    PartialInput(String, usize),
     ErrorInput(String, String),
-    Load(fs::File),
+    /// Load a pattern or a saved session from a file
+    Load(PathBuf),
+    /// Convert an image into cells
+    LoadImage(PathBuf, ImageOptions),
+    /// Place a pattern of the catalog, by name, with its top left corner at the given position
+    Place(String, Option<(i32, i32)>),
+    /// Download a pattern from an URL
+    Fetch(String),
+    /// A downloaded pattern, along with its URL
+    Fetched(String, Environment),
     Save(fs::File),
+    Export(Export, usize, AnimationOptions),
+    Message(String),
+    Speed(SpeedChange),
     Pause,
+    /// Advance the given amount of generations while paused
+    Step(usize),
+    /// Execute the commands of a script, given its path and contents
+    Script(PathBuf, String),
+    /// Start or stop the screensaver
+    Screensaver,
+    /// Start or stop comparing the current tab with a snapshot of it
+    Diff,
+    /// Kill every cell of the current tab
+    Clear,
+    /// Restore the environment loaded in the current tab
+    Reset,
+    /// Store a snapshot of the current tab in a slot, or list the slots
+    Snap(Option<String>),
+    /// Replace the current tab with the snapshot of a slot
+    Restore(String),
+    /// Show the limits of the simulation, or change one of them (0 disables it)
+    Limits(Option<(Limit, usize)>),
+    /// Change the rule of the current tab
+    Rule(Rule),
+    Tab(TabChange),
+    /// Change the amount of times the screen is drawn per second
+    FrameRate(u64),
+    /// Simulate the generations of a tick, without drawing them
     Tick,
+    /// Generations of a tab simulated by the worker thread
+    Simulated(Box<Simulated>),
+    /// Draw the current state of the simulation
+    Frame,
     Quit,
 }
 
+impl AppEvent {
+    /// Returns true if the event reads or changes the environments, so it has to wait until the
+    /// tabs being simulated by the worker thread come back
+    fn needs_environment(&self) -> bool {
+        !matches!(self, AppEvent::ShowStats | AppEvent::ShowCoordinates | AppEvent::PartialInput(..)
+            | AppEvent::ErrorInput(..) | AppEvent::Message(_) | AppEvent::Speed(_) | AppEvent::Pause
+            | AppEvent::Limits(_) | AppEvent::FrameRate(_) | AppEvent::Tick | AppEvent::Simulated(_)
+            | AppEvent::Frame | AppEvent::Quit)
+    }
+}
+
+impl From<InputEvent> for AppEvent {
+    fn from(event: InputEvent) -> Self {
+        match event {
+            InputEvent::Command(input) => commands::parse(&input),
+            InputEvent::Edit(input, cursor) => AppEvent::PartialInput(input, cursor),
+            InputEvent::Message(message) => AppEvent::Message(message),
+            InputEvent::Tick => AppEvent::Tick,
+            InputEvent::Frame => AppEvent::Frame,
+            InputEvent::Quit => AppEvent::Quit,
+        }
+    }
+}
+
+/// Key bindings available without configuration
+const DEFAULT_KEY_BINDINGS: [(&str, &str); 6] = [
+    ("[", "slower"),
+    ("]", "faster"),
+    (".", "step"),
+    ("ctrl-t", "tab new"),
+    ("alt-right", "tab next"),
+    ("alt-left", "tab prev"),
+];
+
+/// Maximum nesting of scripts, to stop scripts that run themselves
+const MAX_SCRIPT_DEPTH: usize = 8;
+
+/// Slowest and fastest tick intervals reachable with the faster / slower commands
+const SLOWEST_TICK: Duration = Duration::from_secs(5);
+const FASTEST_TICK: Duration = Duration::from_millis(1);
+
+/// Frames drawn per second when not configured
+const DEFAULT_FRAME_RATE: u64 = 30;
+/// Most frames drawn per second
+const MAX_FRAME_RATE: u64 = 120;
+
 /// Main application object that manages the interaction and drawing
 pub struct App {
     // Conway's Game of life specific
-    environment: crate::Environment,
-    viewport: crate::Viewport,
+    /// Environments open in tabs, each one with its own viewport and generation
+    sessions: Vec<Session>,
+    /// Index of the tab being shown
+    active: usize,
 
     // Application specific
     show_stats: bool,
     show_coordinates: bool,
     pause: bool,
-    last_simulation_time: Duration,
-    generation: usize,
     tick_time: Duration,
+    /// Generations simulated on each tick. More than one when running at max speed.
+    generations_per_tick: usize,
+    /// Frames drawn per second, independent of the simulation speed
+    frame_rate: u64,
+    key_bindings: KeyBindings,
+    theme: Theme,
+    /// Pause the simulation when exceeded
+    limits: Limits,
+
+    // Input box
+    input: String,
+    cursor: usize,
+    message: String,
+
+    // Communication with the input and export threads
+    tick_state: Arc<Timing>,
+    sender: Option<Sender<AppEvent>>,
+
+    // Simulation on the worker thread
+    worker: Option<Worker>,
+    /// Tabs sent to the worker and not back yet
+    simulating: usize,
+    /// Events that arrived while the tabs were being simulated, handled once they are back
+    deferred: VecDeque<AppEvent>,
+
+    /// Script executed when the application starts
+    startup_script: Option<(PathBuf, String)>,
+    /// Named patterns that can be placed
+    catalog: Catalog,
+    /// Snapshots stored with `snap`, by slot
+    slots: HashMap<String, SavedSession>,
+    /// Directory where snapshots are also written, if enabled
+    snapshot_directory: Option<PathBuf>,
+
+    // Random features
+    /// Seed of the generator, recorded in save files so runs can be repeated
+    seed: u64,
+    /// Source of every random value, so a seed reproduces the whole run
+    generator: RandomGenerator,
 }
 
 impl Default for App {
     /// Creates a default implementation App
     fn default() -> Self {
-        // Setup environment and viewport
-        let mut environment = crate::Environment::default();
+        App::new(App::f_pentomino())
+    }
+}
+
+impl App {
+    /// Creates an App that shows the given environment
+    pub fn new(environment: Environment) -> Self {
+        let sessions = vec![Session::new(environment)];
+        let active = 0;
 
-        // Create the F-Pentomino
+        let show_stats = true;
+        let show_coordinates = false;
+        let tick_time = Duration::from_millis(50);
+        let generations_per_tick = 1;
+        let pause = false;
+        let key_bindings = keys::bindings(&DEFAULT_KEY_BINDINGS);
+        let theme = Theme::default();
+        let frame_rate = DEFAULT_FRAME_RATE;
+        let tick_state = Arc::new(Timing::new(tick_time, App::frame_interval(frame_rate)));
+        let seed = rand::thread_rng().next_u64();
+
+        App {
+            sessions, active, show_stats, show_coordinates, pause,
+            tick_time, generations_per_tick, frame_rate, key_bindings, theme,
+            limits: Limits::default(),
+            input: String::default(),
+            cursor: 0,
+            message: String::default(),
+            tick_state,
+            sender: None,
+            worker: None,
+            simulating: 0,
+            deferred: VecDeque::new(),
+            startup_script: None,
+            catalog: Catalog::default(),
+            slots: HashMap::new(),
+            snapshot_directory: None,
+            seed,
+            generator: RandomGenerator::seed_from_u64(seed),
+        }
+    }
+
+    /// Creates an App using the given configuration
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut environment = match &config.startup_pattern {
+            Some(path) => headless::load(path)?,
+            None => App::f_pentomino(),
+        };
+        environment.set_rule(config.rule);
+
+        let mut app = App::new(environment);
+        app.tick_time = Duration::from_millis(config.tick_rate);
+        app.frame_rate = config.frame_rate.clamp(1, MAX_FRAME_RATE);
+        app.theme = config.theme.clone();
+        app.limits = config.limits;
+        app.snapshot_directory = config.snapshots.clone();
+        if let Some(directory) = &config.catalog {
+            app.catalog.load_directory(directory).map_err(|err| ConfigError::Catalog(directory.clone(), err))?;
+        }
+        for (key, command) in config.keybindings.iter() {
+            let key = keys::parse_key(key).ok_or_else(|| ConfigError::InvalidKey(key.clone()))?;
+            app.key_bindings.insert(key, command.clone());
+        }
+
+        Ok(app)
+    }
+
+    /// Creates an environment with the F-Pentomino
+    fn f_pentomino() -> Environment {
+        let mut environment = crate::Environment::default();
         environment.set_living(&[
             SimCell::new(0, 1), SimCell::new(1, 1),
             SimCell::new(-1, 0), SimCell::new(0, 0),
             SimCell::new(0, -1)]
         );
 
-        let viewport = crate::Viewport::new(-10, 10, 20, 20);
+        environment
+    }
 
-        let show_stats = true;
-        let show_coordinates = false;
-        let last_simulation_time = Duration::from_secs(0);
-        let tick_time = Duration::from_millis(50);
-        let pause = false;
-        let generation = 0;
+    /// Returns the tab being shown
+    fn session(&self) -> &Session {
+        &self.sessions[self.active]
+    }
 
-        App { environment, viewport, show_stats, show_coordinates, pause, generation, last_simulation_time, tick_time }
+    /// Returns the tab being shown, to modify it
+    fn session_mut(&mut self) -> &mut Session {
+        &mut self.sessions[self.active]
+    }
+
+    /// Replaces the environment shown by the App
+    pub fn set_environment(&mut self, environment: Environment) {
+        self.session_mut().set_environment(environment);
+    }
+
+    /// Changes the rule used by the simulation
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.session_mut().environment_mut().set_rule(rule);
+    }
+
+    /// Loads a script of commands that is executed when the application starts
+    pub fn set_script(&mut self, path: &Path) -> io::Result<()> {
+        let script = fs::read_to_string(path)?;
+        self.startup_script = Some((path.to_path_buf(), script));
+
+        Ok(())
+    }
+
+    /// Executes the script given with [App::set_script], if any. Returns true if the script asked
+    /// to quit.
+    pub(crate) fn run_startup_script(&mut self) -> bool {
+        match self.startup_script.take() {
+            Some((path, script)) => self.run_script(&path, &script),
+            None => false,
+        }
+    }
+
+    /// Restarts the random features with the given seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.generator = RandomGenerator::seed_from_u64(seed);
+    }
+
+    /// Returns the seed of the random features
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Replaces the environment with random soups that are reseeded whenever they stagnate
+    pub fn start_screensaver(&mut self) {
+        let seed = self.generator.next_u64();
+        self.session_mut().start_screensaver(seed);
+    }
+
+    /// Returns the current generation of the simulation
+    pub fn generation(&self) -> usize {
+        self.session().generation
+    }
+
+    /// Returns the environment shown by the App
+    pub fn environment(&self) -> &Environment {
+        self.session().environment()
+    }
+
+    /// Returns the amount of open tabs
+    pub fn tab_count(&self) -> usize {
+        self.sessions.len()
     }
-}
 
-impl App {
     /// Starts the application loop
     pub fn run(&mut self) -> Result<(), ApplicationError> {
-        let mut terminal = App::setup_terminal()?;
+        let mut terminal = terminal::setup()?;
         let (tx, rx) = mpsc::channel();
 
         // Run the input thread
-        let initial_tick_time = self.tick_time;
-        let input_thread = thread::spawn(move || App::handle_input(initial_tick_time, tx));
-        let mut current_input = String::default();
-        let mut current_message = String::default();
-
-        // Run the main loop
-        loop {
+        self.tick_state.set_interval(self.tick_time);
+        self.tick_state.set_frame_interval(App::frame_interval(self.frame_rate));
+        let input_tick_state = Arc::clone(&self.tick_state);
+        let key_bindings = self.key_bindings.clone();
+        self.sender = Some(tx.clone());
+        self.worker = Some(Worker::spawn(tx.clone()));
+        let catalog = self.catalog.clone();
+        let complete = move |input: &str| commands::complete(input, &catalog);
+        let input_thread = input::spawn(input_tick_state, key_bindings, Box::new(complete), tx);
+
+        // Run the startup script before the first draw
+        let mut quit = self.run_startup_script();
+
+        // Run the main loop. Ticks only simulate, on the worker thread, and the result is drawn
+        // on the next frame.
+        let mut redraw = true;
+        while !quit {
             // Draw
-            terminal.draw(|rect| {
+            if redraw {
+                self.draw(&mut terminal)?;
+            }
+
+            // Handle input
+            let event = rx.recv()?;
+            redraw = !matches!(event, AppEvent::Tick | AppEvent::Simulated(_));
+            quit = self.handle_event(event);
+        }
+
+        terminal::cleanup(&mut terminal)?;
+        self.sender = None;
+        self.worker = None;
+        drop(rx);
+        input_thread.join().expect("Error closing input");
+
+        Ok(())
+    }
+
+    /// Draws the current tab, the status bar and the input box
+    fn draw(&mut self, terminal: &mut Tui) -> io::Result<()> {
+        terminal.draw(|rect| {
                 let area = rect.size();
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
                         Constraint::Min(4),
+                        Constraint::Length(1),
                         Constraint::Length(4)
                     ].as_ref())
                     .split(area);
@@ -108,227 +433,503 @@ impl App {
                 // SIMULATION VIEWPORT
                 // Resize viewport if necessary
                 let target_area = chunks[0];
-                if target_area.width as usize != self.viewport.width() || target_area.height as usize != self.viewport.height() {
+                let viewport = &mut self.sessions[self.active].viewport;
+                if target_area.width as usize != viewport.width() || target_area.height as usize != viewport.height() {
                     let width = target_area.width as usize;
                     let height = target_area.height as usize;
                     let x = -((width / 2) as i32);
                     let y = (height / 2) as i32;
 
-                    self.viewport = Viewport::new(x, y, width, height);
+                    *viewport = Viewport::new(x, y, width, height);
                 }
 
-                rect.render_widget(self.render_environment(), target_area);
+                let session = self.session();
+                let changes = session.snapshot.as_ref().map(|snapshot| snapshot.diff(session.environment()));
+                rect.render_widget(EnvironmentView::new(&session.viewport, &self.theme).changes(changes.as_ref()), target_area);
+
+                // STATUS BAR
+                rect.render_widget(self.status_bar(), chunks[1]);
 
 
                 // INPUT VIEWPORT
-                let input_block = Paragraph::new(format!("{}\n{}", current_input, current_message))
+                let input_block = Paragraph::new(format!("{}\n{}", self.input, self.message))
                     .block(Block::default()
                         .title("Input")
-                        .borders(Borders::ALL));
-                rect.render_widget(input_block, chunks[1]);
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(self.theme.border.0)));
+                rect.render_widget(input_block, chunks[2]);
+                rect.set_cursor(chunks[2].x + 1 + self.cursor as u16, chunks[2].y + 1);
             })?;
 
-            // Handle input
-            match rx.recv()? {
-                AppEvent::Quit => break,
-                AppEvent::Tick => {
-                    if !self.pause {
-                        let start_instant = Instant::now();
-                        self.environment.simulate();
-                        self.generation += 1;
-                        self.last_simulation_time = start_instant.elapsed();
-                    } else {
-                        self.last_simulation_time = Duration::from_millis(0);
+        Ok(())
+    }
+
+    /// Applies an event to the application. Returns true if the application should quit.
+    fn handle_event(&mut self, event: AppEvent) -> bool {
+        if self.simulating > 0 && event.needs_environment() {
+            self.deferred.push_back(event);
+            return false;
+        }
+
+        match event {
+            AppEvent::Quit => return true,
+            AppEvent::Tick => {
+                // Every tab keeps running, so they can be compared. The tabs keep their current
+                // generation to draw until the worker sends back the cells that changed.
+                for i in 0..self.sessions.len() {
+                    if self.pause {
+                        self.sessions[i].last_simulation_time = Duration::from_millis(0);
+                        continue;
                     }
 
-                    self.environment.fill_viewport(&mut self.viewport);
+                    let job = self.sessions[i].job(i, self.generations_per_tick, self.limits);
+                    let sent = match &self.worker {
+                        Some(worker) => worker.send(job),
+                        None => Err(job),
+                    };
+                    match sent {
+                        Ok(_) => self.simulating += 1,
+                        Err(job) => {
+                            let exceeded = self.sessions[i].simulate_with(job.simulation, job.generations, &job.limits);
+                            self.check_limits(i, exceeded);
+                        }
+                    }
                 }
-                AppEvent::PartialInput(input) => {
-                    current_input = input;
-                    current_message.clear();
+
+                if self.simulating == 0 {
+                    self.tick_state.tick_done();
                 }
-                AppEvent::ErrorInput(input, message) => {
-                    current_input = input;
-                    current_message = message;
+            }
+            AppEvent::Simulated(simulated) => {
+                let Simulated { tab, simulation, update, exceeded } = *simulated;
+                self.sessions[tab].finish_job(simulation, update);
+                self.check_limits(tab, exceeded);
+                self.simulating -= 1;
+
+                if self.simulating == 0 {
+                    self.tick_state.tick_done();
+                    while let Some(event) = self.deferred.pop_front() {
+                        if self.handle_event(event) {
+                            return true;
+                        }
+                    }
                 }
-                AppEvent::Load(mut file) => {
-                    // Try loading the file
-                    let mut environment_data = String::new();
-                    let _ = file.read_to_string(&mut environment_data);
-                    let loaded_env = serde_yaml::from_str::<Environment>(&environment_data);
-                    if let Ok(loaded_env) = loaded_env {
-                        self.environment = loaded_env;
-                        self.generation = 0;
-                        current_message = String::from("Loaded state from file");
+            }
+            AppEvent::Frame => {
+                self.session_mut().fill_viewport();
+                self.tick_state.frame_done();
+            }
+            AppEvent::FrameRate(frame_rate) => {
+                self.frame_rate = frame_rate.clamp(1, MAX_FRAME_RATE);
+                self.tick_state.set_frame_interval(App::frame_interval(self.frame_rate));
+                self.message = format!("Drawing {} frames per second", self.frame_rate);
+            }
+            AppEvent::Step(generations) => {
+                if self.pause {
+                    for i in 0..self.sessions.len() {
+                        let exceeded = self.sessions[i].simulate(generations, &self.limits);
+                        self.check_limits(i, exceeded);
                     }
+                    self.session_mut().fill_viewport();
+                } else {
+                    self.message = String::from("Pause the simulation to step through it");
                 }
-                AppEvent::Save(mut file) => {
-                    let environment_data = serde_yaml::to_string(&self.environment);
-                    if let Ok(environment_data) = environment_data {
-                        let result = file.write_all(environment_data.as_bytes());
-                        match result {
-                            Ok(_) => current_message = String::from("Written state to file"),
-                            Err(err) => current_message = format!("Unable to write state to file. Error: {}", err)
-                        }
-                    } else {}
+            }
+            AppEvent::Speed(change) => {
+                self.change_speed(change);
+                self.tick_state.set_interval(self.tick_time);
+            }
+            AppEvent::PartialInput(input, cursor) => {
+                self.input = input;
+                self.cursor = cursor;
+                self.message.clear();
+            }
+            AppEvent::ErrorInput(input, message) => {
+                self.cursor = input.chars().count();
+                self.input = input;
+                self.message = message;
+            }
+            AppEvent::Load(path) => match self.load(&path) {
+                Ok(()) => self.message = format!("Loaded {}", path.display()),
+                Err(err) => self.message = format!("Unable to load {}. Error: {}", path.display(), err),
+            },
+            AppEvent::LoadImage(path, options) => match image::load(&path, &options) {
+                Ok(environment) => {
+                    self.set_environment(environment);
+                    self.message = format!("Loaded {} cells from {}", self.environment().get_living_count(), path.display());
+                }
+                Err(err) => self.message = format!("Unable to load {}. Error: {}", path.display(), err),
+            },
+            AppEvent::Place(name, position) => self.place(&name, position),
+            AppEvent::Fetch(url) => {
+                // Downloads run on their own thread to keep the simulation going
+                self.message = format!("Downloading {}...", url);
+                let sender = self.sender.clone();
+                thread::spawn(move || {
+                    let event = match headless::fetch(&url) {
+                        Ok(environment) => AppEvent::Fetched(url, environment),
+                        Err(err) => AppEvent::Message(format!("Unable to load {}. Error: {}", url, err)),
+                    };
+                    if let Some(sender) = sender {
+                        let _ = sender.send(event);
+                    }
+                });
+            }
+            AppEvent::Fetched(url, environment) => {
+                self.set_environment(environment);
+                self.message = format!("Loaded pattern from {}", url);
+            }
+            AppEvent::Save(mut file) => {
+                let saved = SavedSession { environment: self.session().environment().clone(), seed: Some(self.seed), generation: None };
+                let environment_data = serde_yaml::to_string(&saved);
+                if let Ok(environment_data) = environment_data {
+                    let result = file.write_all(environment_data.as_bytes());
+                    match result {
+                        Ok(_) => self.message = String::from("Written state to file"),
+                        Err(err) => self.message = format!("Unable to write state to file. Error: {}", err)
+                    }
+                } else {}
+            }
+            AppEvent::Export(request, frames, options) => {
+                // Exports run on their own thread, so they are not limited by the tick rate
+                let session = self.session();
+                let environment = session.environment().clone();
+                let viewport = Viewport::new(session.viewport.x(), session.viewport.y(), session.viewport.width(), session.viewport.height());
+                let sender = self.sender.clone();
+                thread::spawn(move || {
+                    let result = match request {
+                        Export::Gif(file) => export::record_gif(&environment, &viewport, frames, &options, io::BufWriter::new(file)),
+                        Export::Apng(file) => export::record_apng(&environment, &viewport, frames, &options, io::BufWriter::new(file)),
+                        Export::PngFrames(directory) => export::write_png_frames(&environment, &viewport, frames, &options, &directory),
+                    };
+                    let message = match result {
+                        Ok(_) => format!("Exported {} frames", frames),
+                        Err(err) => format!("Unable to export. Error: {}", err)
+                    };
+                    if let Some(sender) = sender {
+                        let _ = sender.send(AppEvent::Message(message));
+                    }
+                });
+                self.message = String::from("Exporting...");
+            }
+            AppEvent::Script(path, script) => return self.run_script(&path, &script),
+            AppEvent::Message(message) => self.message = message,
+            AppEvent::ShowStats => self.show_stats = !self.show_stats,
+            AppEvent::ShowCoordinates => self.show_coordinates = !self.show_coordinates,
+            AppEvent::Pause => self.pause = !self.pause,
+            AppEvent::Tab(change) => self.change_tab(change),
+            AppEvent::Rule(rule) => {
+                self.set_rule(rule);
+                self.message = format!("Changed the rule to {}", rule);
+            }
+            AppEvent::Clear => {
+                self.session_mut().environment_mut().clear();
+                self.message = String::from("Cleared the environment");
+            }
+            AppEvent::Reset => {
+                self.session_mut().reset();
+                self.message = String::from("Restored the loaded environment");
+            }
+            AppEvent::Snap(Some(slot)) => self.snap(slot),
+            AppEvent::Snap(None) => {
+                let mut slots: Vec<&str> = self.slots.keys().map(String::as_str).collect();
+                slots.sort();
+                self.message = match slots.is_empty() {
+                    true => String::from("No snapshots stored"),
+                    false => format!("Snapshots: {}", slots.join(", ")),
+                };
+            }
+            AppEvent::Restore(slot) => self.restore(&slot),
+            AppEvent::Limits(change) => {
+                match change {
+                    Some((Limit::Population, value)) => self.limits.max_population = value,
+                    Some((Limit::Size, value)) => self.limits.max_size = value,
+                    None => {}
+                }
+
+                let show = |value: usize| if value == 0 { String::from("off") } else { value.to_string() };
+                self.message = format!("Limits: population {}, size {}",
+                                       show(self.limits.max_population), show(self.limits.max_size));
+            }
+            AppEvent::Diff => {
+                let session = self.session_mut();
+                if session.snapshot.take().is_some() {
+                    self.message = String::from("Stopped comparing with the snapshot");
+                } else {
+                    session.snapshot = Some(session.environment().clone());
+                    self.message = format!("Comparing with generation {}. New cells are highlighted, dead ones marked", session.generation);
+                }
+            }
+            AppEvent::Screensaver => {
+                if self.session_mut().screensaver.take().is_some() {
+                    self.message = String::from("Screensaver stopped");
+                } else {
+                    self.start_screensaver();
+                    self.message = format!("Screensaver started, seed {}", self.seed);
                 }
-                AppEvent::ShowStats => self.show_stats = !self.show_stats,
-                AppEvent::ShowCoordinates => self.show_coordinates = !self.show_coordinates,
-                AppEvent::Pause => self.pause = !self.pause,
             }
         }
 
-        App::cleanup_terminal(&mut terminal)?;
-        drop(rx);
-        input_thread.join().expect("Error closing input");
+        false
+    }
 
-        Ok(())
+    /// Stores a snapshot of the current tab in a slot, replacing the previous one. It is also
+    /// written to the snapshot directory, if enabled.
+    fn snap(&mut self, slot: String) {
+        let session = self.session();
+        let snapshot = SavedSession {
+            environment: session.environment().clone(),
+            seed: None,
+            generation: Some(session.generation),
+        };
+        self.message = format!("Stored generation {} in slot {}", session.generation, slot);
+
+        if let Some(directory) = &self.snapshot_directory {
+            let path = directory.join(format!("{}.con", slot));
+            let result = serde_yaml::to_string(&snapshot)
+                .map_err(|err| err.to_string())
+                .and_then(|data| fs::create_dir_all(directory)
+                    .and_then(|_| fs::write(&path, data))
+                    .map_err(|err| err.to_string()));
+            if let Err(err) = result {
+                self.message = format!("Stored slot {} in memory, unable to write {}. Error: {}", slot, path.display(), err);
+            }
+        }
+
+        self.slots.insert(slot, snapshot);
     }
 
-    /// Set's up the terminal so it is ready to be written by the UI
-    fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>, ApplicationError> {
-        // Setup the terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-        terminal.clear()?;
+    /// Replaces the current tab with a snapshot. Slots that aren't in memory are read from the
+    /// snapshot directory, if enabled.
+    fn restore(&mut self, slot: &str) {
+        if !self.slots.contains_key(slot) {
+            let stored = self.snapshot_directory.as_ref()
+                .and_then(|directory| fs::read_to_string(directory.join(format!("{}.con", slot))).ok())
+                .and_then(|data| serde_yaml::from_str::<SavedSession>(&data).ok());
+            match stored {
+                Some(snapshot) => {
+                    self.slots.insert(slot.to_string(), snapshot);
+                }
+                None => {
+                    self.message = format!("Unknown snapshot slot: {}", slot);
+                    return;
+                }
+            }
+        }
 
-        Ok(terminal)
+        let snapshot = &self.slots[slot];
+        self.sessions[self.active].restore(snapshot);
+        self.session_mut().fill_viewport();
+        self.message = format!("Restored slot {}, generation {}", slot, self.session().generation);
     }
 
-    /// Clean's up the terminal for the following process
-    fn cleanup_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<(), ApplicationError> {
-        disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-        terminal.show_cursor()?;
+    /// Loads a file into the current tab. Saved sessions also restore the seed they were run
+    /// with; any other pattern file goes through [headless::load].
+    fn load(&mut self, path: &Path) -> Result<(), HeadlessError> {
+        if PatternFormat::from_path(path) == Some(PatternFormat::Yaml) {
+            let data = fs::read_to_string(path)?;
+            let loaded: SavedSession = serde_yaml::from_str(&data).map_err(PatternError::from)?;
+            self.set_environment(loaded.environment);
+            if let Some(seed) = loaded.seed {
+                self.set_seed(seed);
+            }
+        } else {
+            self.set_environment(headless::load(path)?);
+        }
 
         Ok(())
     }
 
-    /// Handle input and events
-    fn handle_input(tick_rate: Duration, sender: Sender<AppEvent>) {
-        let mut last_tick = Instant::now();
-        let mut current_input = String::default();
-
-        loop {
-            let timeout = tick_rate
-                .checked_sub(last_tick.elapsed())
-                .unwrap_or_else(|| Duration::from_secs(0));
-
-            if event::poll(timeout).expect("Poll not working") {
-                // Send the key events
-                if let Event::Key(key) = event::read().expect("Can't read events") {
-                    let result = match (key.code, key.kind) {
-                        (KeyCode::Esc, KeyEventKind::Press) => sender.send(AppEvent::Quit),
-                        // (KeyCode::Char('c'), KeyEventKind::Press) => sender.send(AppEvent::ShowCoordinates),
-                        // (KeyCode::Char('s'), KeyEventKind::Press) => sender.send(AppEvent::ShowStats),
-                        // (KeyCode::Char(' '), KeyEventKind::Press) => sender.send(AppEvent::Pause),
-                        (KeyCode::Char(c), KeyEventKind::Press) => {
-                            current_input.push(c);
-                            sender.send(AppEvent::PartialInput(current_input.clone()))
-                        }
-                        (KeyCode::Backspace, KeyEventKind::Press) => {
-                            current_input.pop();
-                            sender.send(AppEvent::PartialInput(current_input.clone()))
-                        }
-                        (KeyCode::Enter, KeyEventKind::Press) => {
-                            if !current_input.is_empty() {
-                                let message = App::parse_input(&current_input);
-                                current_input.clear();
-                                sender.send(message)
-                            } else {
-                                // Ignore enter
-                                sender.send(AppEvent::PartialInput(String::default()))
-                            }
-                        }
-                        _ => Ok(())
-                    };
+    /// Places a pattern of the catalog. Without a position the pattern is centered in the
+    /// viewport.
+    fn place(&mut self, name: &str, position: Option<(i32, i32)>) {
+        let Some(entry) = self.catalog.find(name) else {
+            self.message = format!("Unknown pattern: {}", name);
+            return;
+        };
 
-                    // Break on an error
-                    if result.is_err() {
-                        break;
-                    }
-                }
+        let pattern = match entry.environment() {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                self.message = format!("Invalid pattern {}. Error: {}", entry.name, err);
+                return;
             }
+        };
 
-            if last_tick.elapsed() >= tick_rate {
-                if let Ok(_) = sender.send(AppEvent::Tick) {
-                    last_tick = Instant::now();
-                }
+        let session = &mut self.sessions[self.active];
+        let viewport = &session.viewport;
+        let top_left = match (position, pattern.bounding_box()) {
+            (Some((x, y)), _) => SimCell::new(x, y),
+            (None, Some((min, max))) => SimCell::new(
+                viewport.x() + (viewport.width() as i32 - (max.x - min.x + 1)) / 2,
+                viewport.y() - (viewport.height() as i32 - (max.y - min.y + 1)) / 2,
+            ),
+            (None, None) => SimCell::new(viewport.x(), viewport.y()),
+        };
+        session.environment_mut().place(&pattern, top_left);
+        session.fill_viewport();
+        self.message = format!("Placed {} at {}", entry.name, top_left);
+    }
+
+    /// Sends every environment to the worker thread again, after the indices of the tabs change
+    fn unsync_tabs(&mut self) {
+        self.sessions.iter_mut().for_each(Session::unsync);
+    }
+
+    /// Opens, closes or switches between tabs
+    fn change_tab(&mut self, change: TabChange) {
+        let count = self.sessions.len();
+        match change {
+            TabChange::New => {
+                let session = self.session().duplicate();
+                self.sessions.insert(self.active + 1, session);
+                self.unsync_tabs();
+                self.active += 1;
+            }
+            TabChange::Close if count == 1 => {
+                self.message = String::from("Unable to close the last tab");
+                return;
+            }
+            TabChange::Close => {
+                self.sessions.remove(self.active);
+                self.unsync_tabs();
+                self.active = self.active.min(count - 2);
+            }
+            TabChange::Next => self.active = (self.active + 1) % count,
+            TabChange::Previous => self.active = (self.active + count - 1) % count,
+            TabChange::Select(tab) if (1..=count).contains(&tab) => self.active = tab - 1,
+            TabChange::Select(tab) => {
+                self.message = format!("There is no tab {}", tab);
+                return;
             }
         }
+
+        self.session_mut().fill_viewport();
+        self.message = format!("Tab {} of {}", self.active + 1, self.sessions.len());
     }
 
-    /// Parses current input and returns a message to send
-    fn parse_input(input: &str) -> AppEvent {
-        let mut chunks = input.split(' ');
-
-        if let Some(instruction) = chunks.next() {
-            match instruction {
-                "stats" | "t" => AppEvent::ShowStats,
-                "coord" | "c" => AppEvent::ShowCoordinates,
-                "pause" | "p" => AppEvent::Pause,
-                "quit" | "q" => AppEvent::Quit,
-                "load" | "l" => {
-                    if let Some(path) = chunks.next() {
-                        let file = fs::File::open(path);
-                        if let Ok(file) = file {
-                            AppEvent::Load(file)
-                        } else {
-                            AppEvent::ErrorInput(input.to_string(), String::from("File not found"))
-                        }
-                    } else {
-                        AppEvent::ErrorInput(input.to_string(), String::from("File not specified"))
-                    }
+    /// Executes each line of a script as if it was typed in the input box. Empty lines and lines
+    /// starting with `#` are skipped. The script stops at the first invalid command. Returns true
+    /// if the script asked to quit.
+    fn run_script(&mut self, path: &Path, script: &str) -> bool {
+        match self.execute_script(path, script, 0) {
+            Ok((executed, quit)) => {
+                self.message = format!("Executed {} commands from {}", executed, path.display());
+                quit
+            }
+            Err(message) => {
+                self.message = message;
+                false
+            }
+        }
+    }
+
+    /// Executes the script and the scripts it runs. Returns the amount of executed commands and
+    /// whether the script asked to quit.
+    fn execute_script(&mut self, path: &Path, script: &str, depth: usize) -> Result<(usize, bool), String> {
+        if depth >= MAX_SCRIPT_DEPTH {
+            return Err(format!("{}: Scripts nested too deeply", path.display()));
+        }
+
+        let mut executed = 0;
+        for (number, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let quit = match commands::parse(line) {
+                AppEvent::ErrorInput(_, message) => return Err(format!("{}:{}: {}", path.display(), number + 1, message)),
+                AppEvent::Script(path, script) => {
+                    let (nested, quit) = self.execute_script(&path, &script, depth + 1)?;
+                    executed += nested;
+                    quit
                 }
-                "save" | "s" => {
-                    if let Some(path) = chunks.next() {
-                        let file = fs::File::create(path);
-                        if let Ok(file) = file {
-                            AppEvent::Save(file)
-                        } else {
-                            AppEvent::ErrorInput(input.to_string(), format!("Unable to create file: {}", path))
-                        }
-                    } else {
-                        AppEvent::ErrorInput(input.to_string(), String::from("File not specified"))
-                    }
+                event => {
+                    executed += 1;
+                    self.handle_event(event)
                 }
-                _ => AppEvent::ErrorInput(input.to_string(), String::from("Unknown instruction"))
+            };
+            if quit {
+                return Ok((executed, true));
             }
-        } else {
-            AppEvent::ErrorInput(input.to_string(), String::from("Invalid instruction"))
         }
+
+        Ok((executed, false))
     }
 
-    /// Render the environment
-    fn render_environment(&mut self) -> Paragraph {
-        // Create title
-        let coordinates = if self.show_coordinates {
-            format!(" -- X={}, Y={}, W={}, H={}",
-                    self.viewport.x(),
-                    self.viewport.y(),
-                    self.viewport.width(),
-                    self.viewport.height())
-        } else {
-            String::from("")
-        };
+    /// Changes the tick interval, or the amount of generations simulated per tick when running
+    /// at max speed
+    fn change_speed(&mut self, change: SpeedChange) {
+        let at_max_speed = self.generations_per_tick > 1;
+        match change {
+            SpeedChange::Interval(interval) => {
+                self.tick_time = interval;
+                self.generations_per_tick = 1;
+            }
+            SpeedChange::Max(generations) => {
+                self.tick_time = Duration::ZERO;
+                self.generations_per_tick = generations.max(1);
+            }
+            SpeedChange::Faster if at_max_speed => self.generations_per_tick *= 2,
+            SpeedChange::Faster if self.tick_time <= FASTEST_TICK => {
+                self.tick_time = Duration::ZERO;
+                self.generations_per_tick = 2;
+            }
+            SpeedChange::Faster => self.tick_time = (self.tick_time / 2).max(FASTEST_TICK),
+            SpeedChange::Slower if at_max_speed => {
+                self.generations_per_tick /= 2;
+                if self.generations_per_tick <= 1 {
+                    self.generations_per_tick = 1;
+                    self.tick_time = FASTEST_TICK;
+                }
+            }
+            SpeedChange::Slower => self.tick_time = (self.tick_time * 2).clamp(FASTEST_TICK, SLOWEST_TICK),
+        }
+    }
 
-        let stats = if self.show_stats {
-            format!(" -- Time={}µm, Living={}", self.last_simulation_time.as_micros(), self.environment.get_living_count())
-        } else {
-            String::default()
-        };
+    /// Returns the time between frames for the given frames per second
+    fn frame_interval(frame_rate: u64) -> Duration {
+        Duration::from_secs(1) / frame_rate.max(1) as u32
+    }
 
-        let title = format!("Conway's Game of Life -- GEN={}{}{}",
-                            self.generation, coordinates, stats);
+    /// Pauses the simulation if a tab exceeded the limits, warning about it
+    fn check_limits(&mut self, tab: usize, exceeded: Option<String>) {
+        if let Some(exceeded) = exceeded {
+            self.pause = true;
+            self.message = if self.sessions.len() > 1 {
+                format!("Paused, tab {} has {}. Change the limits with the limits command", tab + 1, exceeded)
+            } else {
+                format!("Paused, the environment has {}. Change the limits with the limits command", exceeded)
+            };
+        }
+    }
 
-        // Create paragraph
-        Paragraph::new(self.viewport.to_string())
-            .block(Block::default()
-                .title(title)
-                .title_alignment(Alignment::Center)
-                .borders(Borders::ALL))
+    /// Returns the speed of the simulation, as shown in the status bar
+    fn speed(&self) -> String {
+        if self.generations_per_tick > 1 {
+            format!("max/{}", self.generations_per_tick)
+        } else {
+            format!("{}ms", self.tick_time.as_millis())
+        }
+    }
+
+    /// Creates the status bar with the current state of the simulation
+    fn status_bar(&mut self) -> StatusBar {
+        let rate = self.show_stats.then(|| self.session_mut().rate.per_second(Instant::now()));
+        let session = self.session();
+        let viewport = &session.viewport;
+        StatusBar {
+            generation: session.generation,
+            rule: session.environment().rule(),
+            speed: self.speed(),
+            paused: self.pause,
+            stats: self.show_stats.then(|| (session.environment().get_living_count(), session.last_simulation_time)),
+            rate,
+            coordinates: self.show_coordinates.then(|| (viewport.x(), viewport.y(), viewport.width(), viewport.height())),
+            tab: (self.sessions.len() > 1).then_some((self.active + 1, self.sessions.len())),
+            diff: session.snapshot.as_ref().map(|snapshot| snapshot.diff(session.environment()).len()),
+            style: Style::default().fg(self.theme.border.0),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,501 @@
+//! Registry of the commands that can be typed in the input box. It is used both to parse the
+//! input and to complete it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tui_app::editor::{split_last_word, tokenize, Completion};
+
+use crate::Rule;
+use crate::catalog::Catalog;
+use crate::export::AnimationOptions;
+use crate::headless;
+use crate::image::{self, ImageOptions};
+
+use super::{AppEvent, Export, Limit, SpeedChange, TabChange};
+
+/// Generations drawn at max speed when not specified
+const DEFAULT_MAX_SPEED_GENERATIONS: usize = 10;
+
+/// Kind of argument expected by a command, used to complete it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Argument {
+    Path,
+    Number,
+    /// Name of a pattern in the catalog
+    Pattern,
+}
+
+/// A command that can be typed in the input box
+pub(crate) struct Command {
+    pub(crate) name: &'static str,
+    pub(crate) alias: &'static str,
+    pub(crate) arguments: &'static [Argument],
+    pub(crate) usage: &'static str,
+    /// Creates the event of the command from its arguments, or returns an error message
+    parse: fn(&[&str]) -> Result<AppEvent, String>,
+}
+
+/// Every command known by the application
+pub(crate) const COMMANDS: &[Command] = &[
+    Command {
+        name: "stats",
+        alias: "t",
+        arguments: &[],
+        usage: "stats -> Shows/Hides statistics",
+        parse: |_| Ok(AppEvent::ShowStats),
+    },
+    Command {
+        name: "coord",
+        alias: "c",
+        arguments: &[],
+        usage: "coord -> Shows/Hides current coordinates",
+        parse: |_| Ok(AppEvent::ShowCoordinates),
+    },
+    Command {
+        name: "pause",
+        alias: "p",
+        arguments: &[],
+        usage: "pause -> Pause/Unpause the simulation",
+        parse: |_| Ok(AppEvent::Pause),
+    },
+    Command {
+        name: "step",
+        alias: "n",
+        arguments: &[Argument::Number],
+        usage: "step [generations] -> Advance the paused simulation (also the . key)",
+        parse: parse_step,
+    },
+    Command {
+        name: "speed",
+        alias: "v",
+        arguments: &[Argument::Number, Argument::Number],
+        usage: "speed <ms> | speed max [generations] -> Change the time between generations",
+        parse: parse_speed,
+    },
+    Command {
+        name: "faster",
+        alias: "]",
+        arguments: &[],
+        usage: "faster -> Halve the time between generations",
+        parse: |_| Ok(AppEvent::Speed(SpeedChange::Faster)),
+    },
+    Command {
+        name: "slower",
+        alias: "[",
+        arguments: &[],
+        usage: "slower -> Double the time between generations",
+        parse: |_| Ok(AppEvent::Speed(SpeedChange::Slower)),
+    },
+    Command {
+        name: "fps",
+        alias: "i",
+        arguments: &[Argument::Number],
+        usage: "fps <n> -> Change the frames drawn per second, independent of the simulation speed",
+        parse: parse_fps,
+    },
+    Command {
+        name: "rule",
+        alias: "u",
+        arguments: &[],
+        usage: "rule <B/S> -> Change the rule of the current tab, e.g. rule B36/S23",
+        parse: parse_rule,
+    },
+    Command {
+        name: "tab",
+        alias: "b",
+        arguments: &[],
+        usage: "tab [new|close|next|prev|<number>] -> Open, close or switch between tabs",
+        parse: parse_tab,
+    },
+    Command {
+        name: "screensaver",
+        alias: "z",
+        arguments: &[],
+        usage: "screensaver -> Start/Stop reseeding random soups whenever they stagnate",
+        parse: |_| Ok(AppEvent::Screensaver),
+    },
+    Command {
+        name: "diff",
+        alias: "d",
+        arguments: &[],
+        usage: "diff -> Start/Stop highlighting the changes since the current generation",
+        parse: |_| Ok(AppEvent::Diff),
+    },
+    Command {
+        name: "clear",
+        alias: "e",
+        arguments: &[],
+        usage: "clear -> Kill every cell of the current tab",
+        parse: |_| Ok(AppEvent::Clear),
+    },
+    Command {
+        name: "reset",
+        alias: "w",
+        arguments: &[],
+        usage: "reset -> Restore the loaded environment and restart the generation count",
+        parse: |_| Ok(AppEvent::Reset),
+    },
+    Command {
+        name: "snap",
+        alias: "k",
+        arguments: &[],
+        usage: "snap [slot] -> Store a snapshot of the current tab in a slot, or list the slots",
+        parse: |arguments| match arguments.first() {
+            Some(slot) => Ok(AppEvent::Snap(Some(parse_slot(slot)?))),
+            None => Ok(AppEvent::Snap(None)),
+        },
+    },
+    Command {
+        name: "restore",
+        alias: "o",
+        arguments: &[],
+        usage: "restore <slot> -> Replace the current tab with a snapshot",
+        parse: |arguments| {
+            let slot = arguments.first().ok_or_else(|| String::from("Slot not specified"))?;
+            Ok(AppEvent::Restore(parse_slot(slot)?))
+        },
+    },
+    Command {
+        name: "limits",
+        alias: "m",
+        arguments: &[],
+        usage: "limits [population|size <n|off>] -> Show or change the limits that pause the simulation",
+        parse: parse_limits,
+    },
+    Command {
+        name: "quit",
+        alias: "q",
+        arguments: &[],
+        usage: "quit -> Quit the simulation",
+        parse: |_| Ok(AppEvent::Quit),
+    },
+    Command {
+        name: "load",
+        alias: "l",
+        arguments: &[Argument::Path, Argument::Number],
+        usage: "load <path|url> [scale] [dither|threshold] -> Load an environment, download a pattern or convert an image",
+        parse: parse_load,
+    },
+    Command {
+        name: "save",
+        alias: "s",
+        arguments: &[Argument::Path],
+        usage: "save <path> -> Save the current environment",
+        parse: parse_save,
+    },
+    Command {
+        name: "record",
+        alias: "r",
+        arguments: &[Argument::Path, Argument::Number, Argument::Number],
+        usage: "record <file.gif|file.png> <frames> [scale] -> Record an animation",
+        parse: parse_record,
+    },
+    Command {
+        name: "frames",
+        alias: "f",
+        arguments: &[Argument::Path, Argument::Number, Argument::Number],
+        usage: "frames <directory> <frames> [scale] -> Write numbered PNG frames",
+        parse: parse_frames,
+    },
+    Command {
+        name: "place",
+        alias: "a",
+        arguments: &[Argument::Pattern, Argument::Number, Argument::Number],
+        usage: "place <name> [x y] -> Add a pattern of the catalog, e.g. place \"glider gun\"",
+        parse: parse_place,
+    },
+    Command {
+        name: "run",
+        alias: "x",
+        arguments: &[Argument::Path],
+        usage: "run <script> -> Execute the commands in a file, one per line",
+        parse: parse_run,
+    },
+];
+
+/// Finds a command by its name or alias
+pub(crate) fn find(instruction: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|c| c.name == instruction || c.alias == instruction)
+}
+
+/// Parses the input and returns the event to send
+pub(crate) fn parse(input: &str) -> AppEvent {
+    let words = match tokenize(input) {
+        Ok(words) => words,
+        Err(message) => return AppEvent::ErrorInput(input.to_string(), message),
+    };
+    let mut words = words.iter().map(String::as_str);
+
+    let Some(instruction) = words.next() else {
+        return AppEvent::ErrorInput(input.to_string(), String::from("Invalid instruction"));
+    };
+    let Some(command) = find(instruction) else {
+        return AppEvent::ErrorInput(input.to_string(), String::from("Unknown instruction"));
+    };
+
+    let arguments: Vec<&str> = words.collect();
+    match (command.parse)(&arguments) {
+        Ok(event) => event,
+        Err(message) => AppEvent::ErrorInput(input.to_string(), message),
+    }
+}
+
+fn parse_load(arguments: &[&str]) -> Result<AppEvent, String> {
+    let path = arguments.first().ok_or_else(|| String::from("File not specified"))?;
+    if headless::is_url(path) {
+        return Ok(AppEvent::Fetch(path.to_string()));
+    }
+
+    if image::is_image(Path::new(path)) {
+        return parse_image(path, &arguments[1..]);
+    }
+
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(String::from("File not found"));
+    }
+
+    Ok(AppEvent::Load(path))
+}
+
+/// Images take the pixels per cell, and either `dither` or the threshold of the living shades
+fn parse_image(path: &str, arguments: &[&str]) -> Result<AppEvent, String> {
+    let usage = || String::from("Usage: load <image> [scale] [dither|threshold]");
+    let mut options = ImageOptions::default();
+    if let Some(scale) = arguments.first() {
+        options.scale = scale.parse().ok().filter(|&scale| scale > 0).ok_or_else(usage)?;
+    }
+    match arguments.get(1) {
+        Some(&"dither") => options.dither = true,
+        Some(threshold) => options.threshold = threshold.parse().map_err(|_| usage())?,
+        None => {}
+    }
+
+    Ok(AppEvent::LoadImage(PathBuf::from(path), options))
+}
+
+fn parse_save(arguments: &[&str]) -> Result<AppEvent, String> {
+    let path = arguments.first().ok_or_else(|| String::from("File not specified"))?;
+    let file = fs::File::create(path).map_err(|_| format!("Unable to create file: {}", path))?;
+
+    Ok(AppEvent::Save(file))
+}
+
+/// Patterns are placed at the given position, or at the center of the viewport
+fn parse_place(arguments: &[&str]) -> Result<AppEvent, String> {
+    let usage = || String::from("Usage: place <name> [x y]");
+    let position = match arguments {
+        [_] => None,
+        [_, x, y] => Some((x.parse::<i32>().map_err(|_| usage())?, y.parse::<i32>().map_err(|_| usage())?)),
+        _ => return Err(usage()),
+    };
+
+    Ok(AppEvent::Place(arguments[0].to_string(), position))
+}
+
+fn parse_rule(arguments: &[&str]) -> Result<AppEvent, String> {
+    let rule = arguments.first().ok_or_else(|| String::from("Usage: rule <B/S>"))?;
+    let rule = rule.parse::<Rule>().map_err(|_| format!("Invalid rule: {}", rule))?;
+
+    Ok(AppEvent::Rule(rule))
+}
+
+/// Without arguments the next tab is shown
+fn parse_tab(arguments: &[&str]) -> Result<AppEvent, String> {
+    let change = match arguments.first().copied() {
+        Some("new") => TabChange::New,
+        Some("close") => TabChange::Close,
+        Some("next") | None => TabChange::Next,
+        Some("prev") | Some("previous") => TabChange::Previous,
+        Some(tab) => TabChange::Select(tab.parse::<usize>()
+            .map_err(|_| String::from("Usage: tab [new|close|next|prev|<number>]"))?),
+    };
+
+    Ok(AppEvent::Tab(change))
+}
+
+/// Limits are changed by name, with 0 or `off` disabling them
+fn parse_limits(arguments: &[&str]) -> Result<AppEvent, String> {
+    const USAGE: &str = "Usage: limits [population|size <n|off>]";
+    let limit = match arguments.first().copied() {
+        None => return Ok(AppEvent::Limits(None)),
+        Some("population") | Some("pop") => Limit::Population,
+        Some("size") => Limit::Size,
+        Some(_) => return Err(String::from(USAGE)),
+    };
+
+    let value = match arguments.get(1).copied() {
+        Some("off") => 0,
+        Some(value) => value.parse::<usize>().map_err(|_| String::from(USAGE))?,
+        None => return Err(String::from(USAGE)),
+    };
+
+    Ok(AppEvent::Limits(Some((limit, value))))
+}
+
+/// Checks the name of a snapshot slot, which is also used as a file name
+fn parse_slot(slot: &str) -> Result<String, String> {
+    if slot.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        Ok(slot.to_string())
+    } else {
+        Err(String::from("Slot names can only have letters, numbers, - and _"))
+    }
+}
+
+fn parse_run(arguments: &[&str]) -> Result<AppEvent, String> {
+    let path = arguments.first().ok_or_else(|| String::from("File not specified"))?;
+    let script = fs::read_to_string(path).map_err(|_| String::from("File not found"))?;
+
+    Ok(AppEvent::Script(PathBuf::from(path), script))
+}
+
+fn parse_step(arguments: &[&str]) -> Result<AppEvent, String> {
+    let generations = match arguments.first() {
+        Some(generations) => generations.parse::<usize>().map_err(|_| String::from("Invalid amount of generations"))?,
+        None => 1,
+    };
+
+    Ok(AppEvent::Step(generations))
+}
+
+/// Speed is either an interval in milliseconds, or `max` followed by the amount of generations
+/// simulated between each redraw
+fn parse_speed(arguments: &[&str]) -> Result<AppEvent, String> {
+    let usage = || String::from("Usage: speed <ms> | speed max [generations]");
+    let change = match arguments {
+        ["max"] => SpeedChange::Max(DEFAULT_MAX_SPEED_GENERATIONS),
+        ["max", generations, ..] => {
+            let generations = generations.parse::<usize>().ok().filter(|&g| g > 0).ok_or_else(usage)?;
+            SpeedChange::Max(generations)
+        }
+        [milliseconds, ..] => {
+            let milliseconds = milliseconds.parse::<u64>().ok().filter(|&ms| ms > 0).ok_or_else(usage)?;
+            SpeedChange::Interval(Duration::from_millis(milliseconds))
+        }
+        [] => return Err(usage()),
+    };
+
+    Ok(AppEvent::Speed(change))
+}
+
+fn parse_fps(arguments: &[&str]) -> Result<AppEvent, String> {
+    match arguments {
+        [frames, ..] => frames.parse::<u64>().ok()
+            .filter(|&frames| frames > 0)
+            .map(AppEvent::FrameRate)
+            .ok_or_else(|| String::from("Usage: fps <n>")),
+        [] => Err(String::from("Usage: fps <n>")),
+    }
+}
+
+/// Records use the file extension to choose between GIF and animated PNG
+fn parse_record(arguments: &[&str]) -> Result<AppEvent, String> {
+    let (path, frames, options) = parse_export_arguments(arguments, "Usage: record <file.gif|file.png> <frames> [scale]")?;
+
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    let create = || fs::File::create(path).map_err(|_| format!("Unable to create file: {}", path));
+    let export = match extension.as_deref() {
+        Some("gif") => Export::Gif(create()?),
+        Some("png") => Export::Apng(create()?),
+        _ => return Err(String::from("Recordings must be .gif or .png files")),
+    };
+
+    Ok(AppEvent::Export(export, frames, options))
+}
+
+fn parse_frames(arguments: &[&str]) -> Result<AppEvent, String> {
+    let (path, frames, options) = parse_export_arguments(arguments, "Usage: frames <directory> <frames> [scale]")?;
+
+    Ok(AppEvent::Export(Export::PngFrames(PathBuf::from(path)), frames, options))
+}
+
+/// Parses the `<path> <frames> [scale]` arguments shared by the exports
+fn parse_export_arguments<'a>(arguments: &[&'a str], usage: &str) -> Result<(&'a str, usize, AnimationOptions), String> {
+    let (path, frames) = match arguments {
+        [path, frames, ..] => (*path, frames),
+        _ => return Err(String::from(usage)),
+    };
+
+    let invalid = || String::from("Invalid amount of frames or scale");
+    let frames = frames.parse::<usize>().map_err(|_| invalid())?;
+    let mut options = AnimationOptions::default();
+    if let Some(scale) = arguments.get(2) {
+        options.scale = scale.parse::<usize>().ok().filter(|&s| s > 0).ok_or_else(invalid)?;
+    }
+
+    Ok((path, frames, options))
+}
+
+/// Completes the last word of the input. Command names are completed at the start of the
+/// input, and filesystem paths or the names of the patterns in the catalog for the commands
+/// that expect them.
+pub(crate) fn complete(input: &str, catalog: &Catalog) -> Completion {
+    let (head, word) = split_last_word(input);
+
+    let (candidates, suffix) = if head.trim().is_empty() {
+        let candidates: Vec<String> = COMMANDS.iter()
+            .filter(|c| c.name.starts_with(word))
+            .map(|c| c.name.to_string())
+            .collect();
+
+        // Commands with arguments are followed by a space
+        let suffix = match candidates.as_slice() {
+            [name] if find(name).is_some_and(|c| !c.arguments.is_empty()) => " ",
+            _ => "",
+        };
+        (candidates, suffix)
+    } else {
+        let mut words = head.split_whitespace();
+        let argument = words.next()
+            .and_then(find)
+            .and_then(|c| c.arguments.get(words.count()));
+
+        match argument {
+            Some(Argument::Path) => (complete_path(word), ""),
+            Some(Argument::Pattern) => (complete_pattern(word, catalog), ""),
+            _ => (Vec::new(), ""),
+        }
+    };
+
+    Completion::new(head, word, candidates, suffix)
+}
+
+/// Returns the paths that start with the given prefix. Directories end with `/`.
+fn complete_path(prefix: &str) -> Vec<String> {
+    let (directory, name) = match prefix.rfind('/') {
+        Some(index) => prefix.split_at(index + 1),
+        None => ("", prefix),
+    };
+
+    let Ok(entries) = fs::read_dir(if directory.is_empty() { "." } else { directory }) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            if !file_name.starts_with(name) {
+                return None;
+            }
+
+            let is_directory = entry.file_type().is_ok_and(|t| t.is_dir());
+            Some(format!("{}{}{}", directory, file_name, if is_directory { "/" } else { "" }))
+        })
+        .collect();
+    candidates.sort();
+
+    candidates
+}
+
+/// Returns the names of the patterns in the catalog that start with the given prefix, ignoring
+/// case. Names with spaces are quoted.
+fn complete_pattern(prefix: &str, catalog: &Catalog) -> Vec<String> {
+    let prefix = prefix.trim_start_matches('"').to_lowercase();
+    catalog.entries().iter()
+        .filter(|entry| entry.name.to_lowercase().starts_with(&prefix))
+        .map(|entry| if entry.name.contains(' ') { format!("\"{}\"", entry.name) } else { entry.name.clone() })
+        .collect()
+}
@@ -0,0 +1,264 @@
+//! An environment open in the user interface, shown in its own tab.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Environment, Observer, Viewport};
+use crate::config::Limits;
+use crate::screensaver::Screensaver;
+
+use super::worker::{Job, Update};
+
+/// Contents of a save file: the environment, along with the seed of the random features so the
+/// run can be repeated. Files without a seed are plain environments. Snapshots also record their
+/// generation.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct SavedSession {
+    #[serde(flatten)]
+    pub(crate) environment: Environment,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) generation: Option<usize>,
+}
+
+/// Time over which the simulation rate is averaged
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Measures how many generations are simulated per second, averaged over the last second
+#[derive(Debug)]
+pub(crate) struct RateMeter {
+    /// Generations simulated at each instant within the window, oldest first
+    samples: VecDeque<(Instant, usize)>,
+    /// When the measure started, so the first second isn't underestimated
+    start: Instant,
+}
+
+impl RateMeter {
+    pub(crate) fn new(start: Instant) -> Self {
+        RateMeter { samples: VecDeque::new(), start }
+    }
+
+    /// Records the generations simulated at the given instant
+    pub(crate) fn record(&mut self, instant: Instant, generations: usize) {
+        if generations > 0 {
+            self.samples.push_back((instant, generations));
+        }
+    }
+
+    /// Returns the generations per second at the given instant
+    pub(crate) fn per_second(&mut self, now: Instant) -> f64 {
+        while self.samples.front().is_some_and(|(instant, _)| now.duration_since(*instant) > RATE_WINDOW) {
+            self.samples.pop_front();
+        }
+
+        let span = now.duration_since(self.start).min(RATE_WINDOW).as_secs_f64();
+        let generations: usize = self.samples.iter().map(|(_, generations)| generations).sum();
+        if span > 0.0 { generations as f64 / span } else { 0.0 }
+    }
+}
+
+/// The part of a session that changes when simulating, besides the environment, so it can be
+/// simulated on another thread
+#[derive(Debug)]
+pub(crate) struct Simulation {
+    pub(crate) generation: usize,
+    pub(crate) screensaver: Option<Screensaver>,
+    /// Size of the viewport, used for the soups of the screensaver
+    size: (usize, usize),
+    /// Time taken by the last run
+    elapsed: Duration,
+    /// Generations simulated by the last run
+    simulated: usize,
+    /// True if the screensaver replaced the environment during the last run
+    pub(crate) reseeded: bool,
+}
+
+impl Simulation {
+    /// Simulates the given amount of generations of the environment, notifying the observer of
+    /// the births and deaths. In screensaver mode the environment is reseeded as soon as it
+    /// stagnates. Stops early if the environment exceeds the limits, returning the exceeded
+    /// limit.
+    pub(crate) fn run<O: Observer>(&mut self, environment: &mut Environment, generations: usize, limits: &Limits,
+                                   observer: &mut O) -> Option<String> {
+        let start_instant = Instant::now();
+        let mut exceeded = None;
+        self.simulated = 0;
+        self.reseeded = false;
+        while self.simulated < generations && exceeded.is_none() {
+            environment.simulate_observed(observer);
+            self.generation += 1;
+            self.simulated += 1;
+
+            if let Some(screensaver) = &mut self.screensaver {
+                if screensaver.is_stagnant(environment) {
+                    (screensaver.width, screensaver.height) = self.size;
+                    screensaver.seed(environment);
+                    self.generation = 0;
+                    self.reseeded = true;
+                }
+            }
+
+            exceeded = limits.exceeded(environment);
+        }
+        self.elapsed = start_instant.elapsed();
+
+        exceeded
+    }
+}
+
+/// State of a single tab: its environment along with the view of it
+pub(crate) struct Session {
+    environment: Environment,
+    /// True if the worker thread has the same environment, so it doesn't have to be sent again
+    synced: bool,
+    /// Environment as it was loaded, restored when resetting
+    initial: Environment,
+    pub(crate) viewport: Viewport,
+    pub(crate) generation: usize,
+    pub(crate) last_simulation_time: Duration,
+    /// Reseeds the environment when it stagnates, if enabled
+    pub(crate) screensaver: Option<Screensaver>,
+    /// Environment the current one is compared with, if enabled
+    pub(crate) snapshot: Option<Environment>,
+    /// Generations simulated per second
+    pub(crate) rate: RateMeter,
+}
+
+impl Session {
+    /// Creates a session that shows the given environment
+    pub(crate) fn new(environment: Environment) -> Self {
+        Session {
+            initial: environment.clone(),
+            environment,
+            synced: false,
+            viewport: Viewport::new(-10, 10, 20, 20),
+            generation: 0,
+            last_simulation_time: Duration::from_secs(0),
+            screensaver: None,
+            snapshot: None,
+            rate: RateMeter::new(Instant::now()),
+        }
+    }
+
+    /// Creates a new session with a copy of the environment, so it can continue independently
+    pub(crate) fn duplicate(&self) -> Self {
+        Session {
+            environment: self.environment.clone(),
+            synced: false,
+            initial: self.initial.clone(),
+            viewport: Viewport::new(self.viewport.x(), self.viewport.y(), self.viewport.width(), self.viewport.height()),
+            generation: self.generation,
+            last_simulation_time: Duration::from_secs(0),
+            screensaver: None,
+            snapshot: self.snapshot.clone(),
+            rate: RateMeter::new(Instant::now()),
+        }
+    }
+
+    pub(crate) fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// Returns the environment to change it, which has to be sent to the worker thread again
+    pub(crate) fn environment_mut(&mut self) -> &mut Environment {
+        self.synced = false;
+        &mut self.environment
+    }
+
+    /// Makes the environment be sent to the worker thread with the next job, as its tab changed
+    pub(crate) fn unsync(&mut self) {
+        self.synced = false;
+    }
+
+    /// Replaces the environment, restarting the generation count
+    pub(crate) fn set_environment(&mut self, environment: Environment) {
+        self.initial = environment.clone();
+        *self.environment_mut() = environment;
+        self.generation = 0;
+    }
+
+    /// Replaces the environment with a snapshot, keeping the environment restored when resetting
+    pub(crate) fn restore(&mut self, snapshot: &SavedSession) {
+        *self.environment_mut() = snapshot.environment.clone();
+        self.generation = snapshot.generation.unwrap_or_default();
+    }
+
+    /// Restores the environment as it was loaded and restarts the generation count. The current
+    /// rule is kept, so the same pattern can be tried under several rules.
+    pub(crate) fn reset(&mut self) {
+        let rule = self.environment.rule();
+        *self.environment_mut() = self.initial.clone();
+        self.environment.set_rule(rule);
+        self.generation = 0;
+    }
+
+    /// Replaces the environment with random soups that are reseeded whenever they stagnate
+    pub(crate) fn start_screensaver(&mut self, seed: u64) {
+        let mut screensaver = Screensaver::new(self.viewport.width(), self.viewport.height(), Some(seed));
+        screensaver.seed(self.environment_mut());
+        self.generation = 0;
+        self.screensaver = Some(screensaver);
+    }
+
+    /// Takes the state needed to simulate the session. The screensaver goes along with it until
+    /// the simulation is finished.
+    fn simulation(&mut self) -> Simulation {
+        Simulation {
+            generation: self.generation,
+            screensaver: self.screensaver.take(),
+            size: (self.viewport.width(), self.viewport.height()),
+            elapsed: Duration::from_secs(0),
+            simulated: 0,
+            reseeded: false,
+        }
+    }
+
+    /// Creates the job that simulates the session on the worker thread. The environment is only
+    /// sent along if the worker doesn't have it yet.
+    pub(crate) fn job(&mut self, tab: usize, generations: usize, limits: Limits) -> Job {
+        let environment = (!self.synced).then(|| self.environment.clone());
+        self.synced = true;
+
+        Job { tab, environment, simulation: self.simulation(), generations, limits }
+    }
+
+    /// Stores the result of a simulation
+    pub(crate) fn finish(&mut self, simulation: Simulation) {
+        self.generation = simulation.generation;
+        self.screensaver = simulation.screensaver;
+        self.last_simulation_time = simulation.elapsed;
+        self.rate.record(Instant::now(), simulation.simulated);
+    }
+
+    /// Stores the result of a simulation on the worker thread
+    pub(crate) fn finish_job(&mut self, simulation: Simulation, update: Update) {
+        match update {
+            Update::Changes(changes, background) => self.environment.apply(&changes, background),
+            Update::Environment(environment) => self.environment = environment,
+        }
+        self.finish(simulation);
+    }
+
+    /// Simulates the given amount of generations on the current thread. Stops early if the
+    /// environment exceeds the limits, returning the exceeded limit.
+    pub(crate) fn simulate(&mut self, generations: usize, limits: &Limits) -> Option<String> {
+        let simulation = self.simulation();
+        self.simulate_with(simulation, generations, limits)
+    }
+
+    /// Simulates on the current thread with the state taken for a job
+    pub(crate) fn simulate_with(&mut self, mut simulation: Simulation, generations: usize, limits: &Limits) -> Option<String> {
+        let exceeded = simulation.run(self.environment_mut(), generations, limits, &mut ());
+        self.finish(simulation);
+
+        exceeded
+    }
+
+    /// Draws the environment in the viewport
+    pub(crate) fn fill_viewport(&mut self) {
+        self.environment.fill_viewport(&mut self.viewport);
+    }
+}
@@ -0,0 +1,148 @@
+//! Widgets used to draw the user interface.
+
+use std::time::Duration;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Widget};
+
+use crate::{Changes, Rule, SimCell, Viewport};
+use crate::config::Theme;
+
+/// Separator drawn between the fields of the status bar
+const FIELD_SEPARATOR: &str = " │ ";
+
+/// Drawn where a cell died since the snapshot being compared with
+const REMOVED_CELL: char = 'o';
+
+/// Draws the cells of a viewport within a bordered block
+pub(crate) struct EnvironmentView<'a> {
+    viewport: &'a Viewport,
+    theme: &'a Theme,
+    changes: Option<&'a Changes>,
+}
+
+impl<'a> EnvironmentView<'a> {
+    pub(crate) fn new(viewport: &'a Viewport, theme: &'a Theme) -> Self {
+        EnvironmentView { viewport, theme, changes: None }
+    }
+
+    /// Highlights the cells that changed since a snapshot
+    pub(crate) fn changes(mut self, changes: Option<&'a Changes>) -> Self {
+        self.changes = changes;
+        self
+    }
+}
+
+impl Widget for EnvironmentView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title("Conway's Game of Life")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.border.0));
+        let inner = block.inner(area);
+
+        Paragraph::new(self.viewport.to_string())
+            .style(Style::default().fg(self.theme.living.0).bg(self.theme.background.0))
+            .block(block)
+            .render(area, buf);
+
+        let Some(changes) = self.changes else {
+            return;
+        };
+
+        // Position of a cell within the buffer, if it is visible
+        let position = |cell: &SimCell| {
+            let point = self.viewport.world_to_screen(*cell)?;
+            let (column, row) = (u16::try_from(point.x).ok()?, u16::try_from(point.y).ok()?);
+            (column < inner.width && row < inner.height).then(|| (inner.x + column, inner.y + row))
+        };
+
+        for (x, y) in changes.added.iter().filter_map(position) {
+            buf.get_mut(x, y).set_fg(self.theme.added.0);
+        }
+        for (x, y) in changes.removed.iter().filter_map(position) {
+            buf.get_mut(x, y).set_char(REMOVED_CELL).set_fg(self.theme.removed.0);
+        }
+    }
+}
+
+/// Single line with the state of the simulation. Every field has a fixed width, so they don't
+/// move while the values change.
+pub(crate) struct StatusBar {
+    pub(crate) generation: usize,
+    pub(crate) rule: Rule,
+    pub(crate) speed: String,
+    pub(crate) paused: bool,
+    /// Population and time of the last simulation, hidden when `None`
+    pub(crate) stats: Option<(usize, Duration)>,
+    /// Generations simulated per second, hidden when `None`
+    pub(crate) rate: Option<f64>,
+    /// Position and size of the viewport, hidden when `None`
+    pub(crate) coordinates: Option<(i32, i32, usize, usize)>,
+    /// Tab being shown and the amount of tabs, hidden when `None`
+    pub(crate) tab: Option<(usize, usize)>,
+    /// Amount of cells that differ from the snapshot, hidden when `None`
+    pub(crate) diff: Option<usize>,
+    pub(crate) style: Style,
+}
+
+impl StatusBar {
+    /// Returns the text of each field, along with its style
+    fn fields(&self) -> Vec<(String, Style)> {
+        let mut fields = Vec::new();
+        if let Some((tab, count)) = self.tab {
+            fields.push((format!("TAB {}/{}", tab, count), self.style.add_modifier(Modifier::BOLD)));
+        }
+
+        fields.extend([
+            (format!("GEN {:<8}", self.generation), self.style),
+            (format!("{:<12}", self.rule.to_string()), self.style),
+            (format!("SPEED {:<8}", self.speed), self.style),
+        ]);
+
+        if self.paused {
+            fields.push((String::from("PAUSED "), self.style.add_modifier(Modifier::REVERSED)));
+        } else {
+            fields.push((String::from("RUNNING"), self.style));
+        }
+
+        if let Some((population, time)) = self.stats {
+            fields.push((format!("POP {:<8}", population), self.style));
+            fields.push((format!("TIME {:>7}µs", time.as_micros()), self.style));
+        }
+
+        if let Some(rate) = self.rate {
+            fields.push((format!("{:>6.0} GEN/S", rate), self.style));
+        }
+
+        if let Some(diff) = self.diff {
+            fields.push((format!("DIFF {:<8}", diff), self.style));
+        }
+
+        if let Some((x, y, width, height)) = self.coordinates {
+            fields.push((format!("X={} Y={} W={} H={}", x, y, width, height), self.style));
+        }
+
+        fields
+    }
+}
+
+impl Widget for StatusBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut spans = Vec::new();
+        for (i, (text, style)) in self.fields().into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled(FIELD_SEPARATOR, self.style));
+            }
+            spans.push(Span::styled(text, style));
+        }
+
+        Paragraph::new(Line::from(spans))
+            .style(self.style)
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,117 @@
+//! Simulates the tabs on a thread of their own, so slow generations don't freeze the input
+//! handling or the drawing of the user interface.
+//!
+//! The thread keeps its own copy of the environment of every tab. Environments are only sent to
+//! it when they were changed by something else than the simulation, and each job sends back the
+//! cells that changed instead of the whole environment.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::mpsc::{self, SendError, Sender};
+use std::thread;
+
+use crate::{Changes, Environment, Observer, SimCell};
+use crate::config::Limits;
+
+use super::AppEvent;
+use super::session::Simulation;
+
+/// Generations of a tab to simulate
+pub(crate) struct Job {
+    /// Index of the tab
+    pub(crate) tab: usize,
+    /// Environment of the tab, if the copy of the worker is out of date
+    pub(crate) environment: Option<Environment>,
+    pub(crate) simulation: Simulation,
+    pub(crate) generations: usize,
+    pub(crate) limits: Limits,
+}
+
+/// How the environment of a tab changed during a job
+#[derive(Debug)]
+pub(crate) enum Update {
+    /// Cells that changed, along with the new background
+    Changes(Changes, bool),
+    /// The whole environment, when it was replaced instead of simulated
+    Environment(Environment),
+}
+
+/// Result of a job, sent back to the application
+pub(crate) struct Simulated {
+    pub(crate) tab: usize,
+    pub(crate) simulation: Simulation,
+    pub(crate) update: Update,
+    /// Limit exceeded by the simulation, which stopped early
+    pub(crate) exceeded: Option<String>,
+}
+
+/// Thread that simulates the jobs in order. It stops once dropped, after the current job.
+pub(crate) struct Worker {
+    jobs: Sender<Job>,
+}
+
+impl Worker {
+    /// Starts the thread, which sends each result to the application as an event
+    pub(crate) fn spawn(results: Sender<AppEvent>) -> Self {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        thread::spawn(move || {
+            let mut environments: HashMap<usize, Environment> = HashMap::new();
+            for mut job in receiver {
+                if let Some(environment) = job.environment.take() {
+                    environments.insert(job.tab, environment);
+                }
+                let environment = environments.entry(job.tab).or_default();
+
+                let mut toggled = Toggled::default();
+                let exceeded = job.simulation.run(environment, job.generations, &job.limits, &mut toggled);
+                let update = if job.simulation.reseeded {
+                    Update::Environment(environment.clone())
+                } else {
+                    toggled.changes(environment)
+                };
+
+                let simulated = Simulated { tab: job.tab, simulation: job.simulation, update, exceeded };
+                if results.send(AppEvent::Simulated(Box::new(simulated))).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Worker { jobs }
+    }
+
+    /// Queues a job. Returns it back if the thread stopped.
+    pub(crate) fn send(&self, job: Job) -> Result<(), Job> {
+        self.jobs.send(job).map_err(|SendError(job)| job)
+    }
+}
+
+/// Stored cells that changed over several generations. Cells that change twice are back to
+/// where they started, so they are dropped.
+#[derive(Default)]
+struct Toggled(BTreeSet<SimCell>);
+
+impl Toggled {
+    fn toggle(&mut self, cell: SimCell) {
+        if !self.0.remove(&cell) {
+            self.0.insert(cell);
+        }
+    }
+
+    /// Returns the changes that turn the environment before the generations into the given one
+    fn changes(self, environment: &Environment) -> Update {
+        let background = environment.background();
+        let (added, removed) = self.0.into_iter().partition(|cell| environment.get_cell(cell) != background);
+
+        Update::Changes(Changes { added, removed }, background)
+    }
+}
+
+impl Observer for Toggled {
+    fn on_birth(&mut self, cell: SimCell) {
+        self.toggle(cell);
+    }
+
+    fn on_death(&mut self, cell: SimCell) {
+        self.toggle(cell);
+    }
+}
@@ -0,0 +1,196 @@
+//! Catalog of named patterns, so they can be placed without knowing the path of their files.
+//!
+//! A few well known patterns are built in. More can be added from a directory of `.rle` and
+//! `.cells` files, using the `#N` / `!Name:` lines as their name and the `#C` / `!` comments
+//! as their description.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::Environment;
+use crate::pattern::{self, PatternError, PatternFormat};
+
+/// Built in patterns, as name, aliases, description and RLE data
+const BUILTIN_PATTERNS: &[(&str, &[&str], &str, &str)] = &[
+    ("Block", &[], "The most common still life", "2o$2o!"),
+    ("Beehive", &[], "The second most common still life", "b2o$o2bo$b2o!"),
+    ("Loaf", &[], "A common still life", "b2o$o2bo$bobo$2bo!"),
+    ("Boat", &[], "The only 5 cell still life", "2o$obo$bo!"),
+    ("Blinker", &[], "The smallest and most common oscillator", "3o!"),
+    ("Toad", &[], "A period 2 oscillator", "b3o$3o!"),
+    ("Beacon", &[], "A period 2 oscillator made of two blocks", "2o$2o$2b2o$2b2o!"),
+    ("Pulsar", &[], "A period 3 oscillator",
+     "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!"),
+    ("Pentadecathlon", &[], "A period 15 oscillator", "2bo4bo$2ob4ob2o$2bo4bo!"),
+    ("Glider", &[], "The smallest spaceship, moving diagonally", "bo$2bo$3o!"),
+    ("Lightweight spaceship", &["LWSS"], "The smallest orthogonal spaceship", "bo2bo$o$o3bo$4o!"),
+    ("R-pentomino", &["F-pentomino"], "A methuselah that stabilizes after 1103 generations", "b2o$2o$bo!"),
+    ("Acorn", &[], "A methuselah that stabilizes after 5206 generations", "bo$3bo$2o2b3o!"),
+    ("Diehard", &[], "A methuselah that vanishes after 130 generations", "6bo$2o$bo3b3o!"),
+    ("Gosper glider gun", &["Glider gun", "Gosper gun"], "The first known gun, emitting a glider every 30 generations",
+     "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!"),
+];
+
+/// A named pattern of the catalog
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub description: String,
+    format: PatternFormat,
+    data: String,
+}
+
+impl CatalogEntry {
+    /// Parses the pattern of the entry
+    pub fn environment(&self) -> Result<Environment, PatternError> {
+        pattern::parse(&self.data, self.format)
+    }
+
+    /// Returns how far the query is from the name or aliases of the entry, or None if it
+    /// doesn't match. Exact matches have a distance of 0, names that contain the query a
+    /// distance of 1, and small typos the edit distance plus one.
+    fn distance(&self, query: &str) -> Option<usize> {
+        std::iter::once(&self.name)
+            .chain(self.aliases.iter())
+            .filter_map(|name| {
+                let name = normalize(name);
+                if name == query {
+                    Some(0)
+                } else if name.contains(query) {
+                    Some(1)
+                } else {
+                    let distance = edit_distance(&name, query);
+                    (distance <= (query.len() / 4).max(1)).then_some(distance + 1)
+                }
+            })
+            .min()
+    }
+}
+
+/// A collection of named patterns
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    entries: Vec<CatalogEntry>,
+}
+
+impl Default for Catalog {
+    /// Creates a catalog with the built in patterns
+    fn default() -> Self {
+        let entries = BUILTIN_PATTERNS.iter()
+            .map(|(name, aliases, description, data)| CatalogEntry {
+                name: name.to_string(),
+                aliases: aliases.iter().map(|a| a.to_string()).collect(),
+                description: description.to_string(),
+                format: PatternFormat::Rle,
+                data: data.to_string(),
+            })
+            .collect();
+
+        Catalog { entries }
+    }
+}
+
+impl Catalog {
+    /// Returns every pattern of the catalog
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// Adds the `.rle` and `.cells` files within a directory to the catalog. Returns the amount
+    /// of added patterns.
+    pub fn load_directory(&mut self, path: &Path) -> io::Result<usize> {
+        let mut added = 0;
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            let format = match PatternFormat::from_path(&path) {
+                Some(format @ (PatternFormat::Rle | PatternFormat::Cells)) => format,
+                _ => continue,
+            };
+
+            let data = fs::read_to_string(&path)?;
+            let (name, description) = read_metadata(&data, format);
+            let name = name.unwrap_or_else(|| {
+                path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+            });
+
+            self.entries.push(CatalogEntry { name, aliases: Vec::new(), description, format, data });
+            added += 1;
+        }
+
+        Ok(added)
+    }
+
+    /// Returns the entries matching the query, best match first. Names are compared ignoring
+    /// case, spaces and punctuation, and tolerating small typos.
+    pub fn search(&self, query: &str) -> Vec<&CatalogEntry> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(usize, &CatalogEntry)> = self.entries.iter()
+            .filter_map(|entry| Some((entry.distance(&query)?, entry)))
+            .collect();
+        matches.sort_by_key(|(distance, entry)| (*distance, entry.name.len()));
+
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Returns the entry that best matches the query
+    pub fn find(&self, query: &str) -> Option<&CatalogEntry> {
+        self.search(query).into_iter().next()
+    }
+}
+
+/// Reads the name and description of a pattern file
+fn read_metadata(data: &str, format: PatternFormat) -> (Option<String>, String) {
+    let mut name = None;
+    let mut description = Vec::new();
+    for line in data.lines().map(str::trim) {
+        let comment = match format {
+            PatternFormat::Rle => line.strip_prefix('#'),
+            _ => line.strip_prefix('!'),
+        };
+        let Some(comment) = comment else {
+            continue;
+        };
+
+        let (name_value, description_value) = match format {
+            PatternFormat::Rle if comment.starts_with("CXRLE") => continue,
+            PatternFormat::Rle => (comment.strip_prefix('N'), comment.strip_prefix('C').or_else(|| comment.strip_prefix('c'))),
+            _ => (comment.strip_prefix("Name:"), Some(comment)),
+        };
+
+        if let Some(value) = name_value {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = description_value {
+            description.push(value.trim());
+        }
+    }
+
+    let description = description.into_iter().filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+    (name.filter(|n| !n.is_empty()), description)
+}
+
+/// Lowercases the text and removes every character that is not alphanumeric
+fn normalize(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,225 @@
+//! Classification of the objects left once a simulation stabilizes, known as the ash.
+//!
+//! The ash is split into objects, grouping the cells that are at most two cells apart (so they
+//! can affect each other in the next generation). Each object is simulated on its own to find
+//! whether it is a still life, an oscillator or a spaceship, and identified by a canonical form
+//! that doesn't depend on its position, orientation or phase, so common objects can be named.
+//! Objects without a name get a code with their kind and size: `xs<population>` for still
+//! lifes, `xp<period>` for oscillators and `xq<period>` for spaceships.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use grid2d::SparseGrid;
+
+use crate::{Environment, Rule, SimCell};
+use crate::pattern;
+
+/// Longest period of the oscillators and spaceships that are detected
+pub const MAX_PERIOD: usize = 30;
+
+/// Common objects of Conway's Game of Life, as ASCII patterns
+const KNOWN_OBJECTS: &[(&str, &str)] = &[
+    ("block", "xx\nxx"),
+    ("beehive", " xx\nx  x\n xx"),
+    ("loaf", " xx\nx  x\n x x\n  x"),
+    ("boat", "xx\nx x\n x"),
+    ("ship", "xx\nx x\n xx"),
+    ("tub", " x\nx x\n x"),
+    ("pond", " xx\nx  x\nx  x\n xx"),
+    ("long boat", "xx\nx x\n x x\n  x"),
+    ("barge", " x\nx x\n x x\n  x"),
+    ("mango", " xx\nx  x\n x  x\n  xx"),
+    ("eater", "xx\nx x\n  x\n  xx"),
+    ("aircraft carrier", "xx\nx  x\n  xx"),
+    ("snake", "xx x\nx xx"),
+    ("blinker", "xxx"),
+    ("toad", " xxx\nxxx"),
+    ("beacon", "xx\nxx\n  xx\n  xx"),
+    ("glider", " x\n  x\nxxx"),
+    ("lightweight spaceship", " x  x\nx\nx   x\nxxxx"),
+];
+
+/// Behaviour of an object when simulated on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator { period: usize },
+    Spaceship { period: usize },
+    /// Doesn't repeat within [MAX_PERIOD] generations
+    Unstable,
+}
+
+/// An object of the ash
+#[derive(Debug, Clone)]
+pub struct Object {
+    /// Name of a known object, or a code with its kind and size
+    pub name: String,
+    pub kind: ObjectKind,
+    pub known: bool,
+    /// Living cells of the object, in its canonical orientation and phase
+    pub pattern: Environment,
+}
+
+impl Object {
+    /// Returns the amount of living cells of the object
+    pub fn population(&self) -> usize {
+        self.pattern.get_living_count()
+    }
+}
+
+impl Display for Object {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Cells of an object moved so its bounding box starts at (0, 0), sorted
+type Shape = Vec<(i32, i32)>;
+
+/// Classifies objects, naming the known ones
+#[derive(Debug)]
+pub struct Census {
+    rule: Rule,
+    known: HashMap<Shape, &'static str>,
+}
+
+impl Census {
+    /// Creates a census for the given rule. Objects are only named with Conway's rule.
+    pub fn new(rule: Rule) -> Self {
+        let mut census = Census { rule, known: HashMap::new() };
+        if rule.is_conway() {
+            for (name, data) in KNOWN_OBJECTS {
+                let object = pattern::parse_ascii(data).expect("known objects are valid patterns");
+                let (shape, _) = census.canonical(&object);
+                census.known.insert(shape, name);
+            }
+        }
+
+        census
+    }
+
+    /// Splits the ash into objects and classifies each of them
+    pub fn take(&self, ash: &Environment) -> Vec<Object> {
+        components(ash).iter().map(|object| self.classify(object)).collect()
+    }
+
+    /// Classifies a single object
+    pub fn classify(&self, object: &Environment) -> Object {
+        let (shape, kind) = self.canonical(object);
+        let population = shape.len();
+
+        let mut pattern = Environment::with_rule(self.rule);
+        pattern.set_living(&shape.iter().map(|&(x, y)| SimCell::new(x, -y)).collect::<Vec<_>>());
+
+        match self.known.get(&shape) {
+            Some(name) => Object { name: name.to_string(), kind, known: true, pattern },
+            None => {
+                let name = match kind {
+                    ObjectKind::StillLife => format!("xs{population}"),
+                    ObjectKind::Oscillator { period } => format!("xp{period}"),
+                    ObjectKind::Spaceship { period } => format!("xq{period}"),
+                    ObjectKind::Unstable => format!("unstable{population}"),
+                };
+                Object { name, kind, known: false, pattern }
+            }
+        }
+    }
+
+    /// Simulates an object to find its kind and the smallest of the shapes of its phases in
+    /// every orientation
+    fn canonical(&self, object: &Environment) -> (Shape, ObjectKind) {
+        let mut current = Environment::with_rule(self.rule);
+        current.set_living(&object.living_cells().copied().collect::<Vec<_>>());
+
+        let (first_shape, first_corner) = shape(&current);
+        let mut best = smallest_orientation(&first_shape);
+        let mut kind = ObjectKind::Unstable;
+        for generation in 1..=MAX_PERIOD {
+            current.simulate();
+            let (shape, corner) = shape(&current);
+            if shape == first_shape {
+                kind = match generation {
+                    1 => ObjectKind::StillLife,
+                    period if corner == first_corner => ObjectKind::Oscillator { period },
+                    period => ObjectKind::Spaceship { period },
+                };
+                break;
+            }
+
+            best = best.min(smallest_orientation(&shape));
+        }
+
+        // Unstable objects are kept in the phase they were found
+        if kind == ObjectKind::Unstable {
+            best = smallest_orientation(&first_shape);
+        }
+
+        (best, kind)
+    }
+}
+
+/// Returns the shape of an environment along with the corner it was moved from
+fn shape(environment: &Environment) -> (Shape, (i32, i32)) {
+    let Some((min, max)) = environment.bounding_box() else {
+        return (Vec::new(), (0, 0));
+    };
+
+    // Rows are counted downwards, like in pattern files
+    let mut shape: Shape = environment.living_cells().map(|c| (c.x - min.x, max.y - c.y)).collect();
+    shape.sort_unstable();
+    (shape, (min.x, max.y))
+}
+
+/// Returns the smallest of the eight rotations and reflections of a shape
+fn smallest_orientation(shape: &Shape) -> Shape {
+    let transforms: [fn(i32, i32) -> (i32, i32); 8] = [
+        |x, y| (x, y), |x, y| (-x, y), |x, y| (x, -y), |x, y| (-x, -y),
+        |x, y| (y, x), |x, y| (-y, x), |x, y| (y, -x), |x, y| (-y, -x),
+    ];
+
+    transforms.iter()
+        .map(|transform| {
+            let moved: Vec<(i32, i32)> = shape.iter().map(|&(x, y)| transform(x, y)).collect();
+            let min_x = moved.iter().map(|c| c.0).min().unwrap_or_default();
+            let min_y = moved.iter().map(|c| c.1).min().unwrap_or_default();
+            let mut moved: Shape = moved.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+            moved.sort_unstable();
+            moved
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Splits an environment into groups of cells that are at most two cells apart
+pub fn components(environment: &Environment) -> Vec<Environment> {
+    let cells: SparseGrid<()> = environment.living_cells().map(|cell| ((*cell).into(), ())).collect();
+    let mut visited = HashSet::with_capacity(cells.len());
+    let mut objects = Vec::new();
+
+    for start in environment.living_cells() {
+        if !visited.insert(*start) {
+            continue;
+        }
+
+        let mut object = BTreeSet::new();
+        let mut pending = vec![*start];
+        while let Some(cell) = pending.pop() {
+            object.insert(cell);
+            for dx in -2..=2 {
+                for dy in -2..=2 {
+                    let neighbour = SimCell::new(cell.x + dx, cell.y + dy);
+                    if cells.get(neighbour.into()).is_some() && visited.insert(neighbour) {
+                        pending.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        let mut component = Environment::with_rule(environment.rule());
+        component.set_living(&object.into_iter().collect::<Vec<_>>());
+        objects.push(component);
+    }
+
+    objects
+}
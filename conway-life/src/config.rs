@@ -0,0 +1,249 @@
+//! Configuration of the text based user interface, read from a TOML file.
+//!
+//! ```toml
+//! tick_rate = 50
+//! frame_rate = 30
+//! rule = "B3/S23"
+//! startup_pattern = "patterns/gosper-gun.rle"
+//! catalog = "patterns"
+//! snapshots = "snapshots"
+//!
+//! [keybindings]
+//! "ctrl-p" = "pause"
+//! "f2" = "stats"
+//!
+//! [theme]
+//! living = "yellow"
+//! background = "black"
+//! border = "#808080"
+//!
+//! [limits]
+//! max_population = 500000
+//! max_size = 0
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{Environment, Rule};
+use crate::headless::HeadlessError;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Unable to read the configuration file")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid configuration file: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Invalid key binding: {0}")]
+    InvalidKey(String),
+
+    #[error("Unable to load the startup pattern")]
+    StartupPattern(#[from] HeadlessError),
+
+    #[error("Unable to read the pattern catalog {}", .0.display())]
+    Catalog(PathBuf, #[source] io::Error),
+}
+
+impl From<ConfigError> for errors::Error {
+    fn from(error: ConfigError) -> Self {
+        let category = match &error {
+            ConfigError::Io(_) | ConfigError::Catalog(..) => errors::Error::io("Unable to read the configuration"),
+            _ => errors::Error::config("Invalid configuration"),
+        };
+        category.with_source(error)
+    }
+}
+
+/// Settings of the user interface. Every value is optional in the file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Time between generations, in milliseconds
+    pub tick_rate: u64,
+    /// Times the screen is drawn per second, independent of the time between generations
+    pub frame_rate: u64,
+    /// Rule used by the simulation
+    pub rule: Rule,
+    /// Pattern loaded at startup, instead of the F-Pentomino
+    pub startup_pattern: Option<PathBuf>,
+    /// Directory of `.rle` and `.cells` files added to the catalog of named patterns
+    pub catalog: Option<PathBuf>,
+    /// Directory where the snapshots of `snap` are also written as `<slot>.con` files, so
+    /// `restore` can read them in later runs
+    pub snapshots: Option<PathBuf>,
+    /// Commands executed when pressing a key, e.g. `"ctrl-p" = "pause"`. Character keys
+    /// without modifiers only trigger their command while the input box is empty.
+    pub keybindings: HashMap<String, String>,
+    /// Colors of the user interface
+    pub theme: Theme,
+    /// Limits that pause the simulation when exceeded
+    pub limits: Limits,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tick_rate: 50,
+            frame_rate: 30,
+            rule: Rule::default(),
+            startup_pattern: None,
+            catalog: None,
+            snapshots: None,
+            keybindings: HashMap::new(),
+            theme: Theme::default(),
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// Colors of the user interface. Colors can be given by name (e.g. `"light-green"`) or in
+/// `"#rrggbb"` format.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub living: ThemeColor,
+    pub background: ThemeColor,
+    pub border: ThemeColor,
+    /// Cells that are alive but weren't in the snapshot being compared with
+    pub added: ThemeColor,
+    /// Cells that were alive in the snapshot being compared with
+    pub removed: ThemeColor,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            living: ThemeColor(Color::Reset),
+            background: ThemeColor(Color::Reset),
+            border: ThemeColor(Color::Reset),
+            added: ThemeColor(Color::Green),
+            removed: ThemeColor(Color::Red),
+        }
+    }
+}
+
+/// Limits that pause the simulation before a growing pattern uses up all the memory. A value of
+/// 0 disables the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Maximum amount of living cells
+    pub max_population: usize,
+    /// Maximum width or height of the bounding box of the living cells
+    pub max_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_population: 1_000_000,
+            max_size: 100_000,
+        }
+    }
+}
+
+impl Limits {
+    /// Returns a description of the first limit exceeded by the environment, if any
+    pub fn exceeded(&self, environment: &Environment) -> Option<String> {
+        let population = environment.get_living_count();
+        if self.max_population > 0 && population > self.max_population {
+            return Some(format!("{} living cells, over the limit of {}", population, self.max_population));
+        }
+
+        if self.max_size > 0 {
+            let (min, max) = environment.bounding_box()?;
+            let size = (max.x.abs_diff(min.x) as usize + 1).max(max.y.abs_diff(min.y) as usize + 1);
+            if size > self.max_size {
+                return Some(format!("{} cells wide, over the limit of {}", size, self.max_size));
+            }
+        }
+
+        None
+    }
+}
+
+/// A color of the theme
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ThemeColor(pub Color);
+
+impl FromStr for ThemeColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase().replace(['-', '_', ' '], "");
+        let color = match name.as_str() {
+            "reset" | "default" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "darkgray" | "darkgrey" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            hex if hex.starts_with('#') && hex.len() == 7 && hex.is_ascii() => {
+                let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16)
+                    .map_err(|_| format!("Invalid color: {}", s));
+                Color::Rgb(channel(1)?, channel(3)?, channel(5)?)
+            }
+            _ => return Err(format!("Invalid color: {}", s)),
+        };
+
+        Ok(ThemeColor(color))
+    }
+}
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the given file
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let data = fs::read_to_string(path)?;
+        Ok(toml::from_str(&data)?)
+    }
+
+    /// Reads the configuration from the default location, if it exists. Otherwise the default
+    /// configuration is returned.
+    pub fn load_default() -> Result<Self, ConfigError> {
+        match Config::default_path() {
+            Some(path) if path.is_file() => Config::load(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Returns the default location of the configuration file:
+    /// `$XDG_CONFIG_HOME/conway-life/config.toml`, or `~/.config/conway-life/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_home.join("conway-life").join("config.toml"))
+    }
+}
@@ -0,0 +1,183 @@
+//! Compares how the same starting pattern evolves under different rules, to find interesting
+//! Life-like rules.
+//!
+//! Each rule simulates its own copy of the pattern until the population settles into a cycle,
+//! explodes past a limit, or runs out of generations.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::{Environment, Rule};
+use crate::rule::RuleError;
+use crate::search::PopulationCycle;
+
+/// Prefix of a rule set made of the neighbours of a rule
+const AROUND_PREFIX: &str = "around:";
+
+/// Settings of a rule exploration
+#[derive(Debug, Clone)]
+pub struct ExploreOptions {
+    pub rules: Vec<Rule>,
+    /// Generations simulated at most for each rule
+    pub generations: usize,
+    /// Simulations stop once their population goes over this amount
+    pub max_population: usize,
+}
+
+impl Default for ExploreOptions {
+    fn default() -> Self {
+        ExploreOptions {
+            rules: Vec::new(),
+            generations: 1000,
+            max_population: 100_000,
+        }
+    }
+}
+
+/// How a simulation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The population settled into a cycle after the given amount of generations
+    Stabilized(usize),
+    /// The population went over the limit after the given amount of generations
+    Exploded(usize),
+    /// Every generation was simulated without stabilizing
+    Running,
+}
+
+/// Measures of the evolution of a pattern under a rule
+#[derive(Debug, Clone)]
+pub struct RuleMetrics {
+    pub rule: Rule,
+    pub outcome: Outcome,
+    pub generations: usize,
+    pub initial_population: usize,
+    pub final_population: usize,
+    pub peak_population: usize,
+}
+
+impl RuleMetrics {
+    /// Average change of the population per generation
+    pub fn growth_rate(&self) -> f64 {
+        (self.final_population as f64 - self.initial_population as f64) / self.generations.max(1) as f64
+    }
+
+    /// Generations until the population stabilized
+    pub fn stabilization(&self) -> Option<usize> {
+        match self.outcome {
+            Outcome::Stabilized(generations) => Some(generations),
+            _ => None,
+        }
+    }
+}
+
+/// Results of an exploration, with one entry per rule
+#[derive(Debug)]
+pub struct Exploration {
+    pub metrics: Vec<RuleMetrics>,
+}
+
+impl Exploration {
+    /// Writes the results as comma separated values, with a header line
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("rule,outcome,generations,stabilization,initial,final,peak,growth\n");
+        for metrics in self.metrics.iter() {
+            let stabilization = metrics.stabilization().map(|g| g.to_string()).unwrap_or_default();
+            output.push_str(&format!("{},{},{},{},{},{},{},{:.3}\n", metrics.rule, outcome_name(metrics.outcome),
+                                     metrics.generations, stabilization, metrics.initial_population,
+                                     metrics.final_population, metrics.peak_population, metrics.growth_rate()));
+        }
+
+        output
+    }
+}
+
+impl Display for Exploration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<16}{:<12}{:>8}{:>10}{:>10}{:>10}", "RULE", "OUTCOME", "GENS", "FINAL", "PEAK", "GROWTH")?;
+        for metrics in self.metrics.iter() {
+            writeln!(f, "{:<16}{:<12}{:>8}{:>10}{:>10}{:>10.3}", metrics.rule.to_string(),
+                     outcome_name(metrics.outcome), metrics.generations, metrics.final_population,
+                     metrics.peak_population, metrics.growth_rate())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the name of an outcome shown in the results
+fn outcome_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Stabilized(_) => "stable",
+        Outcome::Exploded(_) => "exploded",
+        Outcome::Running => "running",
+    }
+}
+
+/// A set of rules to explore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet(pub Vec<Rule>);
+
+impl FromStr for RuleSet {
+    type Err = RuleError;
+
+    /// Parses a list of rules separated by commas (`B3/S23,B36/S23`), or the rules around
+    /// another one (`around:B3/S23`), which includes the rule itself
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().strip_prefix(AROUND_PREFIX) {
+            Some(rule) => {
+                let rule: Rule = rule.parse()?;
+                let mut rules = vec![rule];
+                rules.extend(rule.neighbours());
+                Ok(RuleSet(rules))
+            }
+            None => s.split(',').map(str::parse).collect::<Result<_, _>>().map(RuleSet),
+        }
+    }
+}
+
+/// Simulates the pattern under a single rule
+pub fn measure(pattern: &Environment, rule: Rule, options: &ExploreOptions) -> RuleMetrics {
+    let mut environment = pattern.clone();
+    environment.set_rule(rule);
+
+    let initial_population = environment.get_living_count();
+    let mut peak_population = initial_population;
+    let mut cycle = PopulationCycle::default();
+    let mut outcome = Outcome::Running;
+    let mut generation = 0;
+    loop {
+        let population = environment.get_living_count();
+        peak_population = peak_population.max(population);
+        if population > options.max_population {
+            outcome = Outcome::Exploded(generation);
+            break;
+        }
+        if cycle.observe(population) {
+            outcome = Outcome::Stabilized(generation);
+            break;
+        }
+        if generation == options.generations {
+            break;
+        }
+
+        environment.simulate();
+        generation += 1;
+    }
+
+    RuleMetrics {
+        rule,
+        outcome,
+        generations: generation,
+        initial_population,
+        final_population: environment.get_living_count(),
+        peak_population,
+    }
+}
+
+/// Simulates the pattern under every rule of the options
+pub fn run(pattern: &Environment, options: &ExploreOptions) -> Exploration {
+    Exploration {
+        metrics: options.rules.iter().map(|rule| measure(pattern, *rule, options)).collect(),
+    }
+}
@@ -0,0 +1,139 @@
+//! Exports the simulation as images and animations, so it can be shared without recording the
+//! screen.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use render::raster::{self, Raster};
+use render::{Color, RenderError};
+use thiserror::Error;
+
+use crate::{Environment, Viewport};
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Unable to write the export")]
+    Io(#[from] io::Error),
+
+    #[error("Unable to encode the GIF")]
+    Gif(#[from] gif::EncodingError),
+
+    #[error("Unable to encode the PNG")]
+    Png(#[from] png::EncodingError),
+
+    #[error("Unable to render the viewport")]
+    Render(#[from] RenderError),
+
+    #[error("The scaled viewport is too large for the image format")]
+    TooLarge,
+}
+
+/// Options used when exporting animations
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    /// Size in pixels of each cell
+    pub scale: usize,
+    /// Time between frames, in hundredths of a second
+    pub frame_delay: u16,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions { scale: 4, frame_delay: 5 }
+    }
+}
+
+/// Colors used for dead and living cells
+const DEAD: Color = Color::BLACK;
+const LIVING: Color = Color::WHITE;
+
+/// Records the given amount of generations of the environment, as seen through the viewport,
+/// into an animated GIF. The environment itself is not modified.
+pub fn record_gif<W: Write>(environment: &Environment, viewport: &Viewport, frames: usize,
+                            options: &AnimationOptions, writer: W) -> Result<(), ExportError> {
+    let (width, height) = raster::scaled_size(viewport.cells().size(), options.scale)?;
+    let (width, height) = (u16::try_from(width).map_err(|_| ExportError::TooLarge)?,
+                           u16::try_from(height).map_err(|_| ExportError::TooLarge)?);
+
+    // Cells are rendered as indices of the palette
+    let palette = [DEAD.channels(), LIVING.channels()].concat();
+    let mut encoder = gif::Encoder::new(writer, width, height, &palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    let mut environment = environment.clone();
+    let mut viewport = Viewport::new(viewport.x(), viewport.y(), viewport.width(), viewport.height());
+    for _ in 0..frames {
+        environment.fill_viewport(&mut viewport);
+
+        let frame = gif::Frame {
+            width,
+            height,
+            delay: options.frame_delay,
+            buffer: Cow::Owned(Raster::from_cells(viewport.cells(), options.scale, 0, 1)?.into_pixels()),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame)?;
+
+        environment.simulate();
+    }
+
+    Ok(())
+}
+
+/// Writes the viewport as a grayscale PNG image
+pub fn write_png<W: Write>(viewport: &Viewport, scale: usize, writer: W) -> Result<(), ExportError> {
+    raster::write_png(&render_gray(viewport, scale)?, writer)?;
+
+    Ok(())
+}
+
+/// Writes the given amount of generations of the environment, as seen through the viewport, as
+/// numbered PNG images (`frame_00000.png`, `frame_00001.png`, ...) in the directory. These can
+/// be assembled into a video with external encoders. The environment itself is not modified.
+pub fn write_png_frames(environment: &Environment, viewport: &Viewport, frames: usize,
+                        options: &AnimationOptions, directory: &Path) -> Result<(), ExportError> {
+    fs::create_dir_all(directory)?;
+
+    let mut environment = environment.clone();
+    let mut viewport = Viewport::new(viewport.x(), viewport.y(), viewport.width(), viewport.height());
+    for frame in 0..frames {
+        environment.fill_viewport(&mut viewport);
+
+        let file = fs::File::create(directory.join(format!("frame_{:05}.png", frame)))?;
+        write_png(&viewport, options.scale, BufWriter::new(file))?;
+
+        environment.simulate();
+    }
+
+    Ok(())
+}
+
+/// Records the given amount of generations of the environment, as seen through the viewport,
+/// into an animated PNG. The environment itself is not modified.
+pub fn record_apng<W: Write>(environment: &Environment, viewport: &Viewport, frames: usize,
+                             options: &AnimationOptions, writer: W) -> Result<(), ExportError> {
+    let frame_count = u32::try_from(frames).map_err(|_| ExportError::TooLarge)?;
+    let (width, height) = raster::scaled_size(viewport.cells().size(), options.scale)?;
+    let mut encoder = raster::png_encoder(width, height, writer)?;
+    encoder.set_animated(frame_count, 0)?;
+    encoder.set_frame_delay(options.frame_delay, 100)?;
+    let mut writer = encoder.write_header()?;
+
+    let mut environment = environment.clone();
+    let mut viewport = Viewport::new(viewport.x(), viewport.y(), viewport.width(), viewport.height());
+    for _ in 0..frames {
+        environment.fill_viewport(&mut viewport);
+        writer.write_image_data(render_gray(&viewport, options.scale)?.pixels())?;
+        environment.simulate();
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Renders the viewport in gray levels, with each cell using `scale` pixels
+fn render_gray(viewport: &Viewport, scale: usize) -> Result<Raster, RenderError> {
+    Raster::from_cells(viewport.cells(), scale, DEAD.luma(), LIVING.luma())
+}
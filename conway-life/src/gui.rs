@@ -0,0 +1,196 @@
+//! Windowed user interface, drawing the environment with a zoom level that is not limited by
+//! the resolution of the terminal.
+//!
+//! The mouse wheel zooms around the pointer, the middle button (or the arrow keys) moves around,
+//! and the left and right buttons paint living and dead cells.
+
+use std::time::{Duration, Instant};
+
+use eframe::egui::{self, Color32, Key, PointerButton, Pos2, Rect, Sense, Vec2};
+use thiserror::Error;
+
+use crate::{Environment, SimCell, Viewport};
+
+/// Size of a cell when the window opens, in points
+const DEFAULT_CELL_SIZE: f32 = 8.0;
+/// Smallest and largest size of a cell, in points
+const CELL_SIZE_RANGE: (f32, f32) = (1.0, 64.0);
+/// Cells moved by each press of an arrow key, at the default cell size
+const PAN_STEP: f32 = 40.0;
+
+#[derive(Error, Debug)]
+pub enum GuiError {
+    #[error("Unable to open the window: {0}")]
+    Window(String),
+}
+
+impl From<GuiError> for errors::Error {
+    fn from(error: GuiError) -> Self {
+        errors::Error::io("Unable to run the window").with_source(error)
+    }
+}
+
+/// Opens a window simulating the given environment, until it is closed
+pub fn run(environment: Environment) -> Result<(), GuiError> {
+    let options = eframe::NativeOptions {
+        initial_window_size: Some(Vec2::new(1024.0, 768.0)),
+        ..Default::default()
+    };
+
+    eframe::run_native("Conway's Game of Life", options,
+                       Box::new(|_| Box::new(GuiApp::new(environment))))
+        .map_err(|err| GuiError::Window(err.to_string()))
+}
+
+/// State of the window
+struct GuiApp {
+    environment: Environment,
+    generation: usize,
+    paused: bool,
+    /// Time between generations, in milliseconds
+    tick_ms: u64,
+    last_tick: Instant,
+    /// Position of the top left corner of the window within the environment, in cells
+    corner: Vec2,
+    /// Size of a cell, in points
+    cell_size: f32,
+}
+
+impl GuiApp {
+    fn new(environment: Environment) -> Self {
+        GuiApp {
+            environment,
+            generation: 0,
+            paused: false,
+            tick_ms: 50,
+            last_tick: Instant::now(),
+            corner: Vec2::new(-40.0, 30.0),
+            cell_size: DEFAULT_CELL_SIZE,
+        }
+    }
+
+    /// Returns the cell below a position of the canvas
+    fn cell_at(&self, canvas: Rect, position: Pos2) -> SimCell {
+        let offset = (position - canvas.min) / self.cell_size;
+        SimCell::new((self.corner.x + offset.x).floor() as i32, (self.corner.y - offset.y).ceil() as i32)
+    }
+
+    /// Changes the size of the cells, keeping the cell under the pointer in place
+    fn zoom(&mut self, canvas: Rect, pointer: Pos2, factor: f32) {
+        let offset = pointer - canvas.min;
+        let before = offset / self.cell_size;
+        self.cell_size = (self.cell_size * factor).clamp(CELL_SIZE_RANGE.0, CELL_SIZE_RANGE.1);
+        let after = offset / self.cell_size;
+
+        self.corner.x += before.x - after.x;
+        self.corner.y -= before.y - after.y;
+    }
+
+    /// Draws the visible cells on the canvas
+    fn draw(&self, painter: &egui::Painter, canvas: Rect) {
+        painter.rect_filled(canvas, 0.0, Color32::BLACK);
+
+        // The viewport covers every cell that is at least partially visible
+        let columns = (canvas.width() / self.cell_size).ceil() as usize + 1;
+        let rows = (canvas.height() / self.cell_size).ceil() as usize + 1;
+        let mut viewport = Viewport::new(self.corner.x.floor() as i32, self.corner.y.ceil() as i32,
+                                         columns.max(1), rows.max(1));
+        self.environment.fill_viewport(&mut viewport);
+
+        let origin = canvas.min + Vec2::new(
+            (viewport.x() as f32 - self.corner.x) * self.cell_size,
+            (self.corner.y - viewport.y() as f32) * self.cell_size,
+        );
+        let size = Vec2::splat((self.cell_size - 1.0).max(1.0));
+        for point in viewport.points::<u32>(1).unwrap_or_default() {
+            let position = origin + Vec2::new(point.x as f32, point.y as f32) * self.cell_size;
+            painter.rect_filled(Rect::from_min_size(position, size), 0.0, Color32::LIGHT_GREEN);
+        }
+    }
+
+    /// Handles the mouse and keyboard over the canvas
+    fn handle_input(&mut self, ui: &egui::Ui, response: &egui::Response, canvas: Rect) {
+        let (scroll, keys) = ui.input(|i| (i.scroll_delta.y, [
+            i.key_pressed(Key::ArrowLeft), i.key_pressed(Key::ArrowRight),
+            i.key_pressed(Key::ArrowUp), i.key_pressed(Key::ArrowDown),
+        ]));
+
+        if let Some(pointer) = response.hover_pos() {
+            if scroll != 0.0 {
+                self.zoom(canvas, pointer, (scroll / 200.0).exp());
+            }
+
+            let cell = self.cell_at(canvas, pointer);
+            if response.dragged_by(PointerButton::Primary) || response.clicked() {
+                self.environment.set_living(&[cell]);
+            } else if response.dragged_by(PointerButton::Secondary) || response.secondary_clicked() {
+                self.environment.set_region((cell, cell), false);
+            }
+        }
+
+        if response.dragged_by(PointerButton::Middle) {
+            let delta = response.drag_delta() / self.cell_size;
+            self.corner.x -= delta.x;
+            self.corner.y += delta.y;
+        }
+
+        let step = PAN_STEP * DEFAULT_CELL_SIZE / self.cell_size;
+        let [left, right, up, down] = keys;
+        self.corner.x += step * (right as i32 - left as i32) as f32;
+        self.corner.y += step * (up as i32 - down as i32) as f32;
+    }
+}
+
+impl eframe::App for GuiApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Simulation
+        let tick = Duration::from_millis(self.tick_ms);
+        if !self.paused && self.last_tick.elapsed() >= tick {
+            self.environment.simulate();
+            self.generation += 1;
+            self.last_tick = Instant::now();
+        }
+
+        egui::TopBottomPanel::top("controls").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(if self.paused { "Run" } else { "Pause" }).clicked()
+                    || ui.input(|i| i.key_pressed(Key::Space)) {
+                    self.paused = !self.paused;
+                }
+                if ui.add_enabled(self.paused, egui::Button::new("Step")).clicked() {
+                    self.environment.simulate();
+                    self.generation += 1;
+                }
+                if ui.button("Clear").clicked() {
+                    self.environment.clear();
+                    self.generation = 0;
+                }
+                ui.add(egui::Slider::new(&mut self.tick_ms, 1..=1000).logarithmic(true).text("ms"));
+                ui.separator();
+                ui.label(format!("GEN {}", self.generation));
+                ui.label(format!("POP {}", self.environment.get_living_count()));
+                ui.label(self.environment.rule().to_string());
+            });
+        });
+
+        egui::CentralPanel::default().frame(egui::Frame::none()).show(ctx, |ui| {
+            let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+            let canvas = response.rect;
+            self.handle_input(ui, &response, canvas);
+            self.draw(&painter, canvas);
+
+            // Position of the cell under the pointer
+            if let Some(pointer) = response.hover_pos() {
+                let cell = self.cell_at(canvas, pointer);
+                painter.text(canvas.right_bottom() - Vec2::splat(8.0), egui::Align2::RIGHT_BOTTOM,
+                             cell.to_string(), egui::FontId::monospace(14.0), Color32::GRAY);
+            }
+        });
+
+        if self.paused {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        } else {
+            ctx.request_repaint_after(tick.saturating_sub(self.last_tick.elapsed()));
+        }
+    }
+}
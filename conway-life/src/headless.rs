@@ -0,0 +1,198 @@
+//! Runs simulations without the terminal user interface, so they can be used from scripts,
+//! CI experiments or benchmarks.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::{Environment, Rule, SimCell};
+use crate::image::{self, ImageError, ImageOptions};
+use crate::pattern::{self, PatternError, PatternFormat};
+
+#[derive(Error, Debug)]
+pub enum HeadlessError {
+    #[error("Unable to read or write pattern file")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid pattern")]
+    Pattern(#[from] PatternError),
+
+    #[error("Unable to load image")]
+    Image(#[from] ImageError),
+
+    #[error("Unable to download pattern: {0}")]
+    Http(String),
+}
+
+impl From<HeadlessError> for errors::Error {
+    fn from(error: HeadlessError) -> Self {
+        let category = match &error {
+            HeadlessError::Io(_) | HeadlessError::Http(_) => errors::Error::io("Unable to read or write the pattern"),
+            HeadlessError::Pattern(_) | HeadlessError::Image(_) => errors::Error::parse("Unable to load the pattern"),
+        };
+        category.with_source(error)
+    }
+}
+
+/// Path used to read a pattern from the standard input or write it to the standard output
+pub const STANDARD_STREAM: &str = "-";
+
+/// Options for a headless run
+#[derive(Debug, Default)]
+pub struct HeadlessOptions {
+    /// Pattern to load, or [STANDARD_STREAM] to read it from the input. An empty environment
+    /// is used if not given.
+    pub pattern: Option<PathBuf>,
+    /// Amount of generations to simulate
+    pub generations: usize,
+    /// Rule to use instead of the one in the pattern
+    pub rule: Option<Rule>,
+    /// File where the resulting environment is written, or [STANDARD_STREAM] to write it to
+    /// the output.
+    pub out: Option<PathBuf>,
+    /// Format of the resulting environment. If not given it is deduced from the `out` file
+    /// extension, falling back to RLE.
+    pub format: Option<PatternFormat>,
+    /// Conversion of the pattern into cells, if it is an image
+    pub image: ImageOptions,
+}
+
+impl HeadlessOptions {
+    /// Returns true if the resulting environment is written to the output stream
+    pub fn writes_to_output(&self) -> bool {
+        self.out.as_deref().is_some_and(is_standard_stream)
+    }
+}
+
+/// Statistics of a finished headless run
+#[derive(Debug)]
+pub struct Summary {
+    pub generations: usize,
+    pub population: usize,
+    pub bounding_box: Option<(SimCell, SimCell)>,
+    pub elapsed: Duration,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Generations: {}", self.generations)?;
+        writeln!(f, "Population: {}", self.population)?;
+        match self.bounding_box {
+            Some((min, max)) => writeln!(f, "Bounding box: {} to {}", min, max)?,
+            None => writeln!(f, "Bounding box: empty")?,
+        }
+
+        let generations_per_second = self.generations as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON);
+        write!(f, "Time: {}µs ({:.0} generations/s)", self.elapsed.as_micros(), generations_per_second)
+    }
+}
+
+/// Simulates the given amount of generations and returns the statistics of the run
+pub fn simulate(environment: &mut Environment, generations: usize) -> Summary {
+    let start_instant = Instant::now();
+    for _ in 0..generations {
+        environment.simulate();
+    }
+    let elapsed = start_instant.elapsed();
+
+    Summary {
+        generations,
+        population: environment.get_living_count(),
+        bounding_box: environment.bounding_box(),
+        elapsed,
+    }
+}
+
+/// Loads the pattern in the options, simulates it and writes the result if requested. The
+/// given input and output are used in place of files named [STANDARD_STREAM].
+pub fn run(options: &HeadlessOptions, mut input: impl Read, mut output: impl Write) -> Result<Summary, HeadlessError> {
+    let mut environment = match &options.pattern {
+        Some(path) if is_standard_stream(path) => {
+            let mut data = String::new();
+            input.read_to_string(&mut data)?;
+            pattern::parse(&data, pattern::detect_format(&data))?
+        }
+        Some(path) => load_with(path, &options.image)?,
+        None => Environment::default(),
+    };
+    if let Some(rule) = options.rule {
+        environment.set_rule(rule);
+    }
+
+    let summary = simulate(&mut environment, options.generations);
+
+    if let Some(path) = &options.out {
+        let format = match options.format {
+            Some(format) => format,
+            None => PatternFormat::from_path(path).unwrap_or(PatternFormat::Rle),
+        };
+        let data = pattern::write(&environment, format)?;
+
+        if is_standard_stream(path) {
+            output.write_all(data.as_bytes())?;
+            output.flush()?;
+        } else {
+            fs::write(path, data)?;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Loads an environment from a pattern file, using its extension to detect the format. Paths
+/// that are http(s) URLs are downloaded with [fetch].
+pub fn load(path: &Path) -> Result<Environment, HeadlessError> {
+    load_with(path, &ImageOptions::default())
+}
+
+/// Loads an environment like [load], converting images (`.png`, `.bmp`) with the given options
+pub fn load_with(path: &Path, image: &ImageOptions) -> Result<Environment, HeadlessError> {
+    if let Some(url) = path.to_str().filter(|p| is_url(p)) {
+        return fetch(url);
+    }
+    if image::is_image(path) {
+        return Ok(image::load(path, image)?);
+    }
+
+    let format = format_from_path(path)?;
+    let data = fs::read_to_string(path)?;
+    Ok(pattern::parse(&data, format)?)
+}
+
+/// Returns true if the location is an http(s) URL
+pub fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// Downloads a pattern. The format is taken from the extension in the URL, or detected from
+/// the contents if it has none.
+#[cfg(feature = "url")]
+pub fn fetch(url: &str) -> Result<Environment, HeadlessError> {
+    let response = ureq::get(url).call().map_err(|err| HeadlessError::Http(err.to_string()))?;
+    let data = response.into_string()?;
+
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let format = PatternFormat::from_path(Path::new(path)).unwrap_or_else(|| pattern::detect_format(&data));
+    Ok(pattern::parse(&data, format)?)
+}
+
+/// Downloading patterns requires the `url` feature
+#[cfg(not(feature = "url"))]
+pub fn fetch(url: &str) -> Result<Environment, HeadlessError> {
+    Err(HeadlessError::Http(format!("{} (built without the url feature)", url)))
+}
+
+/// Returns the format of a pattern file
+fn format_from_path(path: &Path) -> Result<PatternFormat, PatternError> {
+    PatternFormat::from_path(path)
+        .ok_or_else(|| PatternError::UnknownFormat(path.display().to_string()))
+}
+
+/// Returns true if the path refers to the standard input or output
+fn is_standard_stream(path: &Path) -> bool {
+    path.as_os_str() == STANDARD_STREAM
+}
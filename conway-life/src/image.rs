@@ -0,0 +1,294 @@
+//! Seeding of environments from PNG and BMP images.
+//!
+//! Pixels are first turned into shades of gray, with transparent pixels counting as white, and
+//! averaged over blocks of `scale` x `scale` pixels, one for each cell. Cells whose shade is
+//! darker than the threshold come alive, so dark logos on a light background work as they are.
+//! Dithering spreads the error of each cell to its neighbours instead, which keeps the shading
+//! of photographs.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Environment, SimCell};
+
+/// Largest width or height of an image, in pixels
+const MAX_IMAGE_SIZE: usize = 16 * 1024;
+
+#[derive(Error, Debug)]
+pub enum ImageError {
+    #[error("Unable to read image file")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid PNG image")]
+    Png(#[from] png::DecodingError),
+
+    #[error("Invalid BMP image: {0}")]
+    Bmp(String),
+
+    #[error("Unsupported image: {0}")]
+    Unsupported(String),
+}
+
+/// Settings of the conversion of an image into living cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageOptions {
+    /// Width and height in pixels of the block that becomes a single cell
+    pub scale: usize,
+    /// Shades darker than this come alive, from 0 (black) to 255 (white)
+    pub threshold: u8,
+    /// Use Floyd-Steinberg dithering instead of a fixed threshold
+    pub dither: bool,
+    /// Bring the light pixels to life instead of the dark ones
+    pub invert: bool,
+    /// Cell where the top left corner of the image is placed. The image is centered on the
+    /// origin if not given.
+    pub origin: Option<SimCell>,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            scale: 1,
+            threshold: 128,
+            dither: false,
+            invert: false,
+            origin: None,
+        }
+    }
+}
+
+/// Formats of the images that can be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Bmp,
+}
+
+impl ImageFormat {
+    /// Guesses the format of an image from its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = ImageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(ImageFormat::Png),
+            "bmp" => Ok(ImageFormat::Bmp),
+            _ => Err(ImageError::Unsupported(s.to_string())),
+        }
+    }
+}
+
+/// An image in shades of gray, from 0 (black) to 255 (white)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    /// Shades of the pixels, row by row from the top
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    /// Returns the average shade of the pixels of a block, clipped to the image
+    fn block(&self, column: usize, row: usize, scale: usize) -> f32 {
+        let (mut total, mut count) = (0u32, 0u32);
+        for y in (row * scale)..((row + 1) * scale).min(self.height) {
+            for x in (column * scale)..((column + 1) * scale).min(self.width) {
+                total += u32::from(self.pixels[y * self.width + x]);
+                count += 1;
+            }
+        }
+
+        total as f32 / count.max(1) as f32
+    }
+}
+
+/// Returns true if the path has the extension of a supported image
+pub fn is_image(path: &Path) -> bool {
+    ImageFormat::from_path(path).is_some()
+}
+
+/// Loads an image file and converts it into an environment
+pub fn load(path: &Path, options: &ImageOptions) -> Result<Environment, ImageError> {
+    let format = ImageFormat::from_path(path)
+        .ok_or_else(|| ImageError::Unsupported(path.display().to_string()))?;
+    let data = fs::read(path)?;
+    let image = match format {
+        ImageFormat::Png => decode_png(&data)?,
+        ImageFormat::Bmp => decode_bmp(&data)?,
+    };
+
+    Ok(to_environment(&image, options))
+}
+
+/// Converts an image into an environment, one cell for each block of pixels
+pub fn to_environment(image: &GrayImage, options: &ImageOptions) -> Environment {
+    let scale = options.scale.max(1);
+    let width = image.width.div_ceil(scale);
+    let height = image.height.div_ceil(scale);
+
+    let mut shades: Vec<f32> = (0..height)
+        .flat_map(|row| (0..width).map(move |column| (column, row)))
+        .map(|(column, row)| image.block(column, row, scale))
+        .map(|shade| if options.invert { 255.0 - shade } else { shade })
+        .collect();
+
+    let threshold = f32::from(options.threshold);
+    let mut grid = vec![vec![false; width]; height];
+    for (row, cells) in grid.iter_mut().enumerate() {
+        for (column, cell) in cells.iter_mut().enumerate() {
+            let shade = shades[row * width + column];
+            let alive = shade < threshold;
+            *cell = alive;
+
+            if options.dither {
+                // Floyd-Steinberg: the error goes to the cells not visited yet
+                let error = shade - if alive { 0.0 } else { 255.0 };
+                let mut spread = |column: Option<usize>, row: usize, weight: f32| {
+                    if let Some(column) = column.filter(|&c| c < width && row < height) {
+                        shades[row * width + column] += error * weight;
+                    }
+                };
+                spread(Some(column + 1), row, 7.0 / 16.0);
+                spread(column.checked_sub(1), row + 1, 3.0 / 16.0);
+                spread(Some(column), row + 1, 5.0 / 16.0);
+                spread(Some(column + 1), row + 1, 1.0 / 16.0);
+            }
+        }
+    }
+
+    let origin = options.origin
+        .unwrap_or_else(|| SimCell::new(-((width / 2) as i32), (height / 2) as i32));
+    let mut environment = Environment::default();
+    environment.set_from_grid(origin, &grid);
+
+    environment
+}
+
+/// Decodes a PNG image of any color type and bit depth
+pub fn decode_png(data: &[u8]) -> Result<GrayImage, ImageError> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    check_size(reader.info().width as usize, reader.info().height as usize)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => return Err(ImageError::Unsupported(String::from("indexed PNG"))),
+    };
+    let pixels = buffer[..info.buffer_size()].chunks_exact(channels)
+        .map(|pixel| match pixel {
+            [gray] => *gray,
+            [gray, alpha] => over_white(*gray, *alpha),
+            [red, green, blue] => luma(*red, *green, *blue),
+            [red, green, blue, alpha] => over_white(luma(*red, *green, *blue), *alpha),
+            _ => unreachable!("pixels have up to 4 channels"),
+        })
+        .collect();
+
+    Ok(GrayImage { width: info.width as usize, height: info.height as usize, pixels })
+}
+
+/// Decodes an uncompressed BMP image with 1, 4, 8, 24 or 32 bits per pixel
+pub fn decode_bmp(data: &[u8]) -> Result<GrayImage, ImageError> {
+    let invalid = |message: &str| ImageError::Bmp(message.to_string());
+    let u16_at = |offset: usize| data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("truncated header"));
+    let u32_at = |offset: usize| data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("truncated header"));
+
+    if !data.starts_with(b"BM") {
+        return Err(invalid("missing BM signature"));
+    }
+    let pixels_offset = u32_at(10)? as usize;
+    let header_size = u32_at(14)? as usize;
+    let width = u32_at(18)? as i32;
+    let height = u32_at(22)? as i32;
+    let bits = u16_at(28)?;
+    let compression = u32_at(30)?;
+    if header_size < 40 {
+        return Err(ImageError::Unsupported(String::from("BMP core header")));
+    }
+    // Bit fields are accepted for 32 bits images, assuming the usual BGRA order
+    if compression != 0 && !(compression == 3 && bits == 32) {
+        return Err(ImageError::Unsupported(String::from("compressed BMP")));
+    }
+    if width <= 0 || height == 0 {
+        return Err(invalid("invalid size"));
+    }
+
+    // Rows go upwards unless the height is negative
+    let (width, top_down) = (width as usize, height < 0);
+    let height = height.unsigned_abs() as usize;
+    check_size(width, height)?;
+
+    let palette = match bits {
+        1 | 4 | 8 => {
+            let colors = match u32_at(46)? {
+                0 => 1 << bits,
+                colors => colors as usize,
+            };
+            let start = 14 + header_size;
+            let table = data.get(start..start + colors * 4).ok_or_else(|| invalid("truncated palette"))?;
+            table.chunks_exact(4).map(|c| luma(c[2], c[1], c[0])).collect()
+        }
+        24 | 32 => Vec::new(),
+        _ => return Err(ImageError::Unsupported(format!("BMP with {} bits per pixel", bits))),
+    };
+
+    let stride = (width * bits as usize).div_ceil(32) * 4;
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in 0..height {
+        let source_row = if top_down { row } else { height - 1 - row };
+        let start = pixels_offset + source_row * stride;
+        let bytes = data.get(start..start + stride).ok_or_else(|| invalid("truncated pixels"))?;
+        for x in 0..width {
+            let shade = match bits {
+                24 => luma(bytes[x * 3 + 2], bytes[x * 3 + 1], bytes[x * 3]),
+                32 => luma(bytes[x * 4 + 2], bytes[x * 4 + 1], bytes[x * 4]),
+                _ => {
+                    let bit = x * bits as usize;
+                    let index = (bytes[bit / 8] >> (8 - bits as usize - bit % 8)) & ((1 << bits) - 1) as u8;
+                    *palette.get(index as usize).ok_or_else(|| invalid("color out of the palette"))?
+                }
+            };
+            pixels.push(shade);
+        }
+    }
+
+    Ok(GrayImage { width, height, pixels })
+}
+
+/// Rejects images too large to turn into cells
+fn check_size(width: usize, height: usize) -> Result<(), ImageError> {
+    if width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+        return Err(ImageError::Unsupported(format!("images larger than {0}x{0} pixels", MAX_IMAGE_SIZE)));
+    }
+
+    Ok(())
+}
+
+/// Returns the perceived brightness of a color
+fn luma(red: u8, green: u8, blue: u8) -> u8 {
+    ((299 * u32::from(red) + 587 * u32::from(green) + 114 * u32::from(blue)) / 1000) as u8
+}
+
+/// Blends a shade with its transparency over a white background
+fn over_white(shade: u8, alpha: u8) -> u8 {
+    ((u32::from(shade) * u32::from(alpha) + 255 * (255 - u32::from(alpha))) / 255) as u8
+}
@@ -1,13 +1,67 @@
-use std::collections::{BTreeSet, HashMap};
-use std::fmt::{Display, Formatter, Write};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use grid2d::{Bounds, Coord, Grid, Position, Size};
+use render::text::{self, Style};
 use serde::{Deserialize, Serialize};
 
+pub use rule::Rule;
+
+use pattern::PatternError;
+
 #[cfg(test)]
 mod tests;
 
 /// Contains the data for show a text based user interface and interact with an environment.
+#[cfg(feature = "tui")]
 pub mod application;
 
+/// Catalog of named patterns.
+pub mod catalog;
+
+/// Classification and naming of the objects left by a simulation.
+pub mod census;
+
+/// Configuration of the text based user interface.
+#[cfg(feature = "tui")]
+pub mod config;
+
+/// Comparison of a pattern under several rules.
+pub mod explore;
+
+/// Exports simulations as images and animations.
+pub mod export;
+
+/// Windowed user interface, with zoom and cell painting.
+#[cfg(feature = "gui")]
+pub mod gui;
+
+/// Runs simulations without a user interface.
+pub mod headless;
+
+/// Seeding of environments from images.
+pub mod image;
+
+/// Reading and writing of environments in common pattern file formats.
+pub mod pattern;
+
+/// Life-like rules used to evolve an environment.
+pub mod rule;
+
+/// Batch searches of random soups.
+pub mod search;
+
+/// Streams headless simulations to WebSocket clients.
+#[cfg(feature = "serve")]
+pub mod server;
+
+/// Random soups and detection of stagnated simulations, used by the screensaver mode.
+pub mod screensaver;
+
+/// Bindings to run the simulation in a browser.
+#[cfg(feature = "wasm")]
+pub mod web;
+
 /// Represents a single cell within the simulation
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
 pub struct SimCell {
@@ -29,91 +83,401 @@ impl SimCell {
     }
 }
 
-/// Represents an Environment that follows Conway's Game of Life rules. These are:
+impl From<SimCell> for Coord {
+    fn from(cell: SimCell) -> Self {
+        Coord::new(cell.x, cell.y)
+    }
+}
+
+impl From<Coord> for SimCell {
+    fn from(coord: Coord) -> Self {
+        SimCell::new(coord.x, coord.y)
+    }
+}
+
+/// Something that happens to a single cell during a simulation step
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum CellEvent {
+    Birth(SimCell),
+    Death(SimCell),
+}
+
+/// Receives the births and deaths of a simulation step, see [`Environment::simulate_observed`].
+/// Both methods do nothing by default, so only the interesting ones need to be implemented.
+pub trait Observer {
+    /// Called for every dead cell that becomes alive
+    fn on_birth(&mut self, _cell: SimCell) {}
+
+    /// Called for every living cell that dies
+    fn on_death(&mut self, _cell: SimCell) {}
+}
+
+/// Ignores every event
+impl Observer for () {}
+
+/// Collects the events, so they can be drained after the simulation step
+impl Observer for Vec<CellEvent> {
+    fn on_birth(&mut self, cell: SimCell) {
+        self.push(CellEvent::Birth(cell));
+    }
+
+    fn on_death(&mut self, cell: SimCell) {
+        self.push(CellEvent::Death(cell));
+    }
+}
+
+/// Cells that differ between two environments, see [`Environment::diff`]. Both lists are sorted.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Changes {
+    /// Cells that are only alive in the other environment
+    pub added: Vec<SimCell>,
+    /// Cells that are only alive in the original environment
+    pub removed: Vec<SimCell>,
+}
+
+impl Changes {
+    /// Returns true if both environments have the same living cells
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns the amount of cells that differ
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len()
+    }
+}
+
+/// Represents an Environment that follows Conway's Game of Life rules by default. These are:
 /// 1. Any live cell with fewer than two live neighbours dies, as if by underpopulation.
 /// 2. Any live cell with two or three live neighbours lives on to the next generation.
 /// 3. Any live cell with more than three live neighbours dies, as if by overpopulation.
 /// 4. Any dead cell with exactly three live neighbours becomes a live cell, as if by reproduction.
-#[derive(Debug, Default, Serialize, Deserialize)]
+///
+/// Any other Life-like [Rule] can be used instead. Rules with birth on 0 neighbours (B0) would
+/// fill the infinite plane, so they are emulated by alternating the state of the background:
+/// the environment only stores the cells that differ from it.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Environment {
+    /// Cells that differ from the background: the living cells unless the background is alive
     living_cells: BTreeSet<SimCell>,
+    #[serde(default, skip_serializing_if = "Rule::is_conway")]
+    rule: Rule,
+    /// State of every cell that is not stored, which is only alive with B0 rules
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    background: bool,
 }
 
 impl Environment {
+    /// Creates an empty environment that follows the given rule
+    pub fn with_rule(rule: Rule) -> Self {
+        Environment { living_cells: BTreeSet::new(), rule, background: false }
+    }
+
+    /// Returns the rule followed by the environment
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Changes the rule followed by the environment
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// Returns true if every cell that is not stored is alive, which happens with B0 rules
+    pub fn background(&self) -> bool {
+        self.background
+    }
+
     /// Returns true if the given cell is alive
     pub fn get_cell(&self, cell: &SimCell) -> bool {
-        self.living_cells.contains(cell)
+        self.living_cells.contains(cell) != self.background
     }
 
+    /// Returns the amount of living cells, or of dead cells if the background is alive
     pub fn get_living_count(&self) -> usize{
         self.living_cells.len()
     }
 
+    /// Returns an iterator over the living cells, ordered by x and then y. If the background is
+    /// alive these are the dead cells instead.
+    pub fn living_cells(&self) -> impl Iterator<Item=&SimCell> {
+        self.living_cells.iter()
+    }
+
+    /// Returns the smallest and largest corners of the area that contains every living cell,
+    /// or None if the environment is empty.
+    pub fn bounding_box(&self) -> Option<(SimCell, SimCell)> {
+        self.bounds().map(|bounds| (bounds.min.into(), bounds.max.into()))
+    }
+
+    /// Returns the bounds of the living cells, or None if the environment is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::from_coords(self.living_cells.iter().map(|cell| Coord::from(*cell)))
+    }
+
     /// Toggles a cell between living and dead.
     /// Returns the new value of the cell.
     pub fn toggle_cell(&mut self, cell: &SimCell) -> bool {
-        if self.get_cell(cell) {
-            // Set cell to dead
+        if self.living_cells.contains(cell) {
             self.living_cells.remove(cell);
-            false
         } else {
-            // Set cell to living
             self.living_cells.insert(*cell);
-            true
         }
+
+        self.get_cell(cell)
+    }
+
+    /// Kills every cell, keeping the rule
+    pub fn clear(&mut self) {
+        self.living_cells.clear();
+        self.background = false;
     }
 
     /// Sets a range to living
     pub fn set_living(&mut self, cells: &[SimCell]) {
-        self.living_cells.extend(cells.iter())
+        if self.background {
+            for cell in cells {
+                self.living_cells.remove(cell);
+            }
+        } else {
+            self.living_cells.extend(cells.iter())
+        }
+    }
+
+    /// Sets every cell of a region to living or dead. The region is given by its corners, as
+    /// returned by [`Environment::bounding_box`], and includes both of them.
+    pub fn set_region(&mut self, (min, max): (SimCell, SimCell), alive: bool) {
+        if min.x > max.x || min.y > max.y {
+            return;
+        }
+
+        if alive != self.background {
+            // Cells are created in order, so the set is built in bulk and merged at once
+            let mut region: BTreeSet<SimCell> = (min.x..=max.x)
+                .flat_map(|x| (min.y..=max.y).map(move |y| SimCell::new(x, y)))
+                .collect();
+            self.living_cells.append(&mut region);
+        } else {
+            // Only the columns of the region need to be checked
+            let dead: Vec<SimCell> = self.living_cells.range(min..=max)
+                .filter(|c| c.y >= min.y && c.y <= max.y)
+                .copied()
+                .collect();
+            for cell in dead.iter() {
+                self.living_cells.remove(cell);
+            }
+        }
+    }
+
+    /// Sets the cells of a grid of rows, with `true` for living cells and `false` for dead ones.
+    /// The first cell of the first row is placed at the origin, and rows go downwards.
+    pub fn set_from_grid<R: AsRef<[bool]>>(&mut self, origin: SimCell, grid: &[R]) {
+        let mut living = BTreeSet::new();
+        for (row, values) in grid.iter().enumerate() {
+            for (column, alive) in values.as_ref().iter().enumerate() {
+                let cell = SimCell::new(origin.x + column as i32, origin.y - row as i32);
+                if *alive != self.background {
+                    living.insert(cell);
+                } else {
+                    self.living_cells.remove(&cell);
+                }
+            }
+        }
+
+        self.living_cells.append(&mut living);
+    }
+
+    /// Adds the living cells of a pattern, placing the top left corner of its bounding box at
+    /// the given cell
+    pub fn place(&mut self, pattern: &Environment, top_left: SimCell) {
+        let Some((min, max)) = pattern.bounding_box() else {
+            return;
+        };
+
+        let cells: Vec<SimCell> = pattern.living_cells()
+            .map(|c| SimCell::new(top_left.x + c.x - min.x, top_left.y + c.y - max.y))
+            .collect();
+        self.set_living(&cells);
+    }
+
+    /// Returns the cells that differ from the other environment, as the changes needed to turn
+    /// this environment into the other one. Only the stored cells are compared, so both
+    /// environments should have the same background.
+    pub fn diff(&self, other: &Environment) -> Changes {
+        Changes {
+            added: other.living_cells.difference(&self.living_cells).copied().collect(),
+            removed: self.living_cells.difference(&other.living_cells).copied().collect(),
+        }
+    }
+
+    /// Applies the changes returned by [`Environment::diff`], turning this environment into the
+    /// other one. The background of the other environment is given along with them.
+    pub fn apply(&mut self, changes: &Changes, background: bool) {
+        for cell in changes.removed.iter() {
+            self.living_cells.remove(cell);
+        }
+        self.living_cells.extend(changes.added.iter());
+        self.background = background;
     }
 
     /// Performs a simulation step, following the rules for the environment
     pub fn simulate(&mut self) {
-        // Count how the neighborhood is affected
-        let mut neighboors = HashMap::with_capacity(self.living_cells.len() * 9);
-        for cell in self.living_cells.iter() {
-            for x in (cell.x - 1)..=(cell.x + 1) {
-                for y in (cell.y - 1)..=(cell.y + 1) {
-                    // Create neighboring cell
-                    let n = SimCell::new(x, y);
-                    if n == *cell { continue; }
-
-                    // Add to the neighbor
-                    let count = neighboors.entry(n).or_insert(0u32);
-                    *count += 1;
-                }
+        self.simulate_observed(&mut ());
+    }
+
+    /// Performs a simulation step, notifying the observer of every cell that is born or dies.
+    /// When the background changes, only the cells that differ from it are notified: births and
+    /// deaths are then relative to the background.
+    pub fn simulate_observed<O: Observer + ?Sized>(&mut self, observer: &mut O) {
+        // The living cells are sorted by column and then by row, and each column of the next
+        // generation only depends on the three columns around it. Merging those sorted columns
+        // counts the neighbors without hashing every neighbor of every cell.
+        let cells: Vec<SimCell> = self.living_cells.iter().copied().collect();
+        let rows: Vec<i32> = cells.iter().map(|c| c.y).collect();
+        let mut columns: Vec<(i32, &[i32])> = Vec::new();
+        let mut start = 0;
+        for (i, cell) in cells.iter().enumerate() {
+            if cells.get(i + 1).map(|c| c.x) != Some(cell.x) {
+                columns.push((cell.x, &rows[start..=i]));
+                start = i + 1;
             }
         }
 
-        // Add new cells
-        for new_living in neighboors.iter().filter(|(_, &v)| v == 3).map(|(c, _)| *c) {
-            self.living_cells.insert(new_living);
+        // The stored cells are simulated with the rule that gives their difference with the next
+        // background, which never has birth on 0 neighbours
+        let (rule, background) = self.rule.for_background(self.background);
+        let mut next = Vec::with_capacity(cells.len());
+        let mut row_counts = Vec::new();
+        let mut first_source = 0;
+        let mut last_x = None;
+        for &(column_x, _) in columns.iter() {
+            let from = last_x.map_or(column_x - 1, |last: i32| (last + 1).max(column_x - 1));
+            for x in from..=(column_x + 1) {
+                last_x = Some(x);
+                while columns[first_source].0 < x - 1 {
+                    first_source += 1;
+                }
+                let sources_count = columns[first_source..].iter()
+                    .take_while(|(source_x, _)| *source_x <= x + 1)
+                    .count();
+                let sources = &columns[first_source..first_source + sources_count];
+                let living = sources.iter().find(|(source_x, _)| *source_x == x).map_or(&[][..], |(_, rows)| *rows);
+
+                // Living cells in the three columns, per row
+                merge_rows(sources, &mut row_counts);
+
+                // Every row within one of a counted row may change
+                let mut window = 0;
+                let mut living_index = 0;
+                let mut last_y = None;
+                for &(counted_y, _) in row_counts.iter() {
+                    let from = last_y.map_or(counted_y - 1, |last: i32| (last + 1).max(counted_y - 1));
+                    for y in from..=(counted_y + 1) {
+                        last_y = Some(y);
+                        while row_counts[window].0 < y - 1 {
+                            window += 1;
+                        }
+                        while living_index < living.len() && living[living_index] < y {
+                            living_index += 1;
+                        }
+
+                        let alive = living.get(living_index) == Some(&y);
+                        let total: usize = row_counts[window..].iter()
+                            .take_while(|(row, _)| *row <= y + 1)
+                            .map(|(_, count)| count)
+                            .sum();
+                        let neighbors = total - usize::from(alive);
+
+                        let cell = SimCell::new(x, y);
+                        if alive {
+                            if rule.is_survival(neighbors) {
+                                next.push(cell);
+                            } else {
+                                observer.on_death(cell);
+                            }
+                        } else if rule.is_birth(neighbors) {
+                            observer.on_birth(cell);
+                            next.push(cell);
+                        }
+                    }
+                }
+            }
         }
 
-        // Remove any cell with less than 2 neighbors or more than 3
-        self.living_cells
-            .retain(|c|
-                if let Some(&count) = neighboors.get(c) {
-                    count == 2 || count == 3
-                } else {
-                    false
-                });
+        // Cells were visited in order, so the new set is built in bulk
+        self.living_cells = next.into_iter().collect();
+        self.background = background;
     }
 
     /// Fills in a Viewport with the information from the simulation
     pub fn fill_viewport(&self, viewport: &mut Viewport) {
-        viewport.clear();
+        viewport.data.fill(self.background);
 
         self.living_cells.iter().map(|c|
-            if viewport.in_viewport(c.x, c.y) {
-                viewport.set_living(c.x, c.y);
+            if let Some(point) = viewport.world_to_screen(*c) {
+                viewport.data[Position::new(point.x, point.y)] = !self.background;
             }
         ).count();
     }
 }
 
 
+/// Merges the sorted rows of up to three columns, counting the living cells of each row
+fn merge_rows(columns: &[(i32, &[i32])], counts: &mut Vec<(i32, usize)>) {
+    counts.clear();
+    let mut indices = [0; 3];
+    loop {
+        let next = columns.iter().zip(indices.iter())
+            .filter_map(|((_, rows), &index)| rows.get(index))
+            .min();
+        let Some(&row) = next else {
+            break;
+        };
+
+        let mut count = 0;
+        for ((_, rows), index) in columns.iter().zip(indices.iter_mut()) {
+            if rows.get(*index) == Some(&row) {
+                *index += 1;
+                count += 1;
+            }
+        }
+        counts.push((row, count));
+    }
+}
+
+impl FromStr for Environment {
+    type Err = PatternError;
+
+    /// Parses a grid of `x` characters, as printed by a viewport. See [`pattern::parse_ascii`]
+    /// for the header with the position and rule.
+    ///
+    /// ```
+    /// use conway_life::{Environment, SimCell};
+    ///
+    /// let glider: Environment = "@(5,5)\n x\n  x\nxxx".parse().unwrap();
+    /// assert!(glider.get_cell(&SimCell::new(6, 5)));
+    /// assert!(glider.get_cell(&SimCell::new(5, 3)));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        pattern::parse_ascii(s)
+    }
+}
+
+impl Display for Environment {
+    /// Displays the living cells as a grid of `x` characters that can be parsed back
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&pattern::to_ascii(self))
+    }
+}
+
+/// Position within a viewport, counted from its top left corner. `y` grows downwards, unlike
+/// the coordinates of the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScreenPoint<T> {
+    pub x: T,
+    pub y: T,
+}
+
 /// Represents a viewport of an environment at a given position.
 #[derive(Debug)]
 pub struct Viewport {
@@ -121,7 +485,7 @@ pub struct Viewport {
     width: usize,
     y: i32,
     height: usize,
-    data: Vec<bool>,
+    data: Grid<bool>,
 }
 
 impl Viewport {
@@ -148,21 +512,50 @@ impl Viewport {
         assert!(!overflowing_size, "width * height results in overflow");
 
         // Create the viewport vector
-        let data = vec![false; width * height];
+        let data = Grid::new(Size::new(width, height), false);
         Viewport { x, width, y, height, data }
     }
 
-    /// Returns a vector with all the living points within the Viewport
-    pub fn get_points<T: From<i32>>(&self) -> Vec<(T, T)> {
-        let mut points = Vec::new();
+    /// Returns the position within the Viewport of the top left corner of every living cell,
+    /// with each cell being `scale` units wide, e.g. the pixels of a canvas. Fails if a position
+    /// doesn't fit in `T`.
+    pub fn points<T: TryFrom<usize>>(&self, scale: usize) -> Result<Vec<ScreenPoint<T>>, T::Error> {
+        self.data.iter()
+            .filter(|(_, living)| **living)
+            .map(|(position, _)| Ok(ScreenPoint {
+                x: T::try_from(position.column * scale)?,
+                y: T::try_from(position.row * scale)?,
+            }))
+            .collect()
+    }
 
-        for (index, _value) in self.data.iter().enumerate().filter(|&v| *v.1) {
-            let x = T::from((index % self.width) as i32 + self.x);
-            let y = T::from((index / self.width) as i32 - self.y);
-            points.push((x, y));
-        }
+    /// Returns the position within the Viewport of a cell of the environment, or None if the
+    /// cell is not visible
+    pub fn world_to_screen(&self, cell: SimCell) -> Option<ScreenPoint<usize>> {
+        self.in_viewport(cell.x, cell.y).then(|| ScreenPoint {
+            x: cell.x.abs_diff(self.x) as usize,
+            y: cell.y.abs_diff(self.y) as usize,
+        })
+    }
 
-        points
+    /// Returns the cell of the environment at a position within the Viewport, or None if the
+    /// position is outside of it
+    pub fn screen_to_world(&self, point: ScreenPoint<usize>) -> Option<SimCell> {
+        (point.x < self.width && point.y < self.height).then(|| SimCell::new(
+            self.x.wrapping_add_unsigned(point.x as u32),
+            self.y.wrapping_sub_unsigned(point.y as u32),
+        ))
+    }
+
+    /// Returns an iterator over the rows of the Viewport, from top to bottom. Each row contains
+    /// whether its cells are living, from left to right.
+    pub fn rows(&self) -> impl Iterator<Item=&[bool]> {
+        self.data.rows()
+    }
+
+    /// Returns whether each cell is living, to render the Viewport
+    pub fn cells(&self) -> &Grid<bool> {
+        &self.data
     }
 
     /// Clears the whole buffer, setting every cell as dead
@@ -173,20 +566,22 @@ impl Viewport {
     /// Returns if the given position is within the viewport
     #[inline]
     fn in_viewport(&self, x: i32, y: i32) -> bool {
-        x >= self.x && x < self.right() && y <= self.y && y > self.bottom()
+        self.bounds().contains(Coord::new(x, y))
+    }
+
+    /// Returns the bounds of the cells of the environment shown by the Viewport
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            min: Coord::new(self.x, self.bottom() + 1),
+            max: Coord::new(self.right() - 1, self.y),
+        }
     }
 
     /// Sets a position within the viewport as living
     pub fn set_living(&mut self, x: i32, y: i32) {
-        assert!(self.in_viewport(x, y));
-
-        let column = (x - self.x).unsigned_abs() as usize;
-        let row = (y - self.y).unsigned_abs() as usize;
-        let index = row * self.width + column;
-
-        if let Some(c) = self.data.get_mut(index) {
-            *c = true;
-        }
+        let point = self.world_to_screen(SimCell::new(x, y))
+            .expect("position should be within the viewport");
+        self.data[Position::new(point.x, point.y)] = true;
     }
 
 
@@ -226,21 +621,6 @@ impl Viewport {
 impl Display for Viewport {
     /// A simple text based display of the Viewport
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        const LIVING: char = 'x';
-        const DEAD: char = ' ';
-
-        for (i, val) in self.data.iter().enumerate() {
-            // Check if newline is needed
-            if i != 0 && i % self.width == 0 {
-                f.write_char('\n')?;
-            }
-            if *val {
-                f.write_char(LIVING)?;
-            } else {
-                f.write_char(DEAD)?;
-            }
-        }
-
-        Ok(())
+        text::write_cells(f, &self.data, Style::Ascii)
     }
 }
\ No newline at end of file
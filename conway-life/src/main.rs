@@ -1,6 +1,303 @@
-use conway_life::application::{App, ApplicationError};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+#[cfg(feature = "serve")]
+use std::time::Duration;
 
-fn main() -> Result<(), ApplicationError> {
-    let mut app = App::default();
-    app.run()
+use clap::Parser;
+use errors::Error;
+
+use conway_life::application::App;
+#[cfg(any(feature = "gui", feature = "serve"))]
+use conway_life::catalog::Catalog;
+use conway_life::config::Config;
+use conway_life::explore::{self, ExploreOptions, RuleSet};
+use conway_life::headless::{self, HeadlessOptions, STANDARD_STREAM};
+use conway_life::image::ImageOptions;
+use conway_life::pattern::PatternFormat;
+use conway_life::search::{self, SearchOptions};
+#[cfg(feature = "serve")]
+use conway_life::server::{self, ServeOptions, StreamFormat};
+use conway_life::{Rule, SimCell};
+
+/// Conway's Game of Life simulator. Without `--generations` the simulation is shown in a
+/// text based user interface.
+///
+/// In headless mode patterns are read from the standard input and written to the standard
+/// output when they are redirected, so runs can be chained in shell pipelines.
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Pattern to load (.rle, .cells or .con), or - to read it from the standard input. With
+    /// the url feature it can also be an http(s) URL. Images (.png or .bmp) are converted into
+    /// cells, with their dark pixels alive.
+    #[arg(long, short)]
+    pattern: Option<PathBuf>,
+
+    /// Pixels of an image pattern that become a single cell, in each direction
+    #[arg(long, default_value_t = 1)]
+    scale: usize,
+
+    /// Shade (0 to 255) under which the pixels of an image pattern come alive
+    #[arg(long, default_value_t = 128)]
+    threshold: u8,
+
+    /// Dither image patterns instead of using a fixed threshold
+    #[arg(long)]
+    dither: bool,
+
+    /// Bring the light pixels of an image pattern to life instead of the dark ones
+    #[arg(long)]
+    invert: bool,
+
+    /// Cell where the top left corner of an image pattern is placed, as x,y. Images are
+    /// centered if not given.
+    #[arg(long, value_parser = parse_origin, allow_hyphen_values = true)]
+    origin: Option<SimCell>,
+
+    /// Amount of generations to simulate without the user interface. With --search it is the
+    /// most generations a soup is simulated while waiting for it to settle, and with --explore
+    /// the generations simulated for each rule.
+    #[arg(long, short)]
+    generations: Option<usize>,
+
+    /// Rule to use, in B/S notation (e.g. B3/S23)
+    #[arg(long, short)]
+    rule: Option<Rule>,
+
+    /// File where the resulting pattern is written after a headless run (or the report of a
+    /// soup search), or - to write it to the standard output
+    #[arg(long, short)]
+    out: Option<PathBuf>,
+
+    /// Format of the resulting pattern (rle, cells, yaml or ascii)
+    #[arg(long, short)]
+    format: Option<PatternFormat>,
+
+    /// Configuration file of the user interface. Defaults to
+    /// ~/.config/conway-life/config.toml
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// File of commands to execute when the user interface starts, one per line
+    #[arg(long, short)]
+    script: Option<PathBuf>,
+
+    /// Show random soups that are reseeded whenever they stagnate
+    #[arg(long)]
+    screensaver: bool,
+
+    /// Seed of the random soups, so a run can be repeated. Save files record the seed in use
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Open a window instead of using the terminal
+    #[cfg(feature = "gui")]
+    #[arg(long)]
+    gui: bool,
+
+    /// Amount of random soups to search, writing a report of the objects they leave instead of
+    /// showing them. Soups are seeded from --seed onwards.
+    #[arg(long)]
+    search: Option<usize>,
+
+    /// Threads used by --search. Defaults to one per core.
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Rules to compare, as a list (B3/S23,B36/S23) or as the rules around another one
+    /// (around:B3/S23). Each rule simulates --pattern, or a random soup from --seed, for up to
+    /// --generations (1000 by default) and the results are written as a table.
+    #[arg(long)]
+    explore: Option<RuleSet>,
+
+    /// Write the results of --explore as comma separated values
+    #[arg(long)]
+    csv: bool,
+
+    /// Address where the simulation is streamed over WebSocket (e.g. 127.0.0.1:9001) instead
+    /// of showing it
+    #[cfg(feature = "serve")]
+    #[arg(long)]
+    serve: Option<String>,
+
+    /// Milliseconds between the generations streamed with --serve
+    #[cfg(feature = "serve")]
+    #[arg(long, default_value_t = 100)]
+    interval: u64,
+
+    /// Stream binary messages instead of JSON with --serve
+    #[cfg(feature = "serve")]
+    #[arg(long)]
+    binary: bool,
+
+    /// Don't print the summary of a headless run
+    #[arg(long, short)]
+    quiet: bool,
+}
+
+impl Cli {
+    /// Returns the options used to convert an image pattern into cells
+    fn image_options(&self) -> ImageOptions {
+        ImageOptions {
+            scale: self.scale,
+            threshold: self.threshold,
+            dither: self.dither,
+            invert: self.invert,
+            origin: self.origin,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    errors::report(run(Cli::parse()))
+}
+
+/// Runs the mode selected by the arguments
+fn run(args: Cli) -> Result<(), Error> {
+    let image = args.image_options();
+
+    // Rule exploration
+    if let Some(RuleSet(rules)) = args.explore {
+        let pattern = match &args.pattern {
+            Some(path) => headless::load_with(path, &image)?,
+            None => SearchOptions { seed: args.seed.unwrap_or_else(rand::random), ..SearchOptions::default() }.soup(0),
+        };
+        let options = ExploreOptions {
+            rules,
+            generations: args.generations.unwrap_or(ExploreOptions::default().generations),
+            ..ExploreOptions::default()
+        };
+
+        let exploration = explore::run(&pattern, &options);
+        let results = if args.csv { exploration.to_csv() } else { exploration.to_string() };
+        write_report(args.out.as_deref(), &results)?;
+        return Ok(());
+    }
+
+    // Soup search
+    if let Some(soups) = args.search {
+        let mut options = SearchOptions {
+            soups,
+            seed: args.seed.unwrap_or_else(rand::random),
+            max_generations: args.generations.unwrap_or(SearchOptions::default().max_generations),
+            ..SearchOptions::default()
+        };
+        if let Some(rule) = args.rule {
+            options.rule = rule;
+        }
+        if let Some(threads) = args.threads {
+            options.threads = threads;
+        }
+
+        write_report(args.out.as_deref(), &search::run(&options).to_string())?;
+        return Ok(());
+    }
+
+    // Headless run
+    if let Some(generations) = args.generations {
+        // Use the standard streams when they are redirected
+        let pattern = args.pattern
+            .or_else(|| (!io::stdin().is_terminal()).then(|| PathBuf::from(STANDARD_STREAM)));
+        let out = args.out
+            .or_else(|| (!io::stdout().is_terminal()).then(|| PathBuf::from(STANDARD_STREAM)));
+
+        let options = HeadlessOptions {
+            pattern,
+            generations,
+            rule: args.rule,
+            out,
+            format: args.format,
+            image,
+        };
+
+        let summary = headless::run(&options, io::stdin().lock(), io::stdout().lock())?;
+        if !args.quiet {
+            // Keep the standard output clean when the pattern is written there
+            if options.writes_to_output() {
+                eprintln!("{summary}");
+            } else {
+                println!("{summary}");
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Streamed run
+    #[cfg(feature = "serve")]
+    if let Some(address) = &args.serve {
+        let mut environment = match &args.pattern {
+            Some(path) => headless::load_with(path, &image)?,
+            None => Catalog::default().find("R-pentomino").expect("built in pattern").environment()?,
+        };
+        if let Some(rule) = args.rule {
+            environment.set_rule(rule);
+        }
+        let options = ServeOptions {
+            environment,
+            interval: Duration::from_millis(args.interval),
+            format: if args.binary { StreamFormat::Binary } else { StreamFormat::Json },
+        };
+        server::serve(address.as_str(), options)?;
+        return Ok(());
+    }
+
+    // Windowed run
+    #[cfg(feature = "gui")]
+    if args.gui {
+        let mut environment = match &args.pattern {
+            Some(path) => headless::load_with(path, &image)?,
+            None => Catalog::default().find("R-pentomino").expect("built in pattern").environment()?,
+        };
+        if let Some(rule) = args.rule {
+            environment.set_rule(rule);
+        }
+        conway_life::gui::run(environment)?;
+        return Ok(());
+    }
+
+    // Interactive run
+    let config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::load_default()?,
+    };
+    let mut app = App::from_config(&config)?;
+    if let Some(path) = &args.pattern {
+        app.set_environment(headless::load_with(path, &image)?);
+    }
+    if let Some(rule) = args.rule {
+        app.set_rule(rule);
+    }
+    if let Some(seed) = args.seed {
+        app.set_seed(seed);
+    }
+    if args.screensaver {
+        app.start_screensaver();
+    }
+    if let Some(path) = &args.script {
+        app.set_script(path)?;
+    }
+    app.run()?;
+
+    Ok(())
+}
+
+/// Parses a cell given as x,y
+fn parse_origin(s: &str) -> Result<SimCell, String> {
+    let (x, y) = s.split_once(',').ok_or_else(|| String::from("expected x,y"))?;
+    let coordinate = |c: &str| c.trim().parse::<i32>().map_err(|err| err.to_string());
+
+    Ok(SimCell::new(coordinate(x)?, coordinate(y)?))
+}
+
+/// Writes a report to a file, or to the standard output if the path is missing or -
+fn write_report(path: Option<&Path>, report: &str) -> io::Result<()> {
+    match path {
+        Some(path) if path.as_os_str() != STANDARD_STREAM => fs::write(path, report),
+        _ => {
+            print!("{report}");
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,453 @@
+//! Reading and writing of environments in the formats commonly used to share Life patterns.
+//!
+//! Pattern files count rows downwards, while the environment `y` axis grows upwards. The first
+//! row of a pattern is placed at `y = 0` and the following rows at negative `y` values, unless
+//! the file specifies a position.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Environment, Rule, SimCell};
+use crate::rule::RuleError;
+
+/// Maximum length of the lines written in RLE files
+const RLE_LINE_LENGTH: usize = 70;
+
+/// Largest width and height of the RLE patterns that are read, so a huge run count fails instead
+/// of allocating every cell
+pub const MAX_RLE_SIZE: i32 = 1 << 20;
+
+#[derive(Error, Debug)]
+pub enum PatternError {
+    #[error("Unknown pattern format: {0}")]
+    UnknownFormat(String),
+
+    #[error("Invalid RLE header: {0}")]
+    InvalidHeader(String),
+
+    #[error("Invalid character '{0}' in pattern")]
+    InvalidCharacter(char),
+
+    #[error("Invalid run count in pattern")]
+    InvalidCount,
+
+    #[error("Patterns can be at most {0} cells wide and tall")]
+    TooLarge(i32),
+
+    #[error("Invalid rule in pattern")]
+    Rule(#[from] RuleError),
+
+    #[error("Invalid YAML environment")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("The background is alive, only YAML environments can keep it")]
+    AliveBackground,
+}
+
+impl From<PatternError> for errors::Error {
+    fn from(error: PatternError) -> Self {
+        let category = match &error {
+            PatternError::UnknownFormat(_) => errors::Error::format("Unsupported pattern format"),
+            PatternError::AliveBackground => errors::Error::format("Unable to write the pattern"),
+            _ => errors::Error::parse("Invalid pattern"),
+        };
+        category.with_source(error)
+    }
+}
+
+/// Supported pattern file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternFormat {
+    /// Run Length Encoded format (`.rle`)
+    Rle,
+    /// Plaintext format (`.cells`)
+    Cells,
+    /// Serialized environment (`.con`, `.yaml`)
+    Yaml,
+    /// Grid of `x` characters, as shown by a viewport. Has no extension.
+    Ascii,
+}
+
+impl PatternFormat {
+    /// Guesses the format of a file from its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for PatternFormat {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rle" => Ok(PatternFormat::Rle),
+            "cells" | "txt" => Ok(PatternFormat::Cells),
+            "con" | "yaml" | "yml" => Ok(PatternFormat::Yaml),
+            "ascii" => Ok(PatternFormat::Ascii),
+            _ => Err(PatternError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Guesses the format of a pattern from its contents. Grids of `x` characters are recognised
+/// with or without their header. Defaults to RLE.
+pub fn detect_format(input: &str) -> PatternFormat {
+    let first_line = input.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+
+    if first_line.starts_with("living_cells") || first_line.starts_with("rule:") {
+        PatternFormat::Yaml
+    } else if first_line.starts_with('@') {
+        PatternFormat::Ascii
+    } else if first_line.starts_with('!') || first_line.chars().all(|c| matches!(c, '.' | 'O' | '*')) {
+        PatternFormat::Cells
+    } else if first_line.chars().all(|c| matches!(c, 'x' | ' ' | '.')) {
+        PatternFormat::Ascii
+    } else {
+        PatternFormat::Rle
+    }
+}
+
+/// Parses an environment like [parse], failing if it is wider or taller than the given size. RLE
+/// patterns are checked while they are read, before their cells are created.
+pub fn parse_bounded(input: &str, format: PatternFormat, max_size: i32) -> Result<Environment, PatternError> {
+    if format == PatternFormat::Rle {
+        return parse_rle_bounded(input, max_size);
+    }
+
+    let environment = parse(input, format)?;
+    if let Some((min, max)) = environment.bounding_box() {
+        if max.x.abs_diff(min.x) >= max_size as u32 || max.y.abs_diff(min.y) >= max_size as u32 {
+            return Err(PatternError::TooLarge(max_size));
+        }
+    }
+
+    Ok(environment)
+}
+
+/// Parses an environment in the given format
+pub fn parse(input: &str, format: PatternFormat) -> Result<Environment, PatternError> {
+    match format {
+        PatternFormat::Rle => parse_rle(input),
+        PatternFormat::Cells => parse_cells(input),
+        PatternFormat::Yaml => Ok(serde_yaml::from_str(input)?),
+        PatternFormat::Ascii => parse_ascii(input),
+    }
+}
+
+/// Writes an environment in the given format. Only YAML keeps an alive background, which
+/// happens on odd generations of B0 rules: the other formats would invert the pattern.
+pub fn write(environment: &Environment, format: PatternFormat) -> Result<String, PatternError> {
+    if environment.background() && format != PatternFormat::Yaml {
+        return Err(PatternError::AliveBackground);
+    }
+
+    match format {
+        PatternFormat::Rle => Ok(to_rle(environment)),
+        PatternFormat::Cells => Ok(to_cells(environment)),
+        PatternFormat::Yaml => Ok(serde_yaml::to_string(environment)?),
+        PatternFormat::Ascii => Ok(to_ascii(environment)),
+    }
+}
+
+/// Parses a pattern in Run Length Encoded format.
+///
+/// The position of the pattern is read from `#CXRLE Pos=x,y`, `#P x y` or `#R x y` lines, and
+/// the rule from the header line. Patterns without a header follow Conway's rule.
+pub fn parse_rle(input: &str) -> Result<Environment, PatternError> {
+    parse_rle_bounded(input, MAX_RLE_SIZE)
+}
+
+/// Parses a pattern in Run Length Encoded format, failing as soon as a run goes past the given
+/// width or height
+fn parse_rle_bounded(input: &str, max_size: i32) -> Result<Environment, PatternError> {
+    let mut environment = Environment::default();
+    let (mut origin_x, mut origin_y) = (0, 0);
+    let mut lines = input.lines().map(str::trim).filter(|l| !l.is_empty()).peekable();
+
+    // Comments and header
+    while let Some(line) = lines.next_if(|l| l.starts_with('#')) {
+        if let Some((x, y)) = parse_position(&line[1..]) {
+            (origin_x, origin_y) = (x, y);
+        }
+    }
+
+    // The header is the first line that is not a comment, and can be left out
+    if let Some(line) = lines.next_if(|l| l.contains('=')) {
+        for field in line.split(',') {
+            let (key, value) = field.split_once('=')
+                .ok_or_else(|| PatternError::InvalidHeader(line.to_string()))?;
+            match key.trim() {
+                "x" | "y" => {}
+                "rule" => environment.set_rule(value.trim().parse::<Rule>()?),
+                _ => return Err(PatternError::InvalidHeader(line.to_string())),
+            }
+        }
+    }
+
+    // Body. Runs are checked before they are added, so the pattern can't grow past the limit.
+    let advance = |position: i32, count: Option<i32>| position.checked_add(count.unwrap_or(1))
+        .filter(|&position| position <= max_size)
+        .ok_or(PatternError::TooLarge(max_size));
+    let cell = |column: i32, row: i32| origin_x.checked_add(column)
+        .zip(origin_y.checked_add(row).and_then(i32::checked_neg))
+        .map(|(x, y)| SimCell::new(x, y))
+        .ok_or_else(|| PatternError::InvalidHeader(format!("position {},{} is out of range", origin_x, origin_y)));
+
+    let mut cells = Vec::new();
+    let (mut column, mut row) = (0i32, 0i32);
+    let mut count: Option<i32> = None;
+    'body: for line in lines {
+        for c in line.chars() {
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap() as i32;
+                    let next = count.unwrap_or(0).checked_mul(10).and_then(|n| n.checked_add(digit));
+                    count = Some(next.ok_or(PatternError::InvalidCount)?);
+                    continue;
+                }
+                'b' | '.' => column = advance(column, count)?,
+                'o' => {
+                    let end = advance(column, count)?;
+                    for x in column..end {
+                        cells.push(cell(x, row)?);
+                    }
+                    column = end;
+                }
+                '$' => {
+                    row = advance(row, count)?;
+                    column = 0;
+                }
+                '!' => break 'body,
+                c if c.is_whitespace() => {}
+                c => return Err(PatternError::InvalidCharacter(c)),
+            }
+            count = None;
+        }
+    }
+
+    environment.set_living(&cells);
+    Ok(environment)
+}
+
+/// Reads the position from an XRLE, Life 1.05 or XLife comment
+fn parse_position(comment: &str) -> Option<(i32, i32)> {
+    let numbers = if let Some(rest) = comment.strip_prefix("CXRLE") {
+        let position = rest.split_whitespace().find_map(|f| f.strip_prefix("Pos="))?;
+        position.split_once(',')?
+    } else {
+        let rest = comment.strip_prefix('P').or_else(|| comment.strip_prefix('R'))?;
+        let mut parts = rest.split_whitespace();
+        (parts.next()?, parts.next()?)
+    };
+
+    Some((numbers.0.trim().parse().ok()?, numbers.1.trim().parse().ok()?))
+}
+
+/// Writes the environment in Run Length Encoded format, including its position as an XRLE
+/// comment.
+pub fn to_rle(environment: &Environment) -> String {
+    let rule = environment.rule();
+    let Some((min, max)) = environment.bounding_box() else {
+        return format!("x = 0, y = 0, rule = {}\n!\n", rule);
+    };
+
+    let mut output = format!("#CXRLE Pos={},{}\n", min.x, -max.y);
+    output.push_str(&format!("x = {}, y = {}, rule = {}\n",
+                             max.x - min.x + 1, max.y - min.y + 1, rule));
+
+    // Create the list of tokens
+    let mut tokens = Vec::new();
+
+    let mut last_row = 0;
+    for (row, row_cells) in rows_from_top(environment, max.y) {
+        push_run(&mut tokens, row - last_row, '$');
+        last_row = row;
+
+        let mut column = 0;
+        let mut run = 0;
+        for x in row_cells {
+            let x = x - min.x;
+            if x != column + run {
+                push_run(&mut tokens, run, 'o');
+                push_run(&mut tokens, x - column - run, 'b');
+                column = x;
+                run = 0;
+            }
+            run += 1;
+        }
+        push_run(&mut tokens, run, 'o');
+    }
+    tokens.push(String::from("!"));
+
+    // Wrap the lines
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > RLE_LINE_LENGTH {
+            output.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        output.push_str(&token);
+    }
+    output.push('\n');
+
+    output
+}
+
+/// Adds a run of the given tag to the RLE tokens. Empty runs are skipped.
+fn push_run(tokens: &mut Vec<String>, count: i32, tag: char) {
+    if count == 1 {
+        tokens.push(tag.to_string());
+    } else if count > 1 {
+        tokens.push(format!("{}{}", count, tag));
+    }
+}
+
+/// Parses a pattern in plaintext format, where `O` marks a living cell and `.` a dead one.
+/// Lines starting with `!` are comments.
+pub fn parse_cells(input: &str) -> Result<Environment, PatternError> {
+    let mut cells = Vec::new();
+    let rows = input.lines().filter(|l| !l.starts_with('!'));
+    for (row, line) in rows.enumerate() {
+        for (column, c) in line.trim_end().chars().enumerate() {
+            match c {
+                'O' | '*' => cells.push(SimCell::new(column as i32, -(row as i32))),
+                '.' => {}
+                c => return Err(PatternError::InvalidCharacter(c)),
+            }
+        }
+    }
+
+    let mut environment = Environment::default();
+    environment.set_living(&cells);
+    Ok(environment)
+}
+
+/// Writes the environment in plaintext format. The position of the pattern is not kept.
+pub fn to_cells(environment: &Environment) -> String {
+    let Some((min, max)) = environment.bounding_box() else {
+        return String::new();
+    };
+
+    let mut output = String::new();
+    let mut last_row = 0;
+    for (row, row_cells) in rows_from_top(environment, max.y) {
+        for _ in last_row..row {
+            output.push('\n');
+        }
+        last_row = row;
+
+        let mut column = min.x;
+        for x in row_cells {
+            output.extend(std::iter::repeat('.').take((x - column) as usize));
+            output.push('O');
+            column = x + 1;
+        }
+    }
+    output.push('\n');
+
+    output
+}
+
+/// Parses a grid where `x` marks a living cell, and a space or `.` a dead one. Blank lines
+/// before and after the grid are skipped.
+///
+/// The first character of the grid is placed at `(0,0)`, unless it is preceded by a header line
+/// like `@(-3,2) B36/S23`, with the position of that character and optionally a rule.
+pub fn parse_ascii(input: &str) -> Result<Environment, PatternError> {
+    let mut environment = Environment::default();
+    let (mut origin_x, mut origin_y) = (0, 0);
+    let mut lines = input.lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .skip_while(|l| l.trim().is_empty())
+        .peekable();
+
+    if let Some(header) = lines.next_if(|l| l.trim_start().starts_with('@')) {
+        let header = header.trim().trim_start_matches('@').trim_start();
+        let rule = match header.strip_prefix('(').and_then(|h| h.split_once(')')) {
+            Some((position, rule)) => {
+                (origin_x, origin_y) = position.split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                    .ok_or_else(|| PatternError::InvalidHeader(header.to_string()))?;
+                rule.trim()
+            }
+            None => header,
+        };
+        if !rule.is_empty() {
+            environment.set_rule(rule.parse::<Rule>()?);
+        }
+    }
+
+    let mut cells = Vec::new();
+    for (row, line) in lines.enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            match c {
+                'x' => cells.push(SimCell::new(origin_x + column as i32, origin_y - row as i32)),
+                ' ' | '.' => {}
+                c => return Err(PatternError::InvalidCharacter(c)),
+            }
+        }
+    }
+
+    environment.set_living(&cells);
+    Ok(environment)
+}
+
+/// Writes the environment as a grid of `x` characters, as parsed by [`parse_ascii`]. The header
+/// is only written when the grid doesn't start at `(0,0)` or the rule isn't Conway's.
+pub fn to_ascii(environment: &Environment) -> String {
+    let rule = environment.rule();
+    let mut output = String::new();
+    let Some((min, max)) = environment.bounding_box() else {
+        if !rule.is_conway() {
+            output.push_str(&format!("@ {}\n", rule));
+        }
+        return output;
+    };
+
+    if (min.x, max.y) != (0, 0) || !rule.is_conway() {
+        output.push_str(&format!("@{}", SimCell::new(min.x, max.y)));
+        if !rule.is_conway() {
+            output.push_str(&format!(" {}", rule));
+        }
+        output.push('\n');
+    }
+
+    let mut last_row = 0;
+    for (row, row_cells) in rows_from_top(environment, max.y) {
+        for _ in last_row..row {
+            output.push('\n');
+        }
+        last_row = row;
+
+        let mut column = min.x;
+        for x in row_cells {
+            output.extend(std::iter::repeat(' ').take((x - column) as usize));
+            output.push('x');
+            column = x + 1;
+        }
+    }
+    output.push('\n');
+
+    output
+}
+
+/// Groups the living cells in rows, ordered from the top. Each row contains the `x` value of
+/// its cells in increasing order, and is numbered downwards starting at the given top `y`.
+fn rows_from_top(environment: &Environment, top: i32) -> Vec<(i32, Vec<i32>)> {
+    let mut cells: Vec<&SimCell> = environment.living_cells().collect();
+    cells.sort_by_key(|c| (-c.y, c.x));
+
+    let mut rows: Vec<(i32, Vec<i32>)> = Vec::new();
+    for cell in cells {
+        let row = top - cell.y;
+        match rows.last_mut() {
+            Some((last, xs)) if *last == row => xs.push(cell.x),
+            _ => rows.push((row, vec![cell.x])),
+        }
+    }
+
+    rows
+}
@@ -0,0 +1,191 @@
+//! Life-like rules in the usual `B/S` notation.
+
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RuleError {
+    #[error("Rule must have the form B<digits>/S<digits>")]
+    InvalidFormat,
+
+    #[error("Invalid neighbour count '{0}' in rule")]
+    InvalidCount(char),
+}
+
+/// Represents a Life-like rule: which neighbour counts cause a dead cell to be born, and
+/// which ones allow a living cell to survive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Default for Rule {
+    /// Conway's Game of Life (B3/S23)
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl Rule {
+    /// Creates a rule from the list of neighbour counts for birth and survival.
+    ///
+    /// # Panics
+    /// Will panic if any count is greater than 8.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        let mut rule = Rule { birth: [false; 9], survival: [false; 9] };
+        for &count in birth {
+            rule.birth[count as usize] = true;
+        }
+        for &count in survival {
+            rule.survival[count as usize] = true;
+        }
+
+        rule
+    }
+
+    /// Conway's Game of Life rule (B3/S23)
+    pub fn conway() -> Self {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    /// Returns true if this is the standard Conway rule
+    pub fn is_conway(&self) -> bool {
+        *self == Rule::conway()
+    }
+
+    /// Returns true if a dead cell with the given amount of neighbours becomes alive
+    #[inline]
+    pub fn is_birth(&self, neighbours: usize) -> bool {
+        self.birth[neighbours]
+    }
+
+    /// Returns true if a living cell with the given amount of neighbours survives
+    #[inline]
+    pub fn is_survival(&self, neighbours: usize) -> bool {
+        self.survival[neighbours]
+    }
+
+    /// Returns the rules that differ from this one by a single birth or survival count. Rules
+    /// with birth on 0 neighbours are left out, since they fill the whole plane.
+    pub fn neighbours(&self) -> Vec<Rule> {
+        let mut rules = Vec::new();
+        for count in 1..9 {
+            let mut rule = *self;
+            rule.birth[count] = !rule.birth[count];
+            rules.push(rule);
+        }
+        for count in 0..9 {
+            let mut rule = *self;
+            rule.survival[count] = !rule.survival[count];
+            rules.push(rule);
+        }
+
+        rules
+    }
+
+    /// Returns the rule that evolves the cells that differ from the given background, along with
+    /// the background of the next generation. This is how B0 rules are emulated: the background
+    /// comes alive whenever a dead cell without neighbours is born, and stays alive while a living
+    /// cell with 8 neighbours survives. The returned rule never has birth on 0 neighbours.
+    pub(crate) fn for_background(&self, background: bool) -> (Rule, bool) {
+        let next_background = if background { self.survival[8] } else { self.birth[0] };
+
+        let mut rule = Rule { birth: [false; 9], survival: [false; 9] };
+        for count in 0..9 {
+            // Living neighbours of a cell that has `count` neighbours different from the background
+            let living = if background { 8 - count } else { count };
+            let (stored, other) = if background {
+                (self.birth[living], self.survival[living])
+            } else {
+                (self.survival[living], self.birth[living])
+            };
+            rule.survival[count] = stored != next_background;
+            rule.birth[count] = other != next_background;
+        }
+
+        (rule, next_background)
+    }
+
+    /// Parses the digits of one half of a rule
+    fn parse_counts(digits: &str) -> Result<[bool; 9], RuleError> {
+        let mut counts = [false; 9];
+        for c in digits.chars() {
+            match c.to_digit(10) {
+                Some(d) if d <= 8 => counts[d as usize] = true,
+                _ => return Err(RuleError::InvalidCount(c)),
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    /// Parses a rule in `B3/S23` notation. The legacy `23/3` (survival/birth) notation is
+    /// also accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s.trim().split_once('/').ok_or(RuleError::InvalidFormat)?;
+
+        let strip = |half: &str, prefix: char| {
+            half.strip_prefix(prefix)
+                .or_else(|| half.strip_prefix(prefix.to_ascii_lowercase()))
+                .map(str::to_string)
+        };
+
+        let (birth, survival) = match (strip(first, 'B'), strip(second, 'S')) {
+            (Some(birth), Some(survival)) => (birth, survival),
+            (None, None) => {
+                if let (Some(survival), Some(birth)) = (strip(first, 'S'), strip(second, 'B')) {
+                    (birth, survival)
+                } else if first.chars().chain(second.chars()).all(|c| c.is_ascii_digit()) {
+                    (second.to_string(), first.to_string())
+                } else {
+                    return Err(RuleError::InvalidFormat);
+                }
+            }
+            _ => return Err(RuleError::InvalidFormat),
+        };
+
+        Ok(Rule {
+            birth: Rule::parse_counts(&birth)?,
+            survival: Rule::parse_counts(&survival)?,
+        })
+    }
+}
+
+impl Display for Rule {
+    /// Displays the rule in `B3/S23` notation.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_char('B')?;
+        for (count, _) in self.birth.iter().enumerate().filter(|(_, &b)| b) {
+            write!(f, "{}", count)?;
+        }
+        f.write_str("/S")?;
+        for (count, _) in self.survival.iter().enumerate().filter(|(_, &s)| s) {
+            write!(f, "{}", count)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = RuleError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Rule> for String {
+    fn from(value: Rule) -> Self {
+        value.to_string()
+    }
+}
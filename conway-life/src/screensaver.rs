@@ -0,0 +1,135 @@
+//! A screensaver seeds a random soup, simulates it until it stagnates and then starts again
+//! with a new soup.
+//!
+//! A simulation is considered stagnated once it repeats a previous state (still lifes,
+//! oscillators or an empty environment), or after a maximum amount of generations, since
+//! escaping spaceships never repeat a state.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+use rand::prelude::*;
+use rand_xoshiro::Xoshiro256StarStar as RandomGenerator;
+
+use crate::{Environment, SimCell};
+
+/// Longest period detected by default
+const DEFAULT_MAX_PERIOD: usize = 30;
+
+/// Generations simulated before reseeding a soup that never repeats
+const DEFAULT_MAX_GENERATIONS: usize = 3000;
+
+/// Probability of a cell being alive in a soup
+const DEFAULT_DENSITY: f64 = 0.35;
+
+/// Fills an area of the given size, centered at the origin, with random cells. Each cell is
+/// alive with the given probability.
+pub fn random_soup(rng: &mut impl Rng, width: usize, height: usize, density: f64) -> Environment {
+    let left = -((width / 2) as i32);
+    let top = (height / 2) as i32;
+
+    let mut cells = Vec::new();
+    for row in 0..height as i32 {
+        for column in 0..width as i32 {
+            if rng.gen_bool(density) {
+                cells.push(SimCell::new(left + column, top - row));
+            }
+        }
+    }
+
+    let mut environment = Environment::default();
+    environment.set_living(&cells);
+    environment
+}
+
+/// Detects when an environment repeats one of its recent states
+#[derive(Debug)]
+pub struct CycleDetector {
+    /// Hashes of the most recent states, oldest first
+    history: VecDeque<u64>,
+    max_period: usize,
+}
+
+impl CycleDetector {
+    /// Creates a detector of cycles up to the given period
+    pub fn new(max_period: usize) -> Self {
+        CycleDetector { history: VecDeque::with_capacity(max_period), max_period }
+    }
+
+    /// Records the state of the environment. Returns the period of the cycle if the state was
+    /// seen within the last `max_period` observations.
+    pub fn observe(&mut self, environment: &Environment) -> Option<usize> {
+        let mut hasher = DefaultHasher::new();
+        for cell in environment.living_cells() {
+            cell.hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+
+        let period = self.history.iter().rev().position(|&h| h == hash).map(|i| i + 1);
+        if self.history.len() == self.max_period {
+            self.history.pop_front();
+        }
+        self.history.push_back(hash);
+
+        period
+    }
+
+    /// Forgets every recorded state
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// Keeps track of the soups shown by the screensaver
+#[derive(Debug)]
+pub struct Screensaver {
+    generator: RandomGenerator,
+    detector: CycleDetector,
+    /// Generations simulated since the last soup
+    generation: usize,
+    pub width: usize,
+    pub height: usize,
+    pub density: f64,
+    pub max_generations: usize,
+}
+
+impl Screensaver {
+    /// Creates a screensaver that seeds soups of the given size.
+    ///
+    /// * `seed`: Value to use when randomizing the soups. A value of `None` calculates a random
+    /// seed.
+    pub fn new(width: usize, height: usize, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            let mut generator = rand::thread_rng();
+            generator.next_u64()
+        });
+
+        Screensaver {
+            generator: RandomGenerator::seed_from_u64(seed),
+            detector: CycleDetector::new(DEFAULT_MAX_PERIOD),
+            generation: 0,
+            width,
+            height,
+            density: DEFAULT_DENSITY,
+            max_generations: DEFAULT_MAX_GENERATIONS,
+        }
+    }
+
+    /// Replaces the cells of the environment with a new soup, keeping its rule
+    pub fn seed(&mut self, environment: &mut Environment) {
+        let mut soup = random_soup(&mut self.generator, self.width, self.height, self.density);
+        soup.set_rule(environment.rule());
+        *environment = soup;
+
+        self.detector.clear();
+        self.generation = 0;
+    }
+
+    /// Records a simulated generation of the environment. Returns true if the simulation
+    /// stagnated and should be reseeded.
+    pub fn is_stagnant(&mut self, environment: &Environment) -> bool {
+        self.generation += 1;
+        self.detector.observe(environment).is_some() || self.generation >= self.max_generations
+    }
+}
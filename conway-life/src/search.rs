@@ -0,0 +1,254 @@
+//! Searches random soups, counting the objects left once each of them stabilizes.
+//!
+//! Every soup is seeded from the seed of the search plus its index, so any soup of a report can
+//! be recreated on its own. Soups are split between several threads.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256StarStar as RandomGenerator;
+
+use crate::{Environment, Rule};
+use crate::census::{Census, MAX_PERIOD, Object, ObjectKind};
+use crate::pattern;
+use crate::screensaver::random_soup;
+
+/// Generations kept to decide if a soup stabilized
+const STABLE_WINDOW: usize = 4 * MAX_PERIOD;
+
+/// Settings of a soup search
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Amount of soups to search
+    pub soups: usize,
+    /// Seed of the first soup
+    pub seed: u64,
+    /// Width and height of the soups
+    pub size: usize,
+    /// Probability of a cell being alive in a soup
+    pub density: f64,
+    pub rule: Rule,
+    /// Soups that don't stabilize after this amount of generations are reported and skipped
+    pub max_generations: usize,
+    /// Unrecognized objects with at least this amount of cells are included in the report, along
+    /// with every unrecognized spaceship and oscillator with a period over 2 (groups of
+    /// blinkers are too common to report)
+    pub min_report_population: usize,
+    pub threads: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            soups: 1000,
+            seed: 0,
+            size: 16,
+            density: 0.5,
+            rule: Rule::default(),
+            max_generations: 20_000,
+            min_report_population: 12,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Returns the seed of a soup
+    pub fn soup_seed(&self, soup: usize) -> u64 {
+        self.seed.wrapping_add(soup as u64)
+    }
+
+    /// Creates one of the soups of the search
+    pub fn soup(&self, soup: usize) -> Environment {
+        let mut generator = RandomGenerator::seed_from_u64(self.soup_seed(soup));
+        let mut environment = random_soup(&mut generator, self.size, self.size, self.density);
+        environment.set_rule(self.rule);
+        environment
+    }
+}
+
+/// An unrecognized object worth looking at
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// Index of the first soup where it was found
+    pub soup: usize,
+    pub object: Object,
+}
+
+/// Results of a soup search
+#[derive(Debug)]
+pub struct SearchReport {
+    pub options: SearchOptions,
+    /// Amount of each kind of object, by name
+    pub counts: HashMap<String, usize>,
+    /// Unrecognized objects, once each, in the order they were found
+    pub findings: Vec<Finding>,
+    /// Indices of the soups that didn't stabilize
+    pub unstabilized: Vec<usize>,
+    /// Generations simulated until the soups stabilized
+    pub generations: usize,
+    pub elapsed: Duration,
+}
+
+impl SearchReport {
+    /// Returns the objects found sorted by their amount, most common first
+    pub fn sorted_counts(&self) -> Vec<(&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = self.counts.iter().map(|(name, count)| (name.as_str(), *count)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+    }
+}
+
+impl Display for SearchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let options = &self.options;
+        writeln!(f, "Soups: {} of {}x{} at {:.0}% ({}), seeds {} to {}", options.soups, options.size, options.size,
+                 options.density * 100.0, options.rule, options.seed,
+                 options.soup_seed(options.soups.saturating_sub(1)))?;
+
+        let stabilized = options.soups - self.unstabilized.len();
+        writeln!(f, "Generations: {:.0} on average to stabilize", self.generations as f64 / stabilized.max(1) as f64)?;
+        if !self.unstabilized.is_empty() {
+            let soups: Vec<String> = self.unstabilized.iter().map(usize::to_string).collect();
+            writeln!(f, "Didn't stabilize: soups {}", soups.join(", "))?;
+        }
+        let soups_per_second = options.soups as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON);
+        writeln!(f, "Time: {:.2}s ({:.0} soups/s)", self.elapsed.as_secs_f64(), soups_per_second)?;
+
+        writeln!(f, "\nObjects:")?;
+        for (name, count) in self.sorted_counts() {
+            writeln!(f, "  {:<24}{:>10}", name, count)?;
+        }
+
+        if !self.findings.is_empty() {
+            writeln!(f, "\nUnrecognized objects:")?;
+            for finding in self.findings.iter() {
+                writeln!(f, "\n{} in soup {} (seed {})", finding.object, finding.soup,
+                         options.soup_seed(finding.soup))?;
+                write!(f, "{}", pattern::to_rle(&finding.object.pattern))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Detects when the population of a simulation settles into a cycle of at most [MAX_PERIOD]
+/// generations, which happens once the remaining objects stop interacting (escaping
+/// spaceships keep the population constant)
+#[derive(Debug, Default)]
+pub struct PopulationCycle {
+    populations: VecDeque<usize>,
+    generation: usize,
+}
+
+impl PopulationCycle {
+    /// Records the population of the next generation. Returns true once it has been cycling
+    /// for a while.
+    pub fn observe(&mut self, population: usize) -> bool {
+        if self.populations.len() == STABLE_WINDOW + MAX_PERIOD {
+            self.populations.pop_front();
+        }
+        self.populations.push_back(population);
+        self.generation += 1;
+
+        // Checking once per period is enough, since a stable soup stays stable
+        if self.generation % MAX_PERIOD != 1 || self.populations.len() < STABLE_WINDOW + MAX_PERIOD {
+            return false;
+        }
+        let recent = self.populations.len() - STABLE_WINDOW;
+        (1..=MAX_PERIOD).any(|period| (recent..self.populations.len())
+            .all(|i| self.populations[i] == self.populations[i - period]))
+    }
+}
+
+/// Simulates a soup until its population repeats with a period of at most [MAX_PERIOD] for a
+/// while. Returns the amount of generations simulated, or `None` if it didn't stabilize.
+pub fn stabilize(environment: &mut Environment, max_generations: usize) -> Option<usize> {
+    let mut cycle = PopulationCycle::default();
+    for generation in 0..=max_generations {
+        if cycle.observe(environment.get_living_count()) {
+            return Some(generation);
+        }
+
+        if generation < max_generations {
+            environment.simulate();
+        }
+    }
+
+    None
+}
+
+/// Results of a single soup
+struct SoupResult {
+    soup: usize,
+    generations: Option<usize>,
+    objects: Vec<Object>,
+}
+
+/// Searches every soup of the options
+pub fn run(options: &SearchOptions) -> SearchReport {
+    let start = Instant::now();
+    let census = Census::new(options.rule);
+    let threads = options.threads.clamp(1, options.soups.max(1));
+
+    // Each thread takes every n-th soup, and the results are merged in order
+    let mut results: Vec<SoupResult> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|first| {
+                let census = &census;
+                scope.spawn(move || {
+                    (first..options.soups).step_by(threads)
+                        .map(|soup| {
+                            let mut environment = options.soup(soup);
+                            let generations = stabilize(&mut environment, options.max_generations);
+                            let objects = match generations {
+                                Some(_) => census.take(&environment),
+                                None => Vec::new(),
+                            };
+                            SoupResult { soup, generations, objects }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().expect("search threads don't panic")).collect()
+    });
+    results.sort_by_key(|result| result.soup);
+
+    let mut report = SearchReport {
+        options: options.clone(),
+        counts: HashMap::new(),
+        findings: Vec::new(),
+        unstabilized: Vec::new(),
+        generations: 0,
+        elapsed: Duration::ZERO,
+    };
+    for result in results {
+        let Some(generations) = result.generations else {
+            report.unstabilized.push(result.soup);
+            continue;
+        };
+        report.generations += generations;
+
+        for object in result.objects {
+            *report.counts.entry(object.name.clone()).or_default() += 1;
+
+            let is_interesting = !object.known && (object.population() >= options.min_report_population
+                || matches!(object.kind, ObjectKind::Spaceship { .. })
+                || matches!(object.kind, ObjectKind::Oscillator { period } if period > 2));
+            let is_new = !report.findings.iter().any(|f| f.object.pattern.living_cells().eq(object.pattern.living_cells()));
+            if is_interesting && is_new {
+                report.findings.push(Finding { soup: result.soup, object });
+            }
+        }
+    }
+    report.elapsed = start.elapsed();
+
+    report
+}
@@ -0,0 +1,389 @@
+//! Runs a simulation without a user interface, streaming every generation to the clients
+//! connected through WebSocket.
+//!
+//! When connecting, a client receives the whole state of the environment. After that it only
+//! receives the cells that are born or die on each generation, as JSON text messages:
+//!
+//! ```json
+//! {"type":"state","generation":0,"rule":"B3/S23","cells":[[0,0],[1,0]]}
+//! {"type":"generation","generation":1,"births":[[0,1]],"deaths":[[1,0]]}
+//! ```
+//!
+//! or as binary messages: a byte with the type (0 for the state, 1 for a generation, 2 for an
+//! edit), the generation as an `u64` and then each list of cells as an `u32` length followed by
+//! the `x` and `y` of each cell as `i32` values, all in little endian. The state has a single
+//! list of cells and doesn't include the rule.
+//!
+//! Clients edit the shared environment by sending commands as JSON text messages. A toggle
+//! switches a single cell, and a placement adds the living cells of a pattern (in any of the
+//! supported formats) with the top left corner of its bounding box at the given cell:
+//!
+//! ```json
+//! {"type":"toggle","x":3,"y":-2}
+//! {"type":"place","x":10,"y":10,"pattern":"x = 3, y = 3\nbo$2bo$3o!"}
+//! ```
+//!
+//! Commands are applied between generations in the order they arrive, and every client gets
+//! the resulting changes as an `edit` message (with the same fields as a `generation`) before
+//! the next generation. A cell can only be edited once per generation, so later commands
+//! touching the same cells are rejected instead of undoing the first one. Rejected commands
+//! are answered with an error, always as JSON: `{"type":"error","message":"..."}`.
+
+use std::collections::HashSet;
+use std::io;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tungstenite::{Message, WebSocket};
+
+use crate::{CellEvent, Environment, SimCell};
+use crate::pattern::{self, PatternError};
+
+/// Largest distance from the origin of the cells that clients can edit
+pub const MAX_EDIT_DISTANCE: i32 = 1_000_000;
+/// Largest width and height of the patterns placed by clients
+pub const MAX_PATTERN_SIZE: i32 = 1_000;
+/// Largest length of the patterns placed by clients, in bytes
+pub const MAX_PATTERN_LENGTH: usize = 64 * 1024;
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("Unable to listen for connections")]
+    Io(#[from] io::Error),
+}
+
+impl From<ServerError> for errors::Error {
+    fn from(error: ServerError) -> Self {
+        errors::Error::io("Unable to serve the simulation").with_source(error)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Invalid command: {0}")]
+    Invalid(String),
+
+    #[error("Cells can only be edited up to {MAX_EDIT_DISTANCE} cells away from the origin")]
+    OutOfBounds,
+
+    #[error("Invalid pattern: {0}")]
+    Pattern(#[from] PatternError),
+
+    #[error("Patterns can be at most {MAX_PATTERN_SIZE} cells wide and tall, and {MAX_PATTERN_LENGTH} bytes long")]
+    PatternTooLarge,
+
+    #[error("The cells were already edited in this generation")]
+    Conflict,
+}
+
+/// Encoding of the messages sent to the clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    Json,
+    Binary,
+}
+
+/// Settings of a server run
+#[derive(Debug)]
+pub struct ServeOptions {
+    pub environment: Environment,
+    /// Time between generations
+    pub interval: Duration,
+    pub format: StreamFormat,
+}
+
+/// A message sent to the clients
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Update {
+    /// The whole environment, sent when connecting
+    State { generation: usize, rule: String, cells: Vec<(i32, i32)> },
+    /// Changes of a single generation
+    Generation { generation: usize, births: Vec<(i32, i32)>, deaths: Vec<(i32, i32)> },
+    /// Changes made by the clients before simulating the next generation
+    Edit { generation: usize, births: Vec<(i32, i32)>, deaths: Vec<(i32, i32)> },
+    /// Answer to a rejected command
+    Error { message: String },
+}
+
+impl Update {
+    /// Creates the message with the whole environment
+    pub fn state(environment: &Environment, generation: usize) -> Self {
+        Update::State {
+            generation,
+            rule: environment.rule().to_string(),
+            cells: environment.living_cells().map(|c| (c.x, c.y)).collect(),
+        }
+    }
+
+    /// Creates the message with the births and deaths of a generation
+    pub fn generation(events: &[CellEvent], generation: usize) -> Self {
+        let (births, deaths) = split_events(events);
+        Update::Generation { generation, births, deaths }
+    }
+
+    /// Creates the message with the changes made by the clients before simulating a generation
+    pub fn edit(events: &[CellEvent], generation: usize) -> Self {
+        let (births, deaths) = split_events(events);
+        Update::Edit { generation, births, deaths }
+    }
+
+    /// Encodes the message in the given format. Errors are always encoded as JSON.
+    pub fn encode(&self, format: StreamFormat) -> Message {
+        match (format, self) {
+            (StreamFormat::Binary, Update::State { .. } | Update::Generation { .. } | Update::Edit { .. }) =>
+                Message::Binary(self.to_bytes()),
+            _ => Message::Text(serde_json::to_string(self).expect("updates are always serializable")),
+        }
+    }
+
+    /// Encodes the message in binary format, as described in the module documentation. Errors
+    /// don't have a binary format and are left empty.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (kind, generation, lists) = match self {
+            Update::State { generation, cells, .. } => (0u8, generation, vec![cells]),
+            Update::Generation { generation, births, deaths } => (1u8, generation, vec![births, deaths]),
+            Update::Edit { generation, births, deaths } => (2u8, generation, vec![births, deaths]),
+            Update::Error { .. } => return Vec::new(),
+        };
+
+        let mut bytes = vec![kind];
+        bytes.extend((*generation as u64).to_le_bytes());
+        for cells in lists {
+            bytes.extend((cells.len() as u32).to_le_bytes());
+            for (x, y) in cells {
+                bytes.extend(x.to_le_bytes());
+                bytes.extend(y.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+}
+
+/// Splits events into the births and deaths
+fn split_events(events: &[CellEvent]) -> (Vec<(i32, i32)>, Vec<(i32, i32)>) {
+    let (mut births, mut deaths) = (Vec::new(), Vec::new());
+    for event in events {
+        match event {
+            CellEvent::Birth(SimCell { x, y }) => births.push((*x, *y)),
+            CellEvent::Death(SimCell { x, y }) => deaths.push((*x, *y)),
+        }
+    }
+
+    (births, deaths)
+}
+
+/// A command sent by a client to edit the environment
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    /// Switches a cell between living and dead
+    Toggle { x: i32, y: i32 },
+    /// Adds the living cells of a pattern, with the top left corner of its bounding box at the
+    /// given cell
+    Place { x: i32, y: i32, pattern: String },
+}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(|err| CommandError::Invalid(err.to_string()))
+    }
+}
+
+/// Edits made by the clients between two generations
+#[derive(Debug, Default)]
+pub struct Edits {
+    /// Cells already edited, which can't be edited again until the next generation
+    touched: HashSet<SimCell>,
+    events: Vec<CellEvent>,
+}
+
+impl Edits {
+    /// Validates a command and applies it to the environment
+    pub fn apply(&mut self, environment: &mut Environment, command: &Command) -> Result<(), CommandError> {
+        match command {
+            Command::Toggle { x, y } => {
+                let cell = SimCell::new(*x, *y);
+                check_bounds(cell)?;
+                if self.touched.contains(&cell) {
+                    return Err(CommandError::Conflict);
+                }
+
+                self.touched.insert(cell);
+                self.events.push(if environment.toggle_cell(&cell) {
+                    CellEvent::Birth(cell)
+                } else {
+                    CellEvent::Death(cell)
+                });
+            }
+            Command::Place { x, y, pattern } => {
+                let top_left = SimCell::new(*x, *y);
+                check_bounds(top_left)?;
+                if pattern.len() > MAX_PATTERN_LENGTH {
+                    return Err(CommandError::PatternTooLarge);
+                }
+
+                // The size is checked while parsing, so huge run counts fail before allocating
+                let pattern = pattern::parse_bounded(pattern, pattern::detect_format(pattern), MAX_PATTERN_SIZE)
+                    .map_err(|err| match err {
+                        PatternError::TooLarge(_) => CommandError::PatternTooLarge,
+                        err => CommandError::Pattern(err),
+                    })?;
+                let Some((min, max)) = pattern.bounding_box() else {
+                    return Ok(());
+                };
+
+                // The whole pattern is rejected if any of its cells is out of bounds or taken
+                let cells: Vec<SimCell> = pattern.living_cells()
+                    .map(|c| SimCell::new(top_left.x + (c.x - min.x), top_left.y + (c.y - max.y)))
+                    .collect();
+                for cell in cells.iter() {
+                    check_bounds(*cell)?;
+                    if self.touched.contains(cell) {
+                        return Err(CommandError::Conflict);
+                    }
+                }
+
+                self.touched.extend(cells.iter().copied());
+                self.events.extend(cells.iter()
+                    .filter(|c| !environment.get_cell(c))
+                    .map(|c| CellEvent::Birth(*c)));
+                environment.set_living(&cells);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if no cell was changed
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the cells changed so far, as births and deaths
+    pub fn events(&self) -> &[CellEvent] {
+        &self.events
+    }
+
+    /// Forgets the edits, allowing every cell to be edited again
+    pub fn clear(&mut self) {
+        self.touched.clear();
+        self.events.clear();
+    }
+}
+
+/// Checks that a cell is close enough to the origin to be edited
+fn check_bounds(cell: SimCell) -> Result<(), CommandError> {
+    if cell.x.unsigned_abs() > MAX_EDIT_DISTANCE as u32 || cell.y.unsigned_abs() > MAX_EDIT_DISTANCE as u32 {
+        Err(CommandError::OutOfBounds)
+    } else {
+        Ok(())
+    }
+}
+
+/// State shared between the simulation and the thread accepting connections
+struct Shared {
+    environment: Environment,
+    generation: usize,
+    clients: Vec<WebSocket<TcpStream>>,
+}
+
+/// Listens for clients at the given address and simulates the environment forever
+pub fn serve(address: impl ToSocketAddrs, options: ServeOptions) -> Result<(), ServerError> {
+    let listener = TcpListener::bind(address)?;
+    let format = options.format;
+    let shared = Arc::new(Mutex::new(Shared {
+        environment: options.environment,
+        generation: 0,
+        clients: Vec::new(),
+    }));
+
+    // New clients get the whole state while holding the lock, so they don't miss a generation
+    let accepting = Arc::clone(&shared);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let Ok(mut socket) = tungstenite::accept(stream) else {
+                continue;
+            };
+
+            let mut shared = accepting.lock().unwrap();
+            let state = Update::state(&shared.environment, shared.generation);
+            if socket.send(state.encode(format)).is_ok() && socket.get_ref().set_nonblocking(true).is_ok() {
+                shared.clients.push(socket);
+            }
+        }
+    });
+
+    let mut edits = Edits::default();
+    let mut events = Vec::new();
+    loop {
+        let start = Instant::now();
+        {
+            let mut shared = shared.lock().unwrap();
+            let shared = &mut *shared;
+
+            // Commands of every client, rejected ones are answered right away
+            edits.clear();
+            let environment = &mut shared.environment;
+            shared.clients.retain_mut(|client| {
+                receive(client).iter().all(|text| {
+                    let result = text.parse::<Command>().and_then(|command| edits.apply(environment, &command));
+                    match result {
+                        Ok(()) => true,
+                        Err(err) => {
+                            let answer = Update::Error { message: err.to_string() };
+                            is_connected(client.send(answer.encode(format)))
+                        }
+                    }
+                })
+            });
+            if !edits.is_empty() {
+                broadcast(&mut shared.clients, Update::edit(edits.events(), shared.generation).encode(format));
+            }
+
+            events.clear();
+            shared.environment.simulate_observed(&mut events);
+            shared.generation += 1;
+            broadcast(&mut shared.clients, Update::generation(&events, shared.generation).encode(format));
+        }
+
+        thread::sleep(options.interval.saturating_sub(start.elapsed()));
+    }
+}
+
+/// Sends a message to every client, dropping the ones that are gone
+fn broadcast(clients: &mut Vec<WebSocket<TcpStream>>, message: Message) {
+    clients.retain_mut(|client| is_connected(client.send(message.clone())));
+}
+
+/// Reads the pending text messages of a client. Reading also answers pings and close requests.
+fn receive(client: &mut WebSocket<TcpStream>) -> Vec<String> {
+    let mut texts = Vec::new();
+    while client.can_read() {
+        match client.read() {
+            Ok(Message::Text(text)) => texts.push(text),
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    texts
+}
+
+/// Returns false if the result of a send means that the client is gone. Messages that can't be
+/// written right away are kept and sent on the next write.
+fn is_connected(result: tungstenite::Result<()>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(tungstenite::Error::Io(err)) => err.kind() == io::ErrorKind::WouldBlock,
+        Err(_) => false,
+    }
+}
@@ -162,6 +162,95 @@ fn simulate_toad() {
     check_environment(&end, &start);
 }
 
+#[test]
+fn observe_events() {
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(2, 1), SimCell::new(2, 0), SimCell::new(2, -1)]);
+
+    // Events are collected in a vector
+    let mut events = Vec::new();
+    environment.simulate_observed(&mut events);
+    events.sort_by_key(|e| match e {
+        CellEvent::Birth(c) => (0, *c),
+        CellEvent::Death(c) => (1, *c),
+    });
+    assert_eq!(events, vec![
+        CellEvent::Birth(SimCell::new(1, 0)),
+        CellEvent::Birth(SimCell::new(3, 0)),
+        CellEvent::Death(SimCell::new(2, -1)),
+        CellEvent::Death(SimCell::new(2, 1)),
+    ]);
+
+    // Observers may only care about a single kind of event
+    struct Births(usize);
+    impl Observer for Births {
+        fn on_birth(&mut self, _cell: SimCell) {
+            self.0 += 1;
+        }
+    }
+
+    let mut births = Births(0);
+    for _ in 0..4 {
+        environment.simulate_observed(&mut births);
+    }
+    assert_eq!(births.0, 8);
+    assert_eq!(environment.get_living_count(), 3);
+}
+
+#[test]
+fn environment_diff() {
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(2, 1), SimCell::new(2, 0), SimCell::new(2, -1)]);
+    assert!(environment.diff(&environment.clone()).is_empty());
+
+    let mut next = environment.clone();
+    next.simulate();
+    let changes = environment.diff(&next);
+    assert_eq!(changes.added, vec![SimCell::new(1, 0), SimCell::new(3, 0)]);
+    assert_eq!(changes.removed, vec![SimCell::new(2, -1), SimCell::new(2, 1)]);
+    assert_eq!(changes.len(), 4);
+
+    // The changes go the other way when swapping the environments
+    let back = next.diff(&environment);
+    assert_eq!(back.added, changes.removed);
+    assert_eq!(back.removed, changes.added);
+}
+
+#[test]
+fn set_regions() {
+    let mut environment = Environment::default();
+    environment.set_region((SimCell::new(-2, -1), SimCell::new(1, 3)), true);
+    assert_eq!(environment.get_living_count(), 20);
+    assert_eq!(environment.bounding_box(), Some((SimCell::new(-2, -1), SimCell::new(1, 3))));
+    let bounds = environment.bounds().unwrap();
+    assert_eq!((bounds.width(), bounds.height()), (4, 5));
+    assert_eq!(SimCell::from(bounds.max), SimCell::new(1, 3));
+
+    // Clearing only affects the cells within the region
+    environment.set_region((SimCell::new(-1, 0), SimCell::new(5, 1)), false);
+    assert_eq!(environment.get_living_count(), 14);
+    assert!(environment.get_cell(&SimCell::new(-2, 0)));
+    assert!(!environment.get_cell(&SimCell::new(1, 1)));
+    assert!(environment.get_cell(&SimCell::new(1, 2)));
+
+    // Empty regions do nothing
+    environment.set_region((SimCell::new(1, 1), SimCell::new(0, 1)), true);
+    assert_eq!(environment.get_living_count(), 14);
+
+    // Grids overwrite both living and dead cells
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(10, 5), SimCell::new(11, 4)]);
+    environment.set_from_grid(SimCell::new(10, 5), &[
+        &[false, true, false][..],
+        &[false, false, true],
+        &[true, true, true],
+    ]);
+    let glider = [
+        SimCell::new(10, 3), SimCell::new(11, 3), SimCell::new(11, 5), SimCell::new(12, 3), SimCell::new(12, 4),
+    ];
+    assert_eq!(environment.living_cells().copied().collect::<Vec<_>>(), glider);
+}
+
 // Viewport
 mod viewport_panics {
     use crate::Viewport;
@@ -214,12 +303,17 @@ fn viewport_basic() {
     assert!(viewport.in_viewport(x, y), "Viewport origin should be in viewport");
     assert!(!viewport.in_viewport(x.wrapping_add_unsigned(width as u32), y), "Right should not be in viewport");
     assert!(!viewport.in_viewport(x, y.wrapping_add_unsigned(height as u32)), "Bottom should not be in viewport");
+    assert_eq!(viewport.bounds(), grid2d::Bounds {
+        min: grid2d::Coord::new(x, viewport.bottom() + 1),
+        max: grid2d::Coord::new(viewport.right() - 1, y),
+    });
+    assert_eq!((viewport.bounds().width(), viewport.bounds().height()), (width, height));
 
     // Check clearing
-    viewport.data.iter().map(|&d| assert!(!d)).count();
+    viewport.data.cells().iter().map(|&d| assert!(!d)).count();
     viewport.set_living(0, 0);
     viewport.clear();
-    viewport.data.iter().map(|&d| assert!(!d)).count();
+    viewport.data.cells().iter().map(|&d| assert!(!d)).count();
 }
 
 #[test]
@@ -243,6 +337,50 @@ fn environment_viewport() {
     assert_eq!(expected_repr, viewport.to_string());
 }
 
+#[test]
+fn glider_golden() {
+    use util::golden::assert_matches_golden;
+
+    let mut glider: Environment = " x\n  x\nxxx\n".parse().unwrap();
+    let mut viewport = Viewport::new(-1, 1, 6, 6);
+    let mut output = String::new();
+    for generation in 0..=8 {
+        if generation % 4 == 0 {
+            glider.fill_viewport(&mut viewport);
+            output.push_str(&format!("Generation {}\n{}\n", generation, viewport));
+        }
+        glider.simulate();
+    }
+
+    assert_matches_golden("glider.txt", output);
+}
+
+#[test]
+fn viewport_transform() {
+    let mut env = Environment::default();
+    env.set_living(&[SimCell::new(3, 8), SimCell::new(5, 7)]);
+    let mut viewport = Viewport::new(2, 9, 4, 3);
+    env.fill_viewport(&mut viewport);
+
+    // Rows grow downwards on screen
+    assert_eq!(viewport.world_to_screen(SimCell::new(3, 8)), Some(ScreenPoint { x: 1, y: 1 }));
+    assert_eq!(viewport.world_to_screen(SimCell::new(5, 7)), Some(ScreenPoint { x: 3, y: 2 }));
+    assert_eq!(viewport.world_to_screen(SimCell::new(6, 7)), None);
+    assert_eq!(viewport.world_to_screen(SimCell::new(2, 10)), None);
+
+    assert_eq!(viewport.screen_to_world(ScreenPoint { x: 0, y: 0 }), Some(SimCell::new(2, 9)));
+    assert_eq!(viewport.screen_to_world(ScreenPoint { x: 3, y: 2 }), Some(SimCell::new(5, 7)));
+    assert_eq!(viewport.screen_to_world(ScreenPoint { x: 4, y: 0 }), None);
+
+    // Points are scaled and converted to the requested type
+    let points: Vec<ScreenPoint<u16>> = viewport.points(10).unwrap();
+    assert_eq!(points, vec![ScreenPoint { x: 10, y: 10 }, ScreenPoint { x: 30, y: 20 }]);
+    assert!(viewport.points::<u8>(100).is_err());
+    for point in viewport.points::<usize>(1).unwrap() {
+        assert!(env.get_cell(&viewport.screen_to_world(point).unwrap()));
+    }
+}
+
 #[test]
 fn environment_serialization() {
     let mut env = Environment::default();
@@ -253,4 +391,1241 @@ fn environment_serialization() {
 
     let new_env: Environment = serde_yaml::from_str(&serialized).unwrap();
     assert_eq!(env.living_cells, new_env.living_cells);
-}
\ No newline at end of file
+}
+
+#[test]
+fn rule_parsing() {
+    let conway: Rule = "B3/S23".parse().unwrap();
+    assert_eq!(conway, Rule::conway());
+    assert!(conway.is_conway());
+    assert_eq!(conway.to_string(), "B3/S23");
+
+    // Alternative notations
+    assert_eq!("b3/s23".parse::<Rule>().unwrap(), conway);
+    assert_eq!("S23/B3".parse::<Rule>().unwrap(), conway);
+    assert_eq!("23/3".parse::<Rule>().unwrap(), conway);
+
+    let high_life: Rule = "B36/S23".parse().unwrap();
+    assert!(high_life.is_birth(6));
+    assert!(!high_life.is_conway());
+    assert_eq!(high_life.to_string(), "B36/S23");
+
+    // Invalid rules
+    assert_eq!("B3S23".parse::<Rule>(), Err(rule::RuleError::InvalidFormat));
+    assert_eq!("B39/S23".parse::<Rule>(), Err(rule::RuleError::InvalidCount('9')));
+}
+
+#[test]
+fn simulate_with_rule() {
+    // Under HighLife (B36/S23) six neighbours cause a birth in the middle of this pattern
+    // -----
+    // -xxx-
+    // -0--- <- (0, 0) is born
+    // -xxx-
+    // -----
+    let start = [
+        SimCell::new(-1, 1), SimCell::new(0, 1), SimCell::new(1, 1),
+        SimCell::new(-1, -1), SimCell::new(0, -1), SimCell::new(1, -1),
+    ];
+
+    let mut conway = Environment::default();
+    conway.set_living(&start);
+    conway.simulate();
+    assert!(!conway.get_cell(&SimCell::new(0, 0)));
+
+    let mut high_life = Environment::with_rule("B36/S23".parse().unwrap());
+    high_life.set_living(&start);
+    high_life.simulate();
+    assert!(high_life.get_cell(&SimCell::new(0, 0)));
+}
+
+#[test]
+fn simulate_b0_rules() {
+    use pattern::{PatternError, PatternFormat};
+
+    // B0 rules are compared with the brute force evolution of every cell around the soup,
+    // including cells far from it that only follow the background
+    for rule in ["B0/S", "B03/S23", "B0123478/S34678", "B0/S8"] {
+        let mut environment = Environment::with_rule(rule.parse().unwrap());
+        screensaver::Screensaver::new(10, 10, Some(3)).seed(&mut environment);
+
+        for _ in 0..6 {
+            let rule = environment.rule();
+            let mut expected = Vec::new();
+            for x in -20..30 {
+                for y in -20..30 {
+                    let cell = SimCell::new(x, y);
+                    let neighbors = (-1..=1)
+                        .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
+                        .filter(|&(dx, dy)| (dx, dy) != (0, 0) && environment.get_cell(&SimCell::new(x + dx, y + dy)))
+                        .count();
+                    let alive = environment.get_cell(&cell);
+                    expected.push((cell, if alive { rule.is_survival(neighbors) } else { rule.is_birth(neighbors) }));
+                }
+            }
+
+            environment.simulate();
+            for (cell, alive) in expected {
+                assert_eq!(environment.get_cell(&cell), alive, "{} at {} under {}", cell, alive, rule);
+            }
+        }
+    }
+
+    // A single cell under B0/S blinks, with the background alive on odd generations
+    let mut blinker = Environment::with_rule("B0/S".parse().unwrap());
+    blinker.set_living(&[SimCell::new(0, 0)]);
+    blinker.simulate();
+    assert!(blinker.background());
+    assert_eq!(blinker.get_living_count(), 9);
+    assert!(!blinker.get_cell(&SimCell::new(1, 1)));
+    assert!(blinker.get_cell(&SimCell::new(2, 2)));
+
+    let mut viewport = Viewport::new(-2, 2, 5, 5);
+    blinker.fill_viewport(&mut viewport);
+    assert_eq!(viewport.to_string(), "xxxxx\nx   x\nx   x\nx   x\nxxxxx");
+
+    // Only YAML keeps the background when writing
+    for format in [PatternFormat::Rle, PatternFormat::Cells, PatternFormat::Ascii] {
+        assert!(matches!(pattern::write(&blinker, format), Err(PatternError::AliveBackground)));
+    }
+
+    blinker.simulate();
+    assert!(!blinker.background());
+    assert_eq!(blinker.living_cells().collect::<Vec<_>>(), vec![&SimCell::new(0, 0)]);
+    assert_eq!(pattern::write(&blinker, PatternFormat::Rle).unwrap(), pattern::to_rle(&blinker));
+}
+
+#[test]
+fn simulate_soups() {
+    // Compare with counting the neighbors of every cell one by one
+    for rule in ["B3/S23", "B36/S23", "B2/S", "B1/S012345678"] {
+        let mut environment = Environment::with_rule(rule.parse().unwrap());
+        screensaver::Screensaver::new(24, 24, Some(5)).seed(&mut environment);
+
+        for _ in 0..10 {
+            let rule = environment.rule();
+            let (min, max) = environment.bounding_box().unwrap();
+            let mut expected = Vec::new();
+            for x in (min.x - 1)..=(max.x + 1) {
+                for y in (min.y - 1)..=(max.y + 1) {
+                    let cell = SimCell::new(x, y);
+                    let neighbors = (-1..=1)
+                        .flat_map(|dx| (-1..=1).map(move |dy| SimCell::new(x + dx, y + dy)))
+                        .filter(|n| *n != cell && environment.get_cell(n))
+                        .count();
+                    let alive = environment.get_cell(&cell);
+                    if (alive && rule.is_survival(neighbors)) || (!alive && rule.is_birth(neighbors)) {
+                        expected.push(cell);
+                    }
+                }
+            }
+
+            environment.simulate();
+            assert_eq!(environment.living_cells().copied().collect::<Vec<_>>(), expected, "{}", rule);
+        }
+    }
+}
+
+#[test]
+fn rle_round_trip() {
+    // Glider with a position
+    let rle = "#N Glider\n#CXRLE Pos=-1,-2\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+    let env = pattern::parse_rle(rle).unwrap();
+    assert_eq!(env.get_living_count(), 5);
+    assert!(env.get_cell(&SimCell::new(0, 2)));
+    assert!(env.get_cell(&SimCell::new(1, 1)));
+    assert!(env.get_cell(&SimCell::new(-1, 0)));
+    assert_eq!(env.bounding_box(), Some((SimCell::new(-1, 0), SimCell::new(1, 2))));
+
+    let written = pattern::to_rle(&env);
+    assert_eq!(written, "#CXRLE Pos=-1,-2\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    let reparsed = pattern::parse_rle(&written).unwrap();
+    assert_eq!(env.living_cells, reparsed.living_cells);
+
+    // Rules and empty rows are kept
+    let rle = "x = 1, y = 4, rule = B36/S23\no3$o!";
+    let env = pattern::parse_rle(rle).unwrap();
+    assert_eq!(env.rule().to_string(), "B36/S23");
+    assert!(env.get_cell(&SimCell::new(0, -3)));
+    let reparsed = pattern::parse_rle(&pattern::to_rle(&env)).unwrap();
+    assert_eq!(env.living_cells, reparsed.living_cells);
+    assert_eq!(env.rule(), reparsed.rule());
+}
+
+#[test]
+fn rle_run_counts() {
+    use pattern::{PatternError, MAX_RLE_SIZE};
+
+    // Counts that don't fit are rejected instead of wrapping around
+    assert!(matches!(pattern::parse_rle("99999999999o!"), Err(PatternError::InvalidCount)));
+    assert!(matches!(pattern::parse_rle("x = 1, y = 1\n4294967297b!"), Err(PatternError::InvalidCount)));
+
+    // Runs can't go past the largest size, before any cell is created
+    let wide = format!("{}o!", MAX_RLE_SIZE + 1);
+    assert!(matches!(pattern::parse_rle(&wide), Err(PatternError::TooLarge(MAX_RLE_SIZE))));
+    assert!(matches!(pattern::parse_rle("2000000000$o!"), Err(PatternError::TooLarge(_))));
+    assert!(matches!(pattern::parse_rle("#CXRLE Pos=2147483647,0\n2o!"), Err(PatternError::InvalidHeader(_))));
+    assert_eq!(pattern::parse_rle("1000o!").unwrap().get_living_count(), 1000);
+}
+
+#[test]
+fn pattern_fixtures() {
+    use util::fixtures;
+
+    let mut gun = fixtures::parsed("gosper_glider_gun.rle", pattern::parse_rle);
+    assert_eq!(gun.get_living_count(), 36);
+    let (min, max) = gun.bounding_box().unwrap();
+    assert_eq!((max.x - min.x + 1, max.y - min.y + 1), (36, 9));
+
+    // A glider leaves the gun every 30 generations
+    for _ in 0..30 {
+        gun.simulate();
+    }
+    assert_eq!(gun.get_living_count(), 36 + 5);
+}
+
+#[test]
+fn cells_round_trip() {
+    let cells = "!Name: Blinker\n.O.\n.O.\n.O.\n";
+    let env = pattern::parse_cells(cells).unwrap();
+    assert_eq!(env.get_living_count(), 3);
+    assert!(env.get_cell(&SimCell::new(1, -2)));
+
+    assert_eq!(pattern::to_cells(&env), "O\nO\nO\n");
+    assert!(pattern::parse_cells("..X").is_err());
+}
+
+#[test]
+fn ascii_round_trip() {
+    // Without a header the grid starts at the origin
+    let glider: Environment = "\n x\n  x\nxxx\n\n".parse().unwrap();
+    assert_eq!(glider.bounding_box(), Some((SimCell::new(0, -2), SimCell::new(2, 0))));
+    assert_eq!(glider.to_string(), " x\n  x\nxxx\n");
+
+    // The header keeps the position and rule, and dots can be used as dead cells
+    let blinker: Environment = "@(-1, 4) B36/S23\n.x.\n.x.\n.x.".parse().unwrap();
+    assert!(blinker.get_cell(&SimCell::new(0, 4)));
+    assert!(blinker.get_cell(&SimCell::new(0, 2)));
+    assert_eq!(blinker.to_string(), "@(0,4) B36/S23\nx\nx\nx\n");
+
+    let parsed: Environment = blinker.to_string().parse().unwrap();
+    assert!(parsed.diff(&blinker).is_empty());
+    assert_eq!(parsed.rule(), blinker.rule());
+
+    // Environments printed by a viewport can be parsed back
+    let mut viewport = Viewport::new(0, 0, 3, 3);
+    glider.fill_viewport(&mut viewport);
+    let from_viewport: Environment = viewport.to_string().parse().unwrap();
+    assert!(from_viewport.diff(&glider).is_empty());
+
+    assert!("x\nxo".parse::<Environment>().is_err());
+    assert!("@(1)\nx".parse::<Environment>().is_err());
+}
+
+#[test]
+fn headless_simulation() {
+    let mut env = pattern::parse_rle("x = 3, y = 1\n3o!").unwrap();
+    let summary = headless::simulate(&mut env, 3);
+
+    assert_eq!(summary.generations, 3);
+    assert_eq!(summary.population, 3);
+    assert_eq!(summary.bounding_box, Some((SimCell::new(1, -1), SimCell::new(1, 1))));
+}
+
+#[test]
+fn headless_streams() {
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    let options = headless::HeadlessOptions {
+        pattern: Some(PathBuf::from(headless::STANDARD_STREAM)),
+        generations: 1,
+        out: Some(PathBuf::from(headless::STANDARD_STREAM)),
+        format: Some(pattern::PatternFormat::Cells),
+        ..Default::default()
+    };
+    assert!(options.writes_to_output());
+
+    let mut output = Vec::new();
+    let summary = headless::run(&options, Cursor::new("x = 3, y = 1\n3o!"), &mut output).unwrap();
+    assert_eq!(summary.population, 3);
+    assert_eq!(String::from_utf8(output).unwrap(), "O\nO\nO\n");
+}
+
+#[test]
+fn image_seeding() {
+    use image::{decode_bmp, to_environment, GrayImage, ImageOptions};
+
+    // Uncompressed BMP, with the rows stored from the bottom and padded to 4 bytes
+    let bmp = |width: u32, height: i32, bits: u16, palette: &[[u8; 4]], rows: &[Vec<u8>]| {
+        let header_size = 40;
+        let pixels_offset = 14 + header_size + 4 * palette.len() as u32;
+        let mut data = b"BM".to_vec();
+        data.extend(0u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(pixels_offset.to_le_bytes());
+        data.extend(header_size.to_le_bytes());
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(bits.to_le_bytes());
+        data.extend([0; 24]);
+        palette.iter().for_each(|color| data.extend(color));
+        for row in rows.iter().rev() {
+            let mut row = row.clone();
+            row.resize(row.len().div_ceil(4) * 4, 0);
+            data.extend(row);
+        }
+        data
+    };
+
+    // Black, white, red, white on the top row and blue, white, white, black on the bottom one
+    let true_color = bmp(4, 2, 24, &[], &[
+        vec![0, 0, 0, 255, 255, 255, 0, 0, 255, 255, 255, 255],
+        vec![255, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0],
+    ]);
+    let image = decode_bmp(&true_color).unwrap();
+    assert_eq!(image, GrayImage { width: 4, height: 2, pixels: vec![0, 255, 76, 255, 29, 255, 255, 0] });
+
+    let options = ImageOptions { origin: Some(SimCell::new(10, 5)), ..ImageOptions::default() };
+    let environment = to_environment(&image, &options);
+    let expected = [SimCell::new(10, 5), SimCell::new(12, 5), SimCell::new(10, 4), SimCell::new(13, 4)];
+    assert_eq!(environment.get_living_count(), expected.len());
+    assert!(expected.iter().all(|cell| environment.get_cell(cell)));
+
+    // Indexed colors, one bit per pixel
+    let indexed = bmp(3, 1, 1, &[[255, 255, 255, 0], [0, 0, 0, 0]], &[vec![0b1010_0000]]);
+    assert_eq!(decode_bmp(&indexed).unwrap().pixels, vec![0, 255, 0]);
+
+    // Blocks of pixels are averaged, and images are centered by default
+    let scaled = to_environment(&image, &ImageOptions { scale: 2, ..ImageOptions::default() });
+    assert_eq!(scaled.get_living_count(), 0);
+    let inverted = to_environment(&image, &ImageOptions { scale: 2, invert: true, ..ImageOptions::default() });
+    assert_eq!(inverted.living_cells().collect::<Vec<_>>(), vec![&SimCell::new(-1, 0), &SimCell::new(0, 0)]);
+
+    // Dithering keeps the average shade
+    let gray = GrayImage { width: 16, height: 16, pixels: vec![192; 256] };
+    assert_eq!(to_environment(&gray, &ImageOptions::default()).get_living_count(), 0);
+    let dithered = to_environment(&gray, &ImageOptions { dither: true, ..ImageOptions::default() });
+    assert!((56..=72).contains(&dithered.get_living_count()));
+
+    assert!(decode_bmp(b"BM").is_err());
+    assert!(decode_bmp(&true_color[..60]).is_err());
+    assert!(image::is_image(std::path::Path::new("logo.PNG")));
+    assert!(!image::is_image(std::path::Path::new("gun.rle")));
+}
+
+#[test]
+fn census_objects() {
+    use census::{components, Census, ObjectKind};
+
+    let census = Census::new(Rule::default());
+    let parse = |data: &str| pattern::parse_ascii(data).unwrap();
+
+    // Objects are named in any position, orientation and phase
+    let block = census.classify(&parse("@(40,-7)\nxx\nxx"));
+    assert_eq!((block.name.as_str(), block.kind), ("block", ObjectKind::StillLife));
+    let blinker = census.classify(&parse("x\nx\nx"));
+    assert_eq!((blinker.name.as_str(), blinker.kind), ("blinker", ObjectKind::Oscillator { period: 2 }));
+    let mut glider = parse("xxx\nx\n x");
+    glider.simulate();
+    let glider = census.classify(&glider);
+    assert_eq!((glider.name.as_str(), glider.kind), ("glider", ObjectKind::Spaceship { period: 4 }));
+    assert!(glider.known);
+
+    // Unknown objects get a code
+    let unknown = census.classify(&parse("xx\nx x\n x x\n  x x\n   xx"));
+    assert_eq!((unknown.name.as_str(), unknown.known), ("xs10", false));
+    assert_eq!(unknown.population(), 10);
+
+    // Cells two cells apart belong to the same object
+    let ash = parse("xx xx     xxx\nxx xx\n\n\n  x");
+    let objects = census.take(&ash);
+    assert_eq!(components(&ash).len(), 3);
+    let mut names: Vec<&str> = objects.iter().map(|o| o.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["blinker", "unstable1", "xs8"]);
+
+    // Other rules don't have names
+    assert_eq!(Census::new("B36/S23".parse().unwrap()).classify(&parse("xx\nxx")).name, "xs4");
+}
+
+#[test]
+fn soup_search() {
+    use search::{stabilize, SearchOptions};
+
+    let mut blinker = pattern::parse_ascii("xxx").unwrap();
+    assert_eq!(stabilize(&mut blinker, 1000), Some(150));
+    let mut glider = pattern::parse_ascii(" x\n  x\nxxx").unwrap();
+    assert!(stabilize(&mut glider, 1000).is_some());
+    let mut soup = SearchOptions::default().soup(3);
+    assert_eq!(stabilize(&mut soup, 10), None);
+
+    // Results don't depend on the amount of threads
+    let options = SearchOptions { soups: 12, seed: 7, size: 8, threads: 1, ..SearchOptions::default() };
+    let single = search::run(&options);
+    let parallel = search::run(&SearchOptions { threads: 3, ..options.clone() });
+    assert_eq!(single.counts, parallel.counts);
+    assert_eq!(single.generations, parallel.generations);
+    assert!(single.counts.values().sum::<usize>() > 0);
+
+    let report = single.to_string();
+    assert!(report.starts_with("Soups: 12 of 8x8 at 50% (B3/S23), seeds 7 to 18\n"));
+    assert!(report.contains("\nObjects:\n"));
+}
+
+#[test]
+fn rule_exploration() {
+    use explore::{ExploreOptions, Outcome, RuleSet};
+
+    let RuleSet(rules) = "around:B3/S23".parse().unwrap();
+    assert_eq!(rules.len(), 18);
+    assert_eq!(rules[0], Rule::conway());
+    assert!(rules.contains(&"B36/S23".parse().unwrap()) && rules.contains(&"B3/S2".parse().unwrap()));
+    assert!(!rules.iter().any(|rule| rule.is_birth(0)));
+    let RuleSet(rules) = "B3/S23,B1/S1,B/S".parse().unwrap();
+    assert_eq!(rules.len(), 3);
+    assert!("B3/S23,B9".parse::<RuleSet>().is_err());
+
+    let options = ExploreOptions { rules, generations: 500, max_population: 1000 };
+    let blinker = pattern::parse_ascii("xxx").unwrap();
+    let exploration = explore::run(&blinker, &options);
+    let [conway, growing, empty] = &exploration.metrics[..] else {
+        panic!("expected a result per rule");
+    };
+    assert_eq!((conway.outcome, conway.final_population, conway.peak_population), (Outcome::Stabilized(150), 3, 3));
+    assert!(matches!(growing.outcome, Outcome::Exploded(_)) && growing.growth_rate() > 1.0);
+    assert_eq!((empty.final_population, empty.growth_rate()), (0, -0.02));
+
+    let csv = exploration.to_csv();
+    assert_eq!(csv.lines().count(), 4);
+    assert!(csv.starts_with("rule,outcome,generations,stabilization,initial,final,peak,growth\nB3/S23,stable,150,150,3,3,3,0.000\n"));
+    assert!(exploration.to_string().lines().nth(1).unwrap().starts_with("B3/S23          stable"));
+}
+
+#[test]
+fn pattern_urls() {
+    use std::path::Path;
+    use headless::HeadlessError;
+
+    assert!(headless::is_url("https://conwaylife.com/patterns/glider.rle"));
+    assert!(headless::is_url("http://example.com/gun"));
+    assert!(!headless::is_url("patterns/glider.rle"));
+
+    // URLs are never read as files
+    let result = headless::load(Path::new("https://localhost:0/glider.rle"));
+    assert!(matches!(result, Err(HeadlessError::Http(_))));
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn server_updates() {
+    use server::Update;
+
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(0, 0), SimCell::new(1, 0), SimCell::new(2, 0)]);
+    let state = Update::state(&environment, 0);
+    assert_eq!(serde_json::to_string(&state).unwrap(),
+               r#"{"type":"state","generation":0,"rule":"B3/S23","cells":[[0,0],[1,0],[2,0]]}"#);
+
+    let mut events = Vec::new();
+    environment.simulate_observed(&mut events);
+    let Update::Generation { births, deaths, .. } = Update::generation(&events, 1) else {
+        panic!("expected a generation update");
+    };
+    assert_eq!(births.len(), 2);
+    assert_eq!(deaths.len(), 2);
+    assert!(births.contains(&(1, 1)) && deaths.contains(&(0, 0)));
+
+    // Type, generation and a list with a single cell
+    let update = Update::Generation { generation: 2, births: vec![(-1, 3)], deaths: vec![] };
+    let mut expected = vec![1u8];
+    expected.extend(2u64.to_le_bytes());
+    expected.extend(1u32.to_le_bytes());
+    expected.extend((-1i32).to_le_bytes());
+    expected.extend(3i32.to_le_bytes());
+    expected.extend(0u32.to_le_bytes());
+    assert_eq!(update.to_bytes(), expected);
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn server_edits() {
+    use server::{Command, CommandError, Edits};
+
+    let mut environment = Environment::default();
+    let mut edits = Edits::default();
+
+    let toggle: Command = r#"{"type":"toggle","x":1,"y":-2}"#.parse().unwrap();
+    assert_eq!(toggle, Command::Toggle { x: 1, y: -2 });
+    edits.apply(&mut environment, &toggle).unwrap();
+    assert!(environment.get_cell(&SimCell::new(1, -2)));
+
+    // A cell can only be edited once per generation
+    assert!(matches!(edits.apply(&mut environment, &toggle), Err(CommandError::Conflict)));
+    assert!(environment.get_cell(&SimCell::new(1, -2)));
+
+    // Placements are rejected as a whole when overlapping an edited cell
+    let glider = "x = 3, y = 3\nbo$2bo$3o!".to_string();
+    let place = Command::Place { x: 0, y: 0, pattern: glider.clone() };
+    assert!(matches!(edits.apply(&mut environment, &place), Err(CommandError::Conflict)));
+    assert_eq!(environment.get_living_count(), 1);
+    edits.apply(&mut environment, &Command::Place { x: 10, y: 0, pattern: glider }).unwrap();
+    assert_eq!(environment.get_living_count(), 6);
+    assert_eq!(edits.events().len(), 6);
+
+    // Every cell can be edited again on the next generation
+    edits.clear();
+    assert!(edits.is_empty());
+    edits.apply(&mut environment, &toggle).unwrap();
+    assert_eq!(edits.events(), &[CellEvent::Death(SimCell::new(1, -2))]);
+
+    // Invalid commands
+    assert!(matches!("{\"type\":\"fill\"}".parse::<Command>(), Err(CommandError::Invalid(_))));
+    let far = Command::Toggle { x: i32::MIN, y: 0 };
+    assert!(matches!(edits.apply(&mut environment, &far), Err(CommandError::OutOfBounds)));
+    let broken = Command::Place { x: 0, y: 0, pattern: "x = 3, y = 3\nbo$2bo$3q!".to_string() };
+    assert!(matches!(edits.apply(&mut environment, &broken), Err(CommandError::Pattern(_))));
+
+    // Huge run counts are rejected while parsing, without creating the cells
+    let huge: Command = r#"{"type":"place","x":0,"y":0,"pattern":"2000000000o!"}"#.parse().unwrap();
+    assert!(matches!(edits.apply(&mut environment, &huge), Err(CommandError::PatternTooLarge)));
+    let overflow = Command::Place { x: 0, y: 0, pattern: "99999999999o!".to_string() };
+    assert!(matches!(edits.apply(&mut environment, &overflow), Err(CommandError::Pattern(_))));
+    let wide = Command::Place { x: 0, y: 0, pattern: format!("{}o!", server::MAX_PATTERN_SIZE + 1) };
+    assert!(matches!(edits.apply(&mut environment, &wide), Err(CommandError::PatternTooLarge)));
+}
+
+#[test]
+fn pattern_format_detection() {
+    use pattern::{detect_format, PatternFormat};
+
+    assert_eq!(detect_format("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"), PatternFormat::Rle);
+    assert_eq!(detect_format("!Name: Blinker\nOOO"), PatternFormat::Cells);
+    assert_eq!(detect_format(".O.\n..O\nOOO"), PatternFormat::Cells);
+    assert_eq!(detect_format("living_cells:\n- x: 0\n  y: 0\n"), PatternFormat::Yaml);
+    assert_eq!(detect_format("@(0,2)\n x\nxxx"), PatternFormat::Ascii);
+    assert_eq!(detect_format(" x\n  x\nxxx\n"), PatternFormat::Ascii);
+    assert_eq!(detect_format("x = 3, y = 1\n3o!"), PatternFormat::Rle);
+}
+
+#[test]
+fn viewport_rows() {
+    let mut viewport = Viewport::new(0, 0, 3, 2);
+    viewport.set_living(1, -1);
+
+    let rows: Vec<&[bool]> = viewport.rows().collect();
+    assert_eq!(rows, vec![&[false, false, false][..], &[false, true, false][..]]);
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn command_parser() {
+    use crate::application::{AppEvent, TabChange};
+    use crate::application::commands::parse;
+
+    assert!(matches!(parse(r#"place "glider gun" 3 -4"#), AppEvent::Place(name, Some((3, -4))) if name == "glider gun"));
+    assert!(matches!(parse("a lwss"), AppEvent::Place(name, None) if name == "lwss"));
+    assert!(matches!(parse("place glider 3"), AppEvent::ErrorInput(..)));
+
+    assert!(matches!(parse("tab new"), AppEvent::Tab(TabChange::New)));
+    assert!(matches!(parse("tab"), AppEvent::Tab(TabChange::Next)));
+    assert!(matches!(parse("b 2"), AppEvent::Tab(TabChange::Select(2))));
+    assert!(matches!(parse("tab later"), AppEvent::ErrorInput(..)));
+    assert!(matches!(parse("rule B36/S23"), AppEvent::Rule(_)));
+    assert!(matches!(parse("rule B9"), AppEvent::ErrorInput(..)));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn command_completion() {
+    use std::fs;
+    use crate::application::commands;
+    use crate::catalog::Catalog;
+    use util::test_helper::TempDirectoryHandler;
+
+    let catalog = Catalog::default();
+    let complete = |input: &str| commands::complete(input, &catalog);
+
+    // Command names
+    assert_eq!(complete("lo").line, "load ");
+    assert_eq!(complete("pa").line, "pause");
+    let ambiguous = complete("s");
+    assert_eq!(ambiguous.line, "s");
+    assert_eq!(ambiguous.candidates, vec!["stats", "step", "speed", "slower", "screensaver", "snap", "save"]);
+    assert_eq!(complete("r").candidates, vec!["rule", "reset", "restore", "record", "run"]);
+    assert_eq!(complete("sp").line, "speed ");
+    assert_eq!(complete("ste").line, "step ");
+    assert!(complete("pause now").candidates.is_empty());
+    assert_eq!(complete("place gl").line, "place Glider");
+    assert_eq!(complete("place \"gos").line, "place \"Gosper glider gun\"");
+    assert_eq!(complete("place \"Gosper g").line, "place \"Gosper glider gun\"");
+    assert!(complete("place \"Gosper x").candidates.is_empty());
+    assert_eq!(complete("place G").line, "place G");
+    assert!(matches!(crate::application::commands::parse("load https://conwaylife.com/patterns/glider.rle"),
+                     crate::application::AppEvent::Fetch(url) if url.ends_with("glider.rle")));
+
+    // Paths
+    let directory = TempDirectoryHandler::builder()
+        .directory("patterns")
+        .files([("glider.rle", ""), ("gun.rle", "")])
+        .build()
+        .unwrap();
+    let prefix = format!("{}/", directory.path().display());
+
+    let ambiguous = complete(&format!("load {}g", prefix));
+    assert_eq!(ambiguous.line, format!("load {}g", prefix));
+    assert_eq!(ambiguous.candidates.len(), 2);
+    assert_eq!(complete(&format!("load {}gl", prefix)).line, format!("load {}glider.rle", prefix));
+    assert_eq!(complete(&format!("save {}pa", prefix)).line, format!("save {}patterns/", prefix));
+    assert!(complete(&format!("record out.gif {}g", prefix)).candidates.is_empty());
+
+    // Patterns added to the catalog are completed too
+    let mut catalog = Catalog::default();
+    fs::write(directory.path().join("patterns/copperhead.rle"), "x = 1, y = 1\no!").unwrap();
+    catalog.load_directory(&directory.path().join("patterns")).unwrap();
+    assert_eq!(commands::complete("place copp", &catalog).line, "place copperhead");
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn script_file() {
+    use std::fs;
+    use crate::application::App;
+
+    let directory = std::env::temp_dir().join(format!("conway-life-script-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let saved = directory.join("saved.con");
+    let nested = directory.join("nested.txt");
+    fs::write(&nested, "# Advance the simulation\nstep 2\n\n").unwrap();
+    let script = directory.join("script.txt");
+    fs::write(&script, format!("pause\nstep 2\nrun {}\nsave {}\n", nested.display(), saved.display())).unwrap();
+
+    let mut app = App::default();
+    app.set_script(&script).unwrap();
+    let mut expected = app.environment().clone();
+    for _ in 0..4 {
+        expected.simulate();
+    }
+    assert!(!app.run_startup_script());
+    assert_eq!(app.generation(), 4);
+    assert_eq!(app.environment().living_cells().collect::<Vec<_>>(), expected.living_cells().collect::<Vec<_>>());
+    assert!(saved.is_file());
+
+    // Stop at the first invalid line
+    fs::write(&script, "pause\nunknown\nstep\n").unwrap();
+    app.set_script(&script).unwrap();
+    assert!(!app.run_startup_script());
+    assert_eq!(app.generation(), 4);
+
+    // Scripts that run themselves
+    fs::write(&script, format!("run {}\n", script.display())).unwrap();
+    app.set_script(&script).unwrap();
+    assert!(!app.run_startup_script());
+
+    fs::write(&script, "quit\nstep\n").unwrap();
+    app.set_script(&script).unwrap();
+    assert!(app.run_startup_script());
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn pattern_catalog() {
+    use catalog::Catalog;
+    use screensaver::CycleDetector;
+    use util::test_helper::TempDirectoryHandler;
+
+    let catalog = Catalog::default();
+    for entry in catalog.entries() {
+        assert!(entry.environment().unwrap().get_living_count() > 0, "{}", entry.name);
+    }
+
+    let name = |query: &str| catalog.find(query).map(|e| e.name.as_str());
+    assert_eq!(name("glider"), Some("Glider"));
+    assert_eq!(name("Gosper Glider Gun"), Some("Gosper glider gun"));
+    assert_eq!(name("gosper glidr gun"), Some("Gosper glider gun"));
+    assert_eq!(name("gun"), Some("Gosper glider gun"));
+    assert_eq!(name("lwss"), Some("Lightweight spaceship"));
+    assert_eq!(name("f-pentomino"), Some("R-pentomino"));
+    assert_eq!(name("unknown"), None);
+    assert_eq!(name(""), None);
+
+    assert_eq!(catalog.find("gosper glider gun").unwrap().environment().unwrap().get_living_count(), 36);
+    let mut pulsar = catalog.find("pulsar").unwrap().environment().unwrap();
+    assert_eq!(pulsar.get_living_count(), 48);
+    let mut detector = CycleDetector::new(5);
+    let periods: Vec<_> = (0..4).map(|_| {
+        let period = detector.observe(&pulsar);
+        pulsar.simulate();
+        period
+    }).collect();
+    assert_eq!(periods, vec![None, None, None, Some(3)]);
+
+    // Patterns from a directory
+    let directory = TempDirectoryHandler::builder()
+        .file("spaceship.rle", "#N Copperhead\n#C A c/10 orthogonal spaceship\nx = 2, y = 1\n2o!\n")
+        .file("snake.cells", "!Name: Snake\n!A still life\nOO.O\nO.OO\n")
+        .file("unnamed.rle", "x = 1, y = 1\no!\n")
+        .file("notes.txt~", "")
+        .build()
+        .unwrap();
+
+    let mut catalog = Catalog::default();
+    assert_eq!(catalog.load_directory(directory.path()).unwrap(), 3);
+    let copperhead = catalog.find("copperhead").unwrap();
+    assert_eq!(copperhead.description, "A c/10 orthogonal spaceship");
+    assert_eq!(catalog.find("snake").unwrap().description, "A still life");
+    assert_eq!(catalog.find("snake").unwrap().environment().unwrap().get_living_count(), 6);
+    assert_eq!(name("unnamed"), None);
+    assert!(catalog.find("unnamed").is_some());
+}
+
+#[test]
+fn place_pattern() {
+    let mut glider = Environment::default();
+    glider.set_living(&[SimCell::new(1, 0), SimCell::new(2, -1), SimCell::new(0, -2), SimCell::new(1, -2), SimCell::new(2, -2)]);
+
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(0, 0)]);
+    environment.place(&glider, SimCell::new(10, 20));
+    assert_eq!(environment.get_living_count(), 6);
+    assert!(environment.get_cell(&SimCell::new(11, 20)));
+    assert!(environment.get_cell(&SimCell::new(12, 18)));
+
+    environment.place(&Environment::default(), SimCell::new(0, 0));
+    assert_eq!(environment.get_living_count(), 6);
+}
+
+#[test]
+fn cycle_detection() {
+    use screensaver::CycleDetector;
+
+    // Still life
+    let mut detector = CycleDetector::new(10);
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(0, 0), SimCell::new(1, 0), SimCell::new(0, 1), SimCell::new(1, 1)]);
+    assert_eq!(detector.observe(&environment), None);
+    environment.simulate();
+    assert_eq!(detector.observe(&environment), Some(1));
+
+    // Blinker
+    let mut detector = CycleDetector::new(10);
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(-1, 0), SimCell::new(0, 0), SimCell::new(1, 0)]);
+    let periods: Vec<_> = (0..3).map(|_| {
+        let period = detector.observe(&environment);
+        environment.simulate();
+        period
+    }).collect();
+    assert_eq!(periods, vec![None, None, Some(2)]);
+
+    // Gliders move, so they never repeat
+    let mut detector = CycleDetector::new(10);
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(1, 0), SimCell::new(2, -1), SimCell::new(0, -2), SimCell::new(1, -2), SimCell::new(2, -2)]);
+    for _ in 0..8 {
+        assert_eq!(detector.observe(&environment), None);
+        environment.simulate();
+    }
+}
+
+#[test]
+fn screensaver_soups() {
+    use screensaver::Screensaver;
+
+    let highlife: Rule = "B36/S23".parse().unwrap();
+    let mut environment = Environment::with_rule(highlife);
+    let mut screensaver = Screensaver::new(16, 10, Some(7));
+    screensaver.seed(&mut environment);
+    assert_eq!(environment.rule(), highlife);
+    assert!(environment.get_living_count() > 0);
+    let (min, max) = environment.bounding_box().unwrap();
+    assert!(min.x >= -8 && max.x < 8 && min.y > -5 && max.y <= 5);
+
+    // The same seed creates the same soups
+    let mut other = Environment::default();
+    Screensaver::new(16, 10, Some(7)).seed(&mut other);
+    assert_eq!(environment.living_cells().collect::<Vec<_>>(), other.living_cells().collect::<Vec<_>>());
+
+    // Soups that never stagnate are reseeded after a while
+    screensaver.max_generations = 3;
+    let moving: Vec<Environment> = (0..3).map(|x| {
+        let mut environment = Environment::default();
+        environment.set_living(&[SimCell::new(x, 0)]);
+        environment
+    }).collect();
+    assert!(!screensaver.is_stagnant(&moving[0]));
+    assert!(!screensaver.is_stagnant(&moving[1]));
+    assert!(screensaver.is_stagnant(&moving[2]));
+
+    // Repeated states stagnate right away
+    screensaver.seed(&mut environment);
+    let empty = Environment::default();
+    assert!(!screensaver.is_stagnant(&empty));
+    assert!(screensaver.is_stagnant(&empty));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn status_bar() {
+    use std::time::Duration;
+    use crate::application::widgets::StatusBar;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+    use ratatui::widgets::Widget;
+
+    let render = |status_bar: StatusBar| {
+        let area = Rect::new(0, 0, 120, 1);
+        let mut buffer = Buffer::empty(area);
+        status_bar.render(area, &mut buffer);
+        buffer.content.iter().map(|cell| cell.symbol.as_str()).collect::<String>()
+    };
+    let status_bar = |generation, paused| StatusBar {
+        generation,
+        rule: "B36/S23".parse().unwrap(),
+        speed: String::from("50ms"),
+        paused,
+        stats: Some((12, Duration::from_micros(30))),
+        rate: None,
+        coordinates: None,
+        tab: None,
+        diff: None,
+        style: Style::default(),
+    };
+
+    let running = render(status_bar(7, false));
+    assert!(running.starts_with("GEN 7        │ B36/S23      │ SPEED 50ms     │ RUNNING │ POP 12"));
+    assert!(running.contains("TIME      30µs"));
+    assert!(!running.contains("X="));
+
+    // Fields don't move when the values change
+    let paused = render(status_bar(12345, true));
+    assert_eq!(paused.find("PAUSED"), running.find("RUNNING"));
+    assert_eq!(paused.find("POP"), running.find("POP"));
+
+    let tabs = render(StatusBar { tab: Some((2, 3)), ..status_bar(7, false) });
+    assert!(tabs.starts_with("TAB 2/3 │ GEN 7 "));
+
+    let diff = render(StatusBar { diff: Some(4), ..status_bar(7, false) });
+    assert!(diff.contains("│ DIFF 4        "));
+
+    let rate = render(StatusBar { rate: Some(99.6), ..status_bar(7, false) });
+    assert!(rate.contains("   100 GEN/S"));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn simulation_worker() {
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use crate::application::AppEvent;
+    use crate::application::session::Session;
+    use crate::application::worker::{Update, Worker};
+    use crate::config::Limits;
+
+    let glider: Environment = ".x\n..x\nxxx".parse().unwrap();
+    let mut expected = glider.clone();
+    for _ in 0..8 {
+        expected.simulate();
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let worker = Worker::spawn(sender);
+    let mut session = Session::new(glider);
+    for round in 0..2 {
+        // The environment is only sent with the first job, and the session keeps it while the
+        // worker simulates its own copy
+        let job = session.job(3, 4, Limits::default());
+        assert_eq!(job.environment.is_some(), round == 0);
+        assert!(worker.send(job).is_ok());
+        assert_eq!(session.generation % 4, 0);
+
+        match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            AppEvent::Simulated(simulated) => {
+                assert_eq!(simulated.tab, 3);
+                assert!(simulated.exceeded.is_none());
+                assert!(matches!(&simulated.update, Update::Changes(changes, false) if changes.len() == 8));
+                session.finish_job(simulated.simulation, simulated.update);
+            }
+            _ => panic!("Expected a simulated tab"),
+        }
+    }
+    assert_eq!(session.generation, 8);
+    assert!(expected.diff(session.environment()).is_empty());
+
+    // Changed environments are sent again. Limits stop the simulation early.
+    session.environment_mut().toggle_cell(&SimCell::new(10, 10));
+    let limits = Limits { max_population: 3, ..Limits::default() };
+    let job = session.job(0, 4, limits);
+    assert!(job.environment.is_some());
+    assert!(worker.send(job).is_ok());
+    match receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+        AppEvent::Simulated(simulated) => {
+            assert!(simulated.exceeded.is_some());
+            assert_eq!(simulated.simulation.generation, 9);
+        }
+        _ => panic!("Expected a simulated tab"),
+    }
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn simulation_rate() {
+    use std::time::{Duration, Instant};
+    use crate::application::AppEvent;
+    use crate::application::commands::parse;
+    use crate::application::session::RateMeter;
+
+    let start = Instant::now();
+    let mut meter = RateMeter::new(start);
+    assert_eq!(meter.per_second(start), 0.0);
+
+    // The first second is measured over the time elapsed so far
+    meter.record(start + Duration::from_millis(100), 10);
+    meter.record(start + Duration::from_millis(250), 15);
+    assert!((meter.per_second(start + Duration::from_millis(500)) - 50.0).abs() < 1e-6);
+
+    // Later only the last second counts
+    meter.record(start + Duration::from_millis(1900), 30);
+    assert!((meter.per_second(start + Duration::from_millis(2000)) - 30.0).abs() < 1e-6);
+    assert_eq!(meter.per_second(start + Duration::from_secs(5)), 0.0);
+
+    assert!(matches!(parse("fps 10"), AppEvent::FrameRate(10)));
+    assert!(matches!(parse("i 60"), AppEvent::FrameRate(60)));
+    assert!(matches!(parse("fps 0"), AppEvent::ErrorInput(..)));
+    assert!(matches!(parse("fps"), AppEvent::ErrorInput(..)));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn environment_view_changes() {
+    use crate::application::widgets::EnvironmentView;
+    use crate::config::Theme;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::Widget;
+
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(1, -1), SimCell::new(1, -2), SimCell::new(1, -3)]);
+    let snapshot = environment.clone();
+    environment.simulate();
+    let changes = snapshot.diff(&environment);
+
+    // The viewport includes the border, as in the application
+    let mut viewport = Viewport::new(-1, 1, 7, 7);
+    environment.fill_viewport(&mut viewport);
+    let theme = Theme::default();
+    let area = Rect::new(0, 0, 7, 7);
+    let mut buffer = Buffer::empty(area);
+    EnvironmentView::new(&viewport, &theme).changes(Some(&changes)).render(area, &mut buffer);
+
+    // The blinker turns horizontal at y = -2, drawn in the row 3 of the viewport, below the border
+    let row = |y: u16| (2..5).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>();
+    assert_eq!(row(3), " o ");
+    assert_eq!(row(4), "xxx");
+    assert_eq!(row(5), " o ");
+    assert_eq!(buffer.get(2, 4).style.fg, Some(theme.added.0));
+    assert_eq!(buffer.get(3, 3).style.fg, Some(theme.removed.0));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn application_tabs() {
+    use std::fs;
+    use crate::application::App;
+    use util::test_helper::TempFileHandler;
+
+    let script = TempFileHandler::build_with_extension("txt", "pause\ntab new\nrule B36/S23\nstep 3\ntab new\ntab close\ntab 1\n").unwrap();
+
+    let mut app = App::default();
+    app.set_script(script.path()).unwrap();
+    assert!(!app.run_startup_script());
+    assert_eq!(app.tab_count(), 2);
+    assert_eq!(app.generation(), 3);
+    assert!(app.environment().rule().is_conway());
+
+    // Tabs advance together, each one following its own rule
+    let mut conway = App::default().environment().clone();
+    for _ in 0..3 {
+        conway.simulate();
+    }
+    assert_eq!(app.environment().living_cells().collect::<Vec<_>>(), conway.living_cells().collect::<Vec<_>>());
+
+    fs::write(script.path(), "tab 2\ntab close\ntab close\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.tab_count(), 1);
+    assert!(app.environment().rule().is_conway());
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn application_reset() {
+    use std::fs;
+    use crate::application::App;
+    use util::test_helper::TempFileHandler;
+
+    let script = TempFileHandler::build_with_extension("txt", "pause\nrule B36/S23\nstep 5\nclear\n").unwrap();
+
+    let mut app = App::default();
+    let initial = app.environment().clone();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.environment().get_living_count(), 0);
+    assert_eq!(app.generation(), 5);
+
+    // Resetting keeps the rule
+    fs::write(script.path(), "reset\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.generation(), 0);
+    assert!(initial.diff(app.environment()).is_empty());
+    assert_eq!(app.environment().rule().to_string(), "B36/S23");
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn snapshot_slots() {
+    use std::fs;
+    use crate::application::{App, AppEvent};
+    use crate::application::commands::parse;
+    use util::test_helper::TempFileHandler;
+
+    let script = TempFileHandler::build_with_extension("txt", "pause\nstep 3\nsnap before\nstep 4\nsnap after\nclear\nrestore before\n").unwrap();
+
+    let mut app = App::default();
+    let mut expected = app.environment().clone();
+    for _ in 0..3 {
+        expected.simulate();
+    }
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.generation(), 3);
+    assert!(expected.diff(app.environment()).is_empty());
+
+    // Slots are kept after restoring, and resetting still goes back to the loaded environment
+    fs::write(script.path(), "restore after\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.generation(), 7);
+    fs::write(script.path(), "reset\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.generation(), 0);
+
+    // Unknown slots leave the environment alone, and names are checked
+    fs::write(script.path(), "step 2\nrestore missing\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.generation(), 2);
+    assert!(matches!(parse("snap ../up"), AppEvent::ErrorInput(..)));
+    assert!(matches!(parse("restore"), AppEvent::ErrorInput(..)));
+    assert!(matches!(parse("snap"), AppEvent::Snap(None)));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn saved_snapshots() {
+    use std::fs;
+    use crate::application::App;
+    use crate::config::Config;
+
+    let directory = std::env::temp_dir().join(format!("conway-life-snapshots-{}", std::process::id()));
+    let script = std::env::temp_dir().join(format!("conway-life-snapshots-{}.txt", std::process::id()));
+    let config = Config { snapshots: Some(directory.clone()), ..Config::default() };
+
+    // Snapshots written by a run can be restored by a later one
+    fs::write(&script, "pause\nstep 6\nsnap keep\n").unwrap();
+    let mut app = App::from_config(&config).unwrap();
+    app.set_script(&script).unwrap();
+    app.run_startup_script();
+    assert!(directory.join("keep.con").exists());
+
+    fs::write(&script, "restore keep\n").unwrap();
+    let mut other = App::from_config(&config).unwrap();
+    other.set_script(&script).unwrap();
+    other.run_startup_script();
+    assert_eq!(other.generation(), 6);
+    assert!(app.environment().diff(other.environment()).is_empty());
+
+    fs::remove_dir_all(directory).unwrap();
+    fs::remove_file(script).unwrap();
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn seeded_runs() {
+    use crate::application::App;
+
+    let run = |seed| {
+        let mut app = App::default();
+        app.set_seed(seed);
+        app.start_screensaver();
+        app.environment().clone()
+    };
+
+    // The same seed repeats the same soups
+    assert!(run(11).diff(&run(11)).is_empty());
+    assert!(!run(11).diff(&run(12)).is_empty());
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn saved_seed() {
+    use std::fs;
+    use crate::application::App;
+
+    let directory = std::env::temp_dir();
+    let save = directory.join(format!("conway-life-seed-{}.con", std::process::id()));
+    let script = directory.join(format!("conway-life-seed-{}.txt", std::process::id()));
+    fs::write(&script, format!("save \"{}\"\n", save.display())).unwrap();
+
+    let mut app = App::default();
+    app.set_seed(1234);
+    app.set_script(&script).unwrap();
+    app.run_startup_script();
+
+    // The seed is restored with the environment, and ignored when loading the file elsewhere
+    fs::write(&script, format!("load \"{}\"\n", save.display())).unwrap();
+    let mut other = App::default();
+    other.set_script(&script).unwrap();
+    other.run_startup_script();
+    assert_eq!(other.seed(), 1234);
+    assert!(app.environment().diff(other.environment()).is_empty());
+    assert!(headless::load(&save).unwrap().diff(app.environment()).is_empty());
+
+    fs::remove_file(save).unwrap();
+    fs::remove_file(script).unwrap();
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn load_pattern_files() {
+    use std::fs;
+    use crate::application::App;
+    use util::test_helper::TempFileHandler;
+
+    let pattern = TempFileHandler::build_with_extension("rle", "x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!\n").unwrap();
+    let script = TempFileHandler::build_with_extension("txt", format!("pause\nload \"{}\"\n", pattern.path().display())).unwrap();
+
+    let mut app = App::default();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.environment().get_living_count(), 5);
+    assert_eq!(app.environment().rule().to_string(), "B36/S23");
+
+    // Invalid files leave the environment untouched
+    fs::write(pattern.path(), "x = 3, y = 3\nbo$2bo$3q!\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert_eq!(app.environment().get_living_count(), 5);
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn population_limits() {
+    use std::fs;
+    use crate::application::App;
+    use crate::config::Limits;
+    use util::test_helper::TempFileHandler;
+
+    // The F-pentomino grows past 10 cells in a few generations
+    let limits = Limits { max_population: 10, max_size: 0 };
+    assert!(limits.exceeded(&App::default().environment().clone()).is_none());
+
+    let script = TempFileHandler::build_with_extension("txt", "pause\nlimits population 10\nstep 100\n").unwrap();
+
+    let mut app = App::default();
+    app.set_script(script.path()).unwrap();
+    app.run_startup_script();
+    assert!(app.generation() < 100);
+    assert!(limits.exceeded(app.environment()).is_some());
+
+    // Disabled limits let the simulation go on
+    fs::write(script.path(), "limits population off\nstep 10\n").unwrap();
+    app.set_script(script.path()).unwrap();
+    let generation = app.generation();
+    app.run_startup_script();
+    assert_eq!(app.generation(), generation + 10);
+
+    // The bounding box is checked as well
+    let limits = Limits { max_population: 0, max_size: 3 };
+    let mut environment = Environment::default();
+    environment.set_living(&[SimCell::new(0, 0), SimCell::new(2, -2)]);
+    assert!(limits.exceeded(&environment).is_none());
+    environment.set_living(&[SimCell::new(3, 0)]);
+    assert!(limits.exceeded(&environment).is_some());
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn config_file() {
+    use config::{Config, Limits, ThemeColor};
+    use ratatui::style::Color;
+
+    let data = r##"
+        tick_rate = 100
+        frame_rate = 10
+        rule = "B36/S23"
+
+        [keybindings]
+        "ctrl-p" = "pause"
+
+        [theme]
+        living = "light-green"
+        border = "#102030"
+
+        [limits]
+        max_size = 0
+    "##;
+    let config: Config = toml::from_str(data).unwrap();
+    assert_eq!(config.tick_rate, 100);
+    assert_eq!(config.frame_rate, 10);
+    assert_eq!(config.rule.to_string(), "B36/S23");
+    assert_eq!(config.keybindings.get("ctrl-p").map(String::as_str), Some("pause"));
+    assert_eq!(config.theme.living, ThemeColor(Color::LightGreen));
+    assert_eq!(config.theme.background, ThemeColor(Color::Reset));
+    assert_eq!(config.theme.border, ThemeColor(Color::Rgb(0x10, 0x20, 0x30)));
+    assert!(config.startup_pattern.is_none());
+    assert_eq!(config.limits, Limits { max_size: 0, ..Limits::default() });
+
+    // Invalid values are rejected
+    assert!(toml::from_str::<Config>("rule = \"B9\"").is_err());
+    assert!(toml::from_str::<Config>("[theme]\nliving = \"purple-ish\"").is_err());
+    assert!(toml::from_str::<Config>("unknown = 1").is_err());
+
+    // The default file is found within the configuration directory of the user
+    let home = util::test_helper::ScopedHome::new().unwrap();
+    home.config_file("conway-life/config.toml", "tick_rate = 25").unwrap();
+    assert_eq!(Config::load_default().unwrap().tick_rate, 25);
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn config_home() {
+    use config::Config;
+    use util::test_helper::ScopedHome;
+
+    // Without a file the defaults are used
+    let home = ScopedHome::new().unwrap();
+    assert_eq!(Config::default_path(), Some(home.config_dir().join("conway-life").join("config.toml")));
+    assert_eq!(Config::load_default().unwrap().tick_rate, Config::default().tick_rate);
+}
+
+#[test]
+fn error_categories() {
+    use std::path::Path;
+    use crate::headless;
+    use crate::pattern::{self, PatternFormat};
+
+    let unknown = "svg".parse::<PatternFormat>().unwrap_err();
+    assert_eq!(errors::Error::from(unknown).exit_code(), 64);
+
+    let invalid = errors::Error::from(pattern::parse("x = 1, y = 1\nz!", PatternFormat::Rle).unwrap_err());
+    assert!(matches!(invalid, errors::Error::Parse { .. }));
+
+    let missing = errors::Error::from(headless::load(Path::new("missing.rle")).unwrap_err());
+    assert!(matches!(missing, errors::Error::Io { .. }));
+}
@@ -0,0 +1,131 @@
+//! Bindings to run the simulation in a browser, drawing it on a canvas. Built with
+//! `wasm-pack build conway-life --target web --no-default-features --features wasm`, and used by
+//! `web/index.html`.
+
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256StarStar as RandomGenerator;
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::{Environment, Rule, ScreenPoint, Viewport};
+use crate::pattern;
+use crate::screensaver::random_soup;
+
+/// An environment along with the viewport drawn on the canvas
+#[wasm_bindgen]
+pub struct Simulation {
+    environment: Environment,
+    viewport: Viewport,
+    generation: usize,
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Creates an empty simulation, showing the given amount of cells centered at the origin.
+    /// Fails if the size is empty or too large.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize) -> Result<Simulation, JsError> {
+        Simulation::try_new(width, height).ok_or_else(|| JsError::new("The simulation is too large or empty"))
+    }
+
+    /// Replaces the environment with a pattern in any of the supported formats
+    pub fn load(&mut self, data: &str) -> Result<(), JsError> {
+        let format = pattern::detect_format(data);
+        let environment = pattern::parse(data, format)?;
+        self.set_environment(environment);
+        Ok(())
+    }
+
+    /// Replaces the environment with a random soup filling the viewport
+    pub fn randomize(&mut self, density: f64, seed: u32) {
+        let mut generator = RandomGenerator::seed_from_u64(seed.into());
+        let mut soup = random_soup(&mut generator, self.viewport.width(), self.viewport.height(), density);
+        soup.set_rule(self.environment.rule());
+        self.set_environment(soup);
+    }
+
+    /// Changes the rule, given in B/S notation
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsError> {
+        self.environment.set_rule(rule.parse::<Rule>()?);
+        Ok(())
+    }
+
+    /// Toggles the cell at a position of the viewport, counted from its top left corner
+    pub fn toggle(&mut self, column: usize, row: usize) {
+        if let Some(cell) = self.viewport.screen_to_world(ScreenPoint { x: column, y: row }) {
+            self.environment.toggle_cell(&cell);
+        }
+    }
+
+    /// Simulates the given amount of generations
+    pub fn step(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.environment.simulate();
+        }
+        self.generation += generations;
+    }
+
+    /// Moves the viewport by the given amount of cells. Positive values move it right and up.
+    /// The viewport stays in place if it would move past the edges of the environment.
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        let x = self.viewport.x().checked_add(dx);
+        let y = self.viewport.y().checked_add(dy);
+        if let Some(viewport) = x.zip(y).and_then(|(x, y)| {
+            checked_viewport(x, y, self.viewport.width(), self.viewport.height())
+        }) {
+            self.viewport = viewport;
+        }
+    }
+
+    /// Returns the amount of generations simulated since the environment was replaced
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the amount of living cells
+    pub fn population(&self) -> usize {
+        self.environment.get_living_count()
+    }
+
+    /// Draws the viewport on the canvas, with each cell being `scale` pixels wide
+    pub fn draw(&mut self, context: &CanvasRenderingContext2d, scale: usize, living: &str, background: &str) {
+        self.environment.fill_viewport(&mut self.viewport);
+
+        let scale_pixels = scale as f64;
+        context.set_fill_style(&JsValue::from_str(background));
+        context.fill_rect(0.0, 0.0, self.viewport.width() as f64 * scale_pixels,
+                          self.viewport.height() as f64 * scale_pixels);
+
+        context.set_fill_style(&JsValue::from_str(living));
+        for point in self.viewport.points::<u32>(scale).unwrap_or_default() {
+            context.fill_rect(point.x as f64, point.y as f64, scale_pixels, scale_pixels);
+        }
+    }
+}
+
+impl Simulation {
+    /// Creates an empty simulation like [Simulation::new], or None if the size is empty or too
+    /// large
+    pub fn try_new(width: usize, height: usize) -> Option<Simulation> {
+        let x = i32::try_from(width / 2).ok()?;
+        let y = i32::try_from(height / 2).ok()?;
+        let viewport = checked_viewport(-x, y, width, height)?;
+
+        Some(Simulation { environment: Environment::default(), viewport, generation: 0 })
+    }
+
+    fn set_environment(&mut self, environment: Environment) {
+        self.environment = environment;
+        self.generation = 0;
+    }
+}
+
+/// Creates a viewport, or None if it would break any of the preconditions of [Viewport::new]
+fn checked_viewport(x: i32, y: i32, width: usize, height: usize) -> Option<Viewport> {
+    let fits = width != 0 && height != 0
+        && i32::try_from(width).ok().and_then(|width| x.checked_add(width)).is_some()
+        && i32::try_from(height).ok().and_then(|height| y.checked_sub(height)).is_some()
+        && width.checked_mul(height).is_some();
+
+    fits.then(|| Viewport::new(x, y, width, height))
+}
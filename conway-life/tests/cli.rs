@@ -0,0 +1,45 @@
+// The binary is only built with the text based user interface
+#![cfg(feature = "tui")]
+
+use util::process::CliTest;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_conway-life");
+
+#[test]
+fn headless_pipeline() {
+    // Redirected streams are used for the pattern, and the summary goes to the standard error
+    CliTest::new(BINARY)
+        .args(["--generations", "1", "--format", "cells"])
+        .stdin("x = 3, y = 1\n3o!")
+        .run()
+        .success()
+        .stdout_eq("O\nO\nO\n")
+        .stderr_contains("Population: 3");
+}
+
+#[test]
+fn ascii_round_trip() {
+    // Grids at the origin with Conway's rule are written without a header
+    let output = CliTest::new(BINARY)
+        .args(["--generations", "2", "--format", "ascii"])
+        .stdin("x = 3, y = 1\n3o!")
+        .run()
+        .success();
+    assert_eq!(output.stdout(), "xxx\n");
+
+    CliTest::new(BINARY)
+        .args(["-p", "-", "--generations", "1", "--format", "cells"])
+        .stdin(output.stdout())
+        .run()
+        .success()
+        .stdout_eq("O\nO\nO\n");
+}
+
+#[test]
+fn invalid_rule() {
+    CliTest::new(BINARY)
+        .args(["--generations", "1", "--rule", "B9/S23"])
+        .run()
+        .failure()
+        .stderr_contains("Invalid neighbour count '9'");
+}
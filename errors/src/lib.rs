@@ -0,0 +1,142 @@
+//! Error categories shared by the binaries of the workspace. Each crate keeps its own detailed
+//! error enum, and converts it into one of these categories, so every binary reports errors the
+//! same way and exits with a code that tells what went wrong:
+//!
+//! | Category          | Exit code | Meaning                                              |
+//! |-------------------|-----------|------------------------------------------------------|
+//! | [Error::Io]       | 74        | Reading or writing files or streams failed           |
+//! | [Error::Parse]    | 65        | The input, a pattern or an argument, is invalid      |
+//! | [Error::Format]   | 64        | A format is unsupported or can't be encoded          |
+//! | [Error::Config]   | 78        | The configuration is invalid                         |
+//!
+//! The codes follow the BSD `sysexits.h` convention. Binaries run their main logic in a
+//! function returning `Result<(), Error>`, and hand the result to [report]:
+//!
+//! ```ignore
+//! fn main() -> ExitCode {
+//!     errors::report(run())
+//! }
+//! ```
+
+use std::io;
+use std::process::ExitCode;
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Underlying error of a category, kept to report its cause
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("{message}")]
+    Io { message: String, #[source] source: Option<BoxError> },
+
+    #[error("{message}")]
+    Parse { message: String, #[source] source: Option<BoxError> },
+
+    #[error("{message}")]
+    Format { message: String, #[source] source: Option<BoxError> },
+
+    #[error("{message}")]
+    Config { message: String, #[source] source: Option<BoxError> },
+}
+
+impl Error {
+    /// Creates an input or output error without a cause
+    pub fn io(message: impl Into<String>) -> Self {
+        Error::Io { message: message.into(), source: None }
+    }
+
+    /// Creates a parse error without a cause
+    pub fn parse(message: impl Into<String>) -> Self {
+        Error::Parse { message: message.into(), source: None }
+    }
+
+    /// Creates a format error without a cause
+    pub fn format(message: impl Into<String>) -> Self {
+        Error::Format { message: message.into(), source: None }
+    }
+
+    /// Creates a configuration error without a cause
+    pub fn config(message: impl Into<String>) -> Self {
+        Error::Config { message: message.into(), source: None }
+    }
+
+    /// Sets the underlying error, which is reported as the cause
+    pub fn with_source(mut self, error: impl Into<BoxError>) -> Self {
+        let (Error::Io { source, .. } | Error::Parse { source, .. }
+            | Error::Format { source, .. } | Error::Config { source, .. }) = &mut self;
+        *source = Some(error.into());
+        self
+    }
+
+    /// Returns the code the process exits with, following `sysexits.h`
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Io { .. } => 74,
+            Error::Parse { .. } => 65,
+            Error::Format { .. } => 64,
+            Error::Config { .. } => 78,
+        }
+    }
+
+    /// Returns the message followed by its causes, one per line
+    pub fn report(&self) -> String {
+        let mut report = format!("Error: {}", self);
+        let mut cause = std::error::Error::source(self);
+        while let Some(error) = cause {
+            report.push_str(&format!("\n  Caused by: {}", error));
+            cause = error.source();
+        }
+
+        report
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::io("Unable to read or write").with_source(error)
+    }
+}
+
+/// Conversion of the errors of any result into a category, with a message describing what
+/// failed, e.g. `fs::read_to_string(path).io_error("Unable to read the pattern")?`
+pub trait ResultExt<T> {
+    fn io_error(self, message: impl Into<String>) -> Result<T, Error>;
+    fn parse_error(self, message: impl Into<String>) -> Result<T, Error>;
+    fn format_error(self, message: impl Into<String>) -> Result<T, Error>;
+    fn config_error(self, message: impl Into<String>) -> Result<T, Error>;
+}
+
+impl<T, E: Into<BoxError>> ResultExt<T> for Result<T, E> {
+    fn io_error(self, message: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|error| Error::io(message).with_source(error))
+    }
+
+    fn parse_error(self, message: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|error| Error::parse(message).with_source(error))
+    }
+
+    fn format_error(self, message: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|error| Error::format(message).with_source(error))
+    }
+
+    fn config_error(self, message: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|error| Error::config(message).with_source(error))
+    }
+}
+
+/// Reports the result of a binary: errors are written to the standard error with their causes,
+/// and turned into the exit code of their category
+pub fn report(result: Result<(), Error>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error.report());
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
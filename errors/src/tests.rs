@@ -0,0 +1,36 @@
+use std::io;
+
+use crate::*;
+
+#[test]
+fn categories() {
+    let errors = [Error::io("a"), Error::parse("b"), Error::format("c"), Error::config("d")];
+    let codes: Vec<u8> = errors.iter().map(Error::exit_code).collect();
+    assert_eq!(codes, vec![74, 65, 64, 78]);
+    assert_eq!(errors[1].to_string(), "b");
+}
+
+#[test]
+fn causes() {
+    let error = Error::from(io::Error::new(io::ErrorKind::NotFound, "missing.rle"));
+    assert!(matches!(error, Error::Io { .. }));
+    assert_eq!(error.report(), "Error: Unable to read or write\n  Caused by: missing.rle");
+
+    // Causes are reported down to the first error
+    let nested = Error::config("Invalid configuration").with_source(Error::parse("Invalid rule").with_source("B9"));
+    assert_eq!(nested.report(), "Error: Invalid configuration\n  Caused by: Invalid rule\n  Caused by: B9");
+}
+
+#[test]
+fn result_conversions() {
+    let parsed: Result<u32, _> = "x".parse::<u32>().parse_error("Invalid amount of rows");
+    let error = parsed.unwrap_err();
+    assert_eq!(error.exit_code(), 65);
+    assert!(error.report().starts_with("Error: Invalid amount of rows\n  Caused by: invalid digit"));
+
+    let written: Result<(), Error> = Err(io::Error::from(io::ErrorKind::PermissionDenied)).io_error("Unable to write maze.svg");
+    assert_eq!(written.unwrap_err().to_string(), "Unable to write maze.svg");
+
+    assert!(Ok::<_, io::Error>(3).format_error("unused").is_ok());
+    assert!(matches!(Err::<(), _>("bad").config_error("Invalid theme"), Err(Error::Config { .. })));
+}
@@ -0,0 +1,307 @@
+//! Two dimensional grids, so the crates of the workspace don't repeat the math that maps
+//! positions to indices.
+//!
+//! * [Grid] is a dense grid of a fixed [Size], with its cells stored row by row from the top
+//!   left corner. Positions are unsigned, with rows growing downwards as on a screen.
+//! * [SparseGrid] only keeps the cells that were set, at any [Coord] of the plane, and tracks
+//!   their [Bounds].
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+#[cfg(test)]
+mod tests;
+
+/// Position within a dense grid, counted from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position {
+    pub column: usize,
+    pub row: usize,
+}
+
+impl Position {
+    pub fn new(column: usize, row: usize) -> Self {
+        Position { column, row }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{})", self.column, self.row)
+    }
+}
+
+/// Width and height of a dense grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Size {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Size {
+    pub fn new(width: usize, height: usize) -> Self {
+        Size { width, height }
+    }
+
+    /// Returns the amount of cells
+    ///
+    /// # Panics
+    /// Will panic if the amount doesn't fit in an usize.
+    pub fn area(&self) -> usize {
+        self.width.checked_mul(self.height).expect("width * height results in overflow")
+    }
+
+    /// Returns true if the position is within the grid
+    #[inline]
+    pub fn contains(&self, position: Position) -> bool {
+        position.column < self.width && position.row < self.height
+    }
+
+    /// Returns the index of a position in a grid stored row by row, or None if the position is
+    /// outside of it
+    #[inline]
+    pub fn index(&self, position: Position) -> Option<usize> {
+        self.contains(position).then(|| position.row * self.width + position.column)
+    }
+
+    /// Returns the position of an index in a grid stored row by row, or None if the index is
+    /// outside of it
+    #[inline]
+    pub fn position(&self, index: usize) -> Option<Position> {
+        (index < self.area()).then(|| Position::new(index % self.width, index / self.width))
+    }
+
+    /// Returns every position, row by row from the top left corner
+    pub fn positions(&self) -> impl Iterator<Item=Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| Position::new(column, row)))
+    }
+}
+
+/// A dense grid of cells. Grids may be empty, with a width or height of 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    size: Size,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid with every cell set to the value
+    pub fn new(size: Size, value: T) -> Self {
+        Grid { size, cells: vec![value; size.area()] }
+    }
+
+    /// Sets every cell to the value
+    pub fn fill(&mut self, value: T) {
+        self.cells.fill(value);
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from its cells, row by row. Returns None if the amount of cells doesn't
+    /// match the size.
+    pub fn from_vec(size: Size, cells: Vec<T>) -> Option<Self> {
+        (cells.len() == size.area()).then_some(Grid { size, cells })
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn width(&self) -> usize {
+        self.size.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.size.height
+    }
+
+    /// Returns a cell, or None if the position is outside of the grid
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.cells.get(self.size.index(position)?)
+    }
+
+    /// Returns a cell to change, or None if the position is outside of the grid
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        let index = self.size.index(position)?;
+        self.cells.get_mut(index)
+    }
+
+    /// Changes a cell, returning its previous value. Returns None and leaves the grid as it is
+    /// if the position is outside of it.
+    pub fn set(&mut self, position: Position, value: T) -> Option<T> {
+        self.get_mut(position).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// Returns the cells, row by row from the top left corner
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Returns the rows, from the top. Each row contains its cells from left to right.
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        // Empty grids have no rows, even if their height is not 0
+        self.cells.chunks(self.size.width.max(1))
+    }
+
+    /// Returns every cell along with its position, row by row from the top left corner
+    pub fn iter(&self) -> impl Iterator<Item=(Position, &T)> {
+        self.size.positions().zip(self.cells.iter())
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    /// # Panics
+    /// Will panic if the position is outside of the grid.
+    fn index(&self, position: Position) -> &Self::Output {
+        self.get(position).unwrap_or_else(|| panic!("position {} outside of the grid", position))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    /// # Panics
+    /// Will panic if the position is outside of the grid.
+    fn index_mut(&mut self, position: Position) -> &mut Self::Output {
+        self.get_mut(position).unwrap_or_else(|| panic!("position {} outside of the grid", position))
+    }
+}
+
+/// Coordinate on an unbounded plane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Coord {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Coord {
+    pub fn new(x: i32, y: i32) -> Self {
+        Coord { x, y }
+    }
+}
+
+impl Display for Coord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+/// Smallest rectangle that contains some coordinates, with both corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub min: Coord,
+    pub max: Coord,
+}
+
+impl Bounds {
+    /// Returns the bounds of a single coordinate
+    pub fn at(coord: Coord) -> Self {
+        Bounds { min: coord, max: coord }
+    }
+
+    /// Returns the bounds of the coordinates, or None if there are none
+    pub fn from_coords(coords: impl IntoIterator<Item=Coord>) -> Option<Self> {
+        let mut coords = coords.into_iter();
+        let first = coords.next()?;
+
+        Some(coords.fold(Bounds::at(first), Bounds::including))
+    }
+
+    /// Returns the bounds extended to contain the coordinate
+    pub fn including(self, coord: Coord) -> Self {
+        Bounds {
+            min: Coord::new(self.min.x.min(coord.x), self.min.y.min(coord.y)),
+            max: Coord::new(self.max.x.max(coord.x), self.max.y.max(coord.y)),
+        }
+    }
+
+    /// Returns true if the coordinate is within the bounds
+    pub fn contains(&self, coord: Coord) -> bool {
+        (self.min.x..=self.max.x).contains(&coord.x) && (self.min.y..=self.max.y).contains(&coord.y)
+    }
+
+    /// Returns the amount of columns within the bounds
+    pub fn width(&self) -> usize {
+        self.max.x.abs_diff(self.min.x) as usize + 1
+    }
+
+    /// Returns the amount of rows within the bounds
+    pub fn height(&self) -> usize {
+        self.max.y.abs_diff(self.min.y) as usize + 1
+    }
+}
+
+/// A grid that only keeps the cells that were set, at any coordinate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Coord, T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid { cells: HashMap::new() }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        SparseGrid::default()
+    }
+
+    /// Returns the amount of cells that were set
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.cells.get(&coord)
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.cells.get_mut(&coord)
+    }
+
+    /// Sets a cell, returning its previous value
+    pub fn set(&mut self, coord: Coord, value: T) -> Option<T> {
+        self.cells.insert(coord, value)
+    }
+
+    /// Removes a cell, returning its value
+    pub fn remove(&mut self, coord: Coord) -> Option<T> {
+        self.cells.remove(&coord)
+    }
+
+    /// Returns every cell along with its coordinate, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item=(Coord, &T)> {
+        self.cells.iter().map(|(coord, value)| (*coord, value))
+    }
+
+    /// Returns the bounds of the cells, or None if there are none
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::from_coords(self.cells.keys().copied())
+    }
+
+    /// Copies the cells within the bounds into a dense grid, with the top left corner at the
+    /// minimum coordinate. Cells that were not set get the default value.
+    pub fn to_grid(&self, bounds: Bounds) -> Grid<T> where T: Clone + Default {
+        let mut grid = Grid::new(Size::new(bounds.width(), bounds.height()), T::default());
+        for (coord, value) in self.iter().filter(|(coord, _)| bounds.contains(*coord)) {
+            let position = Position::new(coord.x.abs_diff(bounds.min.x) as usize, coord.y.abs_diff(bounds.min.y) as usize);
+            grid[position] = value.clone();
+        }
+
+        grid
+    }
+}
+
+impl<T> FromIterator<(Coord, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item=(Coord, T)>>(iter: I) -> Self {
+        SparseGrid { cells: iter.into_iter().collect() }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::*;
+
+#[test]
+fn size_indices() {
+    let size = Size::new(3, 2);
+    assert_eq!(size.area(), 6);
+    assert_eq!(size.index(Position::new(2, 1)), Some(5));
+    assert_eq!(size.index(Position::new(3, 0)), None);
+    assert_eq!(size.index(Position::new(0, 2)), None);
+    assert_eq!(size.position(4), Some(Position::new(1, 1)));
+    assert_eq!(size.position(6), None);
+
+    // Every index maps back to its position
+    for (index, position) in size.positions().enumerate() {
+        assert_eq!(size.index(position), Some(index));
+        assert_eq!(size.position(index), Some(position));
+    }
+
+    // Empty sizes have no positions
+    assert_eq!(Size::new(0, 4).positions().count(), 0);
+    assert_eq!(Size::new(0, 4).position(0), None);
+}
+
+#[test]
+fn dense_grid() {
+    let mut grid = Grid::new(Size::new(3, 2), '.');
+    assert_eq!(grid.set(Position::new(1, 0), 'x'), Some('.'));
+    grid[Position::new(2, 1)] = 'o';
+    assert_eq!(grid.set(Position::new(3, 1), 'x'), None);
+    assert_eq!(grid.get(Position::new(1, 0)), Some(&'x'));
+    assert_eq!(grid.get(Position::new(0, 2)), None);
+
+    let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+    assert_eq!(rows, vec![".x.", "..o"]);
+    assert_eq!(grid.iter().filter(|(_, &cell)| cell != '.').map(|(position, _)| position).collect::<Vec<_>>(),
+               vec![Position::new(1, 0), Position::new(2, 1)]);
+
+    grid.fill('.');
+    assert!(grid.cells().iter().all(|&cell| cell == '.'));
+
+    assert!(Grid::from_vec(Size::new(2, 2), vec![1, 2, 3]).is_none());
+    let grid = Grid::from_vec(Size::new(2, 2), vec![1, 2, 3, 4]).unwrap();
+    assert_eq!(grid[Position::new(0, 1)], 3);
+
+    // Empty grids have no rows
+    let empty = Grid::new(Size::new(0, 3), false);
+    assert_eq!(empty.rows().count(), 0);
+    assert_eq!(empty.iter().count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn grid_out_of_bounds() {
+    let grid = Grid::new(Size::new(2, 2), 0);
+    let _ = grid[Position::new(2, 0)];
+}
+
+#[test]
+fn sparse_grid() {
+    let mut grid: SparseGrid<u8> = [(Coord::new(-2, 5), 1), (Coord::new(3, -1), 2)].into_iter().collect();
+    assert_eq!(grid.len(), 2);
+    assert_eq!(grid.set(Coord::new(0, 0), 3), None);
+    assert_eq!(grid.set(Coord::new(0, 0), 4), Some(3));
+    assert_eq!(grid.get(Coord::new(3, -1)), Some(&2));
+
+    let bounds = grid.bounds().unwrap();
+    assert_eq!(bounds, Bounds { min: Coord::new(-2, -1), max: Coord::new(3, 5) });
+    assert_eq!((bounds.width(), bounds.height()), (6, 7));
+    assert!(bounds.contains(Coord::new(0, 0)));
+    assert!(!bounds.contains(Coord::new(4, 0)));
+
+    let dense = grid.to_grid(bounds);
+    assert_eq!(dense.size(), Size::new(6, 7));
+    assert_eq!(dense[Position::new(0, 6)], 1);
+    assert_eq!(dense[Position::new(5, 0)], 2);
+    assert_eq!(dense[Position::new(2, 1)], 4);
+    assert_eq!(dense.cells().iter().filter(|&&cell| cell != 0).count(), 3);
+
+    assert_eq!(grid.remove(Coord::new(0, 0)), Some(4));
+    grid.remove(Coord::new(-2, 5));
+    grid.remove(Coord::new(3, -1));
+    assert!(grid.is_empty());
+    assert_eq!(grid.bounds(), None);
+}
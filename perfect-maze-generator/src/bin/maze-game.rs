@@ -0,0 +1,37 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use errors::Error;
+
+use perfect_maze_generator::PerfectMaze;
+use perfect_maze_generator::game::MazeGame;
+
+/// Walk through a perfect maze in the terminal, from its top left corner to the exit in the
+/// bottom right one.
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Amount of rows to use. Cannot be 0.
+    #[arg(long, short, default_value_t = 10)]
+    rows: usize,
+
+    /// Amount of columns to use. Cannot be 0.
+    #[arg(long, short, default_value_t = 20)]
+    columns: usize,
+
+    /// Seed for randomizing the maze. A seed of 0 means no randomization is done.
+    #[arg(long, short)]
+    seed: Option<u64>,
+}
+
+fn main() -> ExitCode {
+    errors::report(run(Cli::parse()))
+}
+
+/// Plays the maze given by the arguments
+fn run(args: Cli) -> Result<(), Error> {
+    let maze = PerfectMaze::try_new(args.columns, args.rows, args.seed)
+        .ok_or_else(|| Error::parse("The maze needs at least one row and one column"))?;
+    MazeGame::new(maze).run()?;
+
+    Ok(())
+}
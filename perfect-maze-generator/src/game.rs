@@ -0,0 +1,337 @@
+//! A game in the terminal where the player walks through a maze, from its top left corner to the
+//! exit in the bottom right one. It runs on the same user interface blocks as the Game of Life:
+//! moves are commands, typed in the input box or bound to the arrows and `w`, `a`, `s`, `d`.
+
+use std::io;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use grid2d::Position;
+use ratatui::layout::{Constraint, Direction as LayoutDirection, Layout};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use render::text::{self, Style};
+use tui_app::ApplicationError;
+use tui_app::editor::{split_last_word, tokenize, Completion};
+use tui_app::input::{self, InputEvent, Timing};
+use tui_app::keys::{self, KeyBindings};
+use tui_app::terminal::{self, Tui};
+
+use crate::PerfectMaze;
+
+/// Characters drawn on the cells of the player and the exit
+const PLAYER: char = '@';
+const EXIT: char = '*';
+
+/// Commands understood by the game, in the order they are completed
+const COMMANDS: [&str; 7] = ["up", "down", "left", "right", "new", "restart", "quit"];
+
+/// Keys bound to the moves
+const DEFAULT_KEY_BINDINGS: [(&str, &str); 8] = [
+    ("up", "up"),
+    ("down", "down"),
+    ("left", "left"),
+    ("right", "right"),
+    ("w", "up"),
+    ("s", "down"),
+    ("a", "left"),
+    ("d", "right"),
+];
+
+/// The clock of the game only counts seconds
+const TICK: Duration = Duration::from_secs(1);
+const FRAME: Duration = Duration::from_millis(50);
+
+/// Directions in which the player moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Represents an event happening within the game.
+#[derive(Debug, PartialEq)]
+pub enum GameEvent {
+    Move(Direction),
+    /// Start a new maze, optionally with other columns and rows, and a seed
+    New(Option<(usize, usize)>, Option<u64>),
+    /// Go back to the start of the current maze
+    Restart,
+    PartialInput(String, usize),
+    Message(String),
+    Tick,
+    Frame,
+    Quit,
+}
+
+impl From<InputEvent> for GameEvent {
+    fn from(event: InputEvent) -> Self {
+        match event {
+            InputEvent::Command(input) => parse(&input),
+            InputEvent::Edit(input, cursor) => GameEvent::PartialInput(input, cursor),
+            InputEvent::Message(message) => GameEvent::Message(message),
+            InputEvent::Tick => GameEvent::Tick,
+            InputEvent::Frame => GameEvent::Frame,
+            InputEvent::Quit => GameEvent::Quit,
+        }
+    }
+}
+
+/// Parses a command typed by the user. Invalid commands become a message explaining them.
+pub fn parse(input: &str) -> GameEvent {
+    let words = match tokenize(input) {
+        Ok(words) => words,
+        Err(message) => return GameEvent::Message(message),
+    };
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let Some((&instruction, arguments)) = words.split_first() else {
+        return GameEvent::PartialInput(String::new(), 0);
+    };
+
+    match (instruction, arguments) {
+        ("up", []) => GameEvent::Move(Direction::Up),
+        ("down", []) => GameEvent::Move(Direction::Down),
+        ("left", []) => GameEvent::Move(Direction::Left),
+        ("right", []) => GameEvent::Move(Direction::Right),
+        ("restart", []) => GameEvent::Restart,
+        ("quit", []) => GameEvent::Quit,
+        ("new", arguments) => parse_new(arguments),
+        _ => GameEvent::Message(format!("Unknown command: {}", input.trim())),
+    }
+}
+
+/// Parses the arguments of `new [columns rows] [seed]`
+fn parse_new(arguments: &[&str]) -> GameEvent {
+    let numbers: Option<Vec<u64>> = arguments.iter().map(|a| a.parse().ok()).collect();
+    let usage = || GameEvent::Message(String::from("Usage: new [columns rows] [seed], with columns and rows over 0"));
+
+    match numbers.as_deref() {
+        Some([]) => GameEvent::New(None, None),
+        Some(&[seed]) => GameEvent::New(None, Some(seed)),
+        Some(&[columns, rows, ref seed @ ..]) if columns > 0 && rows > 0 && seed.len() <= 1 =>
+            GameEvent::New(Some((columns as usize, rows as usize)), seed.first().copied()),
+        _ => usage(),
+    }
+}
+
+/// Completes the names of the commands
+pub fn complete(input: &str) -> Completion {
+    let (head, word) = split_last_word(input);
+    let candidates = if head.trim().is_empty() {
+        COMMANDS.iter().filter(|c| c.starts_with(word)).map(|c| c.to_string()).collect()
+    } else {
+        Vec::new()
+    };
+
+    Completion::new(head, word, candidates, "")
+}
+
+/// State of a game, without the user interface
+#[derive(Debug)]
+pub struct Game {
+    maze: PerfectMaze,
+    player: Position,
+    moves: usize,
+    /// Seconds since the first move
+    seconds: u64,
+}
+
+impl Game {
+    /// Starts a game at the top left corner of the maze
+    pub fn new(maze: PerfectMaze) -> Self {
+        Game { maze, player: Position::new(0, 0), moves: 0, seconds: 0 }
+    }
+
+    pub fn maze(&self) -> &PerfectMaze {
+        &self.maze
+    }
+
+    /// Returns the cell of the player
+    pub fn player(&self) -> Position {
+        self.player
+    }
+
+    /// Returns the cell the player has to reach
+    pub fn exit(&self) -> Position {
+        Position::new(self.maze.columns() - 1, self.maze.rows() - 1)
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.player == self.exit()
+    }
+
+    /// Moves the player unless a wall is in the way or the maze is solved. Returns true if the
+    /// player moved.
+    pub fn step(&mut self, direction: Direction) -> bool {
+        if self.is_solved() {
+            return false;
+        }
+
+        let Position { column, row } = self.player;
+        let maze = &self.maze;
+        let next = match direction {
+            Direction::Right if maze.get_right_wall(row, column) == Some(false) => Position::new(column + 1, row),
+            Direction::Down if maze.get_bottom_wall(row, column) == Some(false) => Position::new(column, row + 1),
+            Direction::Left if column > 0 && maze.get_right_wall(row, column - 1) == Some(false) => Position::new(column - 1, row),
+            Direction::Up if row > 0 && maze.get_bottom_wall(row - 1, column) == Some(false) => Position::new(column, row - 1),
+            _ => return false,
+        };
+
+        self.player = next;
+        self.moves += 1;
+        true
+    }
+
+    /// Counts a second, while the player walks the maze
+    pub fn tick(&mut self) {
+        if self.moves > 0 && !self.is_solved() {
+            self.seconds += 1;
+        }
+    }
+
+    /// Goes back to the start of the maze
+    pub fn restart(&mut self) {
+        self.player = Position::new(0, 0);
+        self.moves = 0;
+        self.seconds = 0;
+    }
+
+    /// Returns the maze drawn with box drawing characters, along with the player and the exit
+    pub fn render(&self) -> String {
+        let mut lines: Vec<Vec<char>> = text::walls(&self.maze.scene(), Style::Unicode)
+            .lines()
+            .map(|line| line.chars().collect())
+            .collect();
+
+        // Cells are between the lines of walls
+        for (position, symbol) in [(self.exit(), EXIT), (self.player, PLAYER)] {
+            lines[2 * position.row + 1][2 * position.column + 1] = symbol;
+        }
+
+        lines.into_iter().map(|line| line.into_iter().collect::<String>() + "\n").collect()
+    }
+
+    /// Returns the status of the game, as shown under the maze
+    pub fn status(&self) -> String {
+        if self.is_solved() {
+            format!("Solved in {} moves and {}s! Type new for another maze", self.moves, self.seconds)
+        } else {
+            format!("Moves: {} | Time: {}s | Seed: {}", self.moves, self.seconds, self.maze.seed())
+        }
+    }
+}
+
+/// Terminal application that plays the game
+pub struct MazeGame {
+    game: Game,
+    key_bindings: KeyBindings,
+
+    // Input box
+    input: String,
+    cursor: usize,
+    message: String,
+}
+
+impl MazeGame {
+    /// Creates the application with the maze to play
+    pub fn new(maze: PerfectMaze) -> Self {
+        MazeGame {
+            game: Game::new(maze),
+            key_bindings: keys::bindings(&DEFAULT_KEY_BINDINGS),
+            input: String::default(),
+            cursor: 0,
+            message: String::from("Reach the * with the arrows or w, a, s, d. Esc quits"),
+        }
+    }
+
+    /// Starts the application loop
+    pub fn run(&mut self) -> Result<(), ApplicationError> {
+        let mut terminal = terminal::setup()?;
+        let (tx, rx) = mpsc::channel();
+
+        let timing = Arc::new(Timing::new(TICK, FRAME));
+        let input_thread = input::spawn(Arc::clone(&timing), self.key_bindings.clone(), Box::new(complete), tx);
+
+        loop {
+            self.draw(&mut terminal)?;
+
+            match rx.recv()? {
+                GameEvent::Quit => break,
+                GameEvent::Tick => {
+                    self.game.tick();
+                    timing.tick_done();
+                }
+                GameEvent::Frame => timing.frame_done(),
+                event => self.handle_event(event),
+            }
+        }
+
+        terminal::cleanup(&mut terminal)?;
+        drop(rx);
+        input_thread.join().expect("Error closing input");
+
+        Ok(())
+    }
+
+    /// Applies an event that changes the game or the input box
+    fn handle_event(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::Move(direction) => {
+                if !self.game.step(direction) && !self.game.is_solved() {
+                    self.message = String::from("There is a wall in the way");
+                } else {
+                    self.message.clear();
+                }
+            }
+            GameEvent::New(size, seed) => {
+                let (columns, rows) = size.unwrap_or((self.game.maze().columns(), self.game.maze().rows()));
+                self.game = Game::new(PerfectMaze::new(columns, rows, seed));
+                self.message = format!("New {}x{} maze", columns, rows);
+            }
+            GameEvent::Restart => {
+                self.game.restart();
+                self.message.clear();
+            }
+            GameEvent::PartialInput(input, cursor) => {
+                self.input = input;
+                self.cursor = cursor;
+                self.message.clear();
+            }
+            GameEvent::Message(message) => self.message = message,
+            GameEvent::Tick | GameEvent::Frame | GameEvent::Quit => {}
+        }
+    }
+
+    /// Draws the maze, the status line and the input box
+    fn draw(&self, terminal: &mut Tui) -> io::Result<()> {
+        terminal.draw(|rect| {
+            let chunks = Layout::default()
+                .direction(LayoutDirection::Vertical)
+                .constraints([
+                    Constraint::Min(3),
+                    Constraint::Length(1),
+                    Constraint::Length(4)
+                ].as_ref())
+                .split(rect.size());
+
+            rect.render_widget(Paragraph::new(self.game.render()), chunks[0]);
+            rect.render_widget(Paragraph::new(self.game.status()), chunks[1]);
+
+            let input_block = Paragraph::new(format!("{}\n{}", self.input, self.message))
+                .block(Block::default().title("Input").borders(Borders::ALL));
+            rect.render_widget(input_block, chunks[2]);
+            rect.set_cursor(chunks[2].x + 1 + self.cursor as u16, chunks[2].y + 1);
+        })?;
+
+        Ok(())
+    }
+}
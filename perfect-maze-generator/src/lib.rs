@@ -1,62 +1,33 @@
 use std::collections::HashSet;
-use std::fmt::{Display, Formatter, Write};
+use std::fmt::{Display, Formatter};
 use std::mem::swap;
+use grid2d::{Grid, Position, Size};
 use rand::prelude::*;
 use rand_xoshiro::Xoshiro256StarStar as RandomGenerator;
+use render::text::{self, Style};
+use render::{CellWalls, WallScene};
 
 #[cfg(test)]
 mod tests;
 
+/// Game in the terminal where the player walks through the maze
+#[cfg(feature = "tui")]
+pub mod game;
+
 #[derive(Debug)]
 pub struct PerfectMaze {
     columns: usize,
     rows: usize,
     seed: u64,
-    walls: Vec<bool>,
+    /// Walls between each cell and the next one to the right, except in the last column
+    right_walls: Grid<bool>,
+    /// Walls between each cell and the next one below, except in the last row
+    bottom_walls: Grid<bool>,
 }
 
 impl Display for PerfectMaze {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let vertical_walls = self.columns - 1;
-        let horizontal_walls = self.columns;
-
-        // Maze characters
-        const H_WALL: char = '_';
-        const V_WALL: char = '|';
-        const EMPTY: char = ' ';
-        const NEWLINE: char = '\n';
-
-        // Top row
-        let top_wall_size = vertical_walls + horizontal_walls + 2;
-        for _ in 0..top_wall_size {
-            f.write_char(H_WALL)?;
-        }
-        f.write_char(NEWLINE)?;
-
-        // Rows
-        for row in 0..self.rows {
-            f.write_char(V_WALL)?;
-
-            for column in 0..self.columns {
-                // Bottom wall
-                if self.get_bottom_wall(row, column).unwrap() {
-                    f.write_char(H_WALL)?;
-                } else {
-                    f.write_char(EMPTY)?;
-                }
-
-                // Right wall
-                if self.get_right_wall(row, column).unwrap() {
-                    f.write_char(V_WALL)?;
-                } else {
-                    f.write_char(EMPTY)?;
-                }
-            }
-            f.write_char(NEWLINE)?;
-        }
-
-
-        Ok(())
+        text::write_walls(f, &self.scene(), Style::Ascii)
     }
 }
 
@@ -79,6 +50,17 @@ impl PerfectMaze {
         assert_ne!(columns, 0);
         assert_ne!(rows, 0);
 
+        Self::generate(columns, rows, seed)
+    }
+
+    /// Creates a new maze like [PerfectMaze::new], returning None instead of panicking if
+    /// `width` or `height` is 0.
+    pub fn try_new(columns: usize, rows: usize, seed: Option<u64>) -> Option<Self> {
+        (columns != 0 && rows != 0).then(|| Self::generate(columns, rows, seed))
+    }
+
+    /// Generates a maze with valid dimensions
+    fn generate(columns: usize, rows: usize, seed: Option<u64>) -> Self {
         // Generate seed
         let seed = seed.unwrap_or_else(|| {
             let mut generator = rand::thread_rng();
@@ -86,29 +68,35 @@ impl PerfectMaze {
         });
 
         // Set walls (and fill with true)
-        let total_walls = (columns - 1) * rows + (rows - 1) * columns;
-        let walls = vec![true; total_walls];
+        let right_walls = Grid::new(Size::new(columns - 1, rows), true);
+        let bottom_walls = Grid::new(Size::new(columns, rows - 1), true);
+        let mut maze = PerfectMaze { columns, rows, seed, right_walls, bottom_walls };
 
-        // Create the list of wall indices
+        // Create the list of walls
         // Do not randomize walls if seed is zero
-        let mut wall_indices: Vec<usize> = (0..total_walls).collect();
+        let mut walls = maze.walls();
         if seed != 0 {
             let mut generator = RandomGenerator::seed_from_u64(seed);
-            wall_indices.shuffle(&mut generator);
+            walls.shuffle(&mut generator);
         }
 
         // Create
-        let mut maze = PerfectMaze { columns, rows, seed, walls };
-        maze.tumble_walls(&wall_indices);
+        maze.tumble_walls(&walls);
 
         maze
     }
 
+    /// Returns every inner wall, row by row. The right walls of a row come before its bottom walls.
+    fn walls(&self) -> Vec<Wall> {
+        let right = self.right_walls.size().positions().map(Wall::Right);
+        let bottom = self.bottom_walls.size().positions().map(Wall::Bottom);
 
-    /// Returns the amount of walls in a row (both horizontal + vertical)
-    #[inline]
-    fn walls_per_row(&self) -> usize {
-        2 * self.columns - 1
+        let mut walls: Vec<Wall> = right.chain(bottom).collect();
+        walls.sort_by_key(|wall| match wall {
+            Wall::Right(position) => (position.row, 0, position.column),
+            Wall::Bottom(position) => (position.row, 1, position.column),
+        });
+        walls
     }
 
     /// Validates if the current cell is valid
@@ -127,13 +115,7 @@ impl PerfectMaze {
         self.is_valid_cell(row, column)?;
 
         // If we are in the last column, the right wall is always up
-        if column == self.columns - 1 {
-            return Some(true);
-        }
-
-        // Find the wall id and return the status
-        let wall_id = row * self.walls_per_row() + column;
-        Some(self.walls[wall_id])
+        Some(self.right_walls.get(Position::new(column, row)).copied().unwrap_or(true))
     }
 
     /// Returns the status of the bottom wall of the cell. If the cell is not valid then None
@@ -142,13 +124,15 @@ impl PerfectMaze {
         self.is_valid_cell(row, column)?;
 
         // If we are in the last row, the bottom wall is always up
-        if row == self.rows - 1 {
-            return Some(true);
-        }
+        Some(self.bottom_walls.get(Position::new(column, row)).copied().unwrap_or(true))
+    }
 
-        // Find the wall id and return the status
-        let wall_id = row * self.walls_per_row() + (self.columns - 1) + column;
-        Some(self.walls[wall_id])
+    /// Returns the walls of the maze, to render it in other formats than text
+    pub fn scene(&self) -> WallScene {
+        WallScene::from_fn(Size::new(self.columns, self.rows), |cell| CellWalls {
+            right: self.get_right_wall(cell.row, cell.column).unwrap(),
+            bottom: self.get_bottom_wall(cell.row, cell.column).unwrap(),
+        })
     }
 
     /// Returns the number of columns in the maze (a.k.a. width)
@@ -167,21 +151,10 @@ impl PerfectMaze {
     }
 
     /// Returns the cell pair that is separated by the given wall
-    fn cell_pair_from_wall(&self, wall_id: usize) -> (MazeCell, MazeCell) {
-        let current_row = wall_id / self.walls_per_row();
-        let wall_in_row = wall_id % self.walls_per_row();
-        let is_vertical = wall_in_row < (self.columns() - 1);
-        let total_columns = self.columns();
-
-        if is_vertical {
-            let cell_a = MazeCell { row: current_row, column: wall_in_row, total_columns };
-            let cell_b = MazeCell { row: current_row, column: wall_in_row + 1, total_columns };
-            (cell_a, cell_b)
-        } else {
-            let column = wall_in_row - (self.columns() - 1);
-            let cell_a = MazeCell { row: current_row, column, total_columns };
-            let cell_b = MazeCell { row: current_row + 1, column, total_columns };
-            (cell_a, cell_b)
+    fn cell_pair_from_wall(wall: Wall) -> (Position, Position) {
+        match wall {
+            Wall::Right(cell) => (cell, Position::new(cell.column + 1, cell.row)),
+            Wall::Bottom(cell) => (cell, Position::new(cell.column, cell.row + 1)),
         }
     }
 
@@ -195,26 +168,29 @@ impl PerfectMaze {
     }
 
     /// Applies the wall tumbling algorithm to the list of walls
-    fn tumble_walls(&mut self, wall_indices: &[usize]) {
+    fn tumble_walls(&mut self, walls: &[Wall]) {
         // Initialize sets
-        let total_cells = self.rows() * self.columns();
-        let mut cell_sets = Vec::with_capacity(total_cells);
-        for index in 0..total_cells {
+        let cells = Size::new(self.columns(), self.rows());
+        let mut cell_sets = Vec::with_capacity(cells.area());
+        for index in 0..cells.area() {
             let set = HashSet::from([index; 1]);
             cell_sets.push(set);
         }
 
-        // Iterate through the wall indices
-        for current_wall in wall_indices {
-            let (cell_a, cell_b) = self.cell_pair_from_wall(*current_wall);
+        // Iterate through the walls
+        for &current_wall in walls {
+            let (cell_a, cell_b) = Self::cell_pair_from_wall(current_wall);
 
             // Search the set of each cell
-            let mut id_set_a = Self::get_set_with_cell(&cell_sets, cell_a.id()).unwrap();
-            let mut id_set_b = Self::get_set_with_cell(&cell_sets, cell_b.id()).unwrap();
+            let mut id_set_a = Self::get_set_with_cell(&cell_sets, cells.index(cell_a).unwrap()).unwrap();
+            let mut id_set_b = Self::get_set_with_cell(&cell_sets, cells.index(cell_b).unwrap()).unwrap();
 
             if id_set_a != id_set_b {
                 // Wall can be tumbled
-                self.walls[*current_wall] = false;
+                match current_wall {
+                    Wall::Right(cell) => self.right_walls[cell] = false,
+                    Wall::Bottom(cell) => self.bottom_walls[cell] = false,
+                }
 
                 // To remove the sets from the Vec we must make sure that
                 // first we take the one with the largest index. On removal
@@ -231,15 +207,11 @@ impl PerfectMaze {
     }
 }
 
-/// Represents a cell within the Maze.
-#[derive(Debug, PartialEq)]
-struct MazeCell {
-    row: usize,
-    column: usize,
-    total_columns: usize,
-}
-
-impl MazeCell {
-    /// Returns the ID of the cell within the maze
-    fn id(&self) -> usize { self.row * self.total_columns + self.column }
+/// Represents an inner wall of the Maze, next to the given cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Wall {
+    /// Wall to the right of the cell
+    Right(Position),
+    /// Wall below the cell
+    Bottom(Position),
 }
\ No newline at end of file
@@ -1,4 +1,7 @@
+use std::process::ExitCode;
+
 use clap::Parser;
+use errors::Error;
 
 use perfect_maze_generator as maze_generator;
 
@@ -19,10 +22,12 @@ struct Cli {
     seed: Option<u64>,
 }
 
-fn main() {
+fn main() -> ExitCode {
     // Get CLI arguments
     let args = Cli::parse();
 
-    let maze = maze_generator::PerfectMaze::new(args.columns, args.rows, args.seed);
-    println!("{maze}");
+    let result = maze_generator::PerfectMaze::try_new(args.columns, args.rows, args.seed)
+        .map(|maze| println!("{maze}"))
+        .ok_or_else(|| Error::parse("The maze needs at least one row and one column"));
+    errors::report(result)
 }
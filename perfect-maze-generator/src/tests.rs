@@ -1,4 +1,5 @@
 use crate::*;
+use util::seed::TestSeed;
 
 #[should_panic]
 #[test]
@@ -7,6 +8,15 @@ fn invalid_maze() {
     let _maze = PerfectMaze::new(0, 0, None);
 }
 
+#[test]
+fn fallible_maze() {
+    assert!(PerfectMaze::try_new(0, 5, None).is_none());
+    assert!(PerfectMaze::try_new(5, 0, Some(1)).is_none());
+
+    let maze = PerfectMaze::try_new(2, 3, Some(0)).unwrap();
+    assert_eq!(maze.to_string(), PerfectMaze::new(2, 3, Some(0)).to_string());
+}
+
 #[test]
 fn default_maze() {
     let (columns, rows) = (10, 15);
@@ -56,6 +66,29 @@ fn display_maze() {
     assert_eq!(expected, maze.to_string());
 }
 
+#[test]
+fn random_mazes() {
+    let seed = TestSeed::new("random_mazes");
+    let mut values = seed.values();
+
+    for _ in 0..20 {
+        let columns = (values.next().unwrap() % 12 + 1) as usize;
+        let rows = (values.next().unwrap() % 12 + 1) as usize;
+        let maze = PerfectMaze::new(columns, rows, values.next());
+
+        // A single path between any two cells means the passages form a tree, one less than the cells
+        let passages: usize = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .map(|(row, column)| {
+                let right = column + 1 < columns && !maze.get_right_wall(row, column).unwrap();
+                let bottom = row + 1 < rows && !maze.get_bottom_wall(row, column).unwrap();
+                usize::from(right) + usize::from(bottom)
+            })
+            .sum();
+        assert_eq!(passages, columns * rows - 1, "{}x{} maze with seed {}\n{}", columns, rows, maze.seed(), maze);
+    }
+}
+
 #[test]
 fn internal_values() {
     const COLUMNS: usize = 8;
@@ -63,16 +96,94 @@ fn internal_values() {
     let maze = PerfectMaze::new(COLUMNS, ROWS, None);
 
 
+    // Walls keep the order of each row: right walls first, then bottom walls
     const WALLS_PER_ROW: usize = 2 * COLUMNS - 1;
-    assert_eq!(maze.walls_per_row(), WALLS_PER_ROW);
+    let walls = maze.walls();
+    assert_eq!(walls.len(), WALLS_PER_ROW * ROWS - COLUMNS);
+    assert_eq!(walls[0], Wall::Right(Position::new(0, 0)));
+    assert_eq!(walls[COLUMNS - 1], Wall::Bottom(Position::new(0, 0)));
+    assert_eq!(walls[WALLS_PER_ROW], Wall::Right(Position::new(0, 1)));
 
     // Compare cell positions
-    const C00: MazeCell = MazeCell { row: 0, column: 0, total_columns: COLUMNS };
-    const C01: MazeCell = MazeCell { row: 0, column: 1, total_columns: COLUMNS };
-    const C10: MazeCell = MazeCell { row: 1, column: 0, total_columns: COLUMNS };
-    const C11: MazeCell = MazeCell { row: 1, column: 1, total_columns: COLUMNS };
-    assert_eq!(maze.cell_pair_from_wall(0), (C00, C01));
-    assert_eq!(maze.cell_pair_from_wall(COLUMNS - 1), (C00, C10));
-    assert_eq!(maze.cell_pair_from_wall(WALLS_PER_ROW), (C10, C11));
-    assert_eq!(maze.cell_pair_from_wall(COLUMNS), (C01, C11));
-}
\ No newline at end of file
+    const C00: Position = Position { column: 0, row: 0 };
+    const C01: Position = Position { column: 1, row: 0 };
+    const C10: Position = Position { column: 0, row: 1 };
+    const C11: Position = Position { column: 1, row: 1 };
+    assert_eq!(PerfectMaze::cell_pair_from_wall(walls[0]), (C00, C01));
+    assert_eq!(PerfectMaze::cell_pair_from_wall(walls[COLUMNS - 1]), (C00, C10));
+    assert_eq!(PerfectMaze::cell_pair_from_wall(walls[WALLS_PER_ROW]), (C10, C11));
+    assert_eq!(PerfectMaze::cell_pair_from_wall(walls[COLUMNS]), (C01, C11));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn game_moves() {
+    use crate::game::{Direction, Game};
+
+    // Without randomization the first row is open, and every column goes down from it
+    let mut game = Game::new(PerfectMaze::new(3, 2, Some(0)));
+    assert!(!game.step(Direction::Up));
+    assert!(!game.step(Direction::Left));
+    assert!(game.step(Direction::Right));
+    assert!(game.step(Direction::Down));
+    assert!(!game.step(Direction::Right));
+    assert!(game.step(Direction::Up));
+    assert!(game.step(Direction::Right));
+    assert_eq!(game.player(), Position::new(2, 0));
+
+    game.tick();
+    assert!(game.step(Direction::Down));
+    assert!(game.is_solved());
+    assert_eq!(game.moves(), 5);
+    assert_eq!(game.seconds(), 1);
+
+    // Solved games stop moving and counting time
+    game.tick();
+    assert!(!game.step(Direction::Left));
+    assert_eq!(game.seconds(), 1);
+    assert!(game.status().starts_with("Solved in 5 moves"));
+
+    game.restart();
+    assert_eq!(game.player(), Position::new(0, 0));
+    assert_eq!(game.moves(), 0);
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn game_render() {
+    use crate::game::{Direction, Game};
+
+    let mut game = Game::new(PerfectMaze::new(2, 2, Some(0)));
+    let expected = "\
+┌───┐
+│@  │
+│ ╷ │
+│ │*│
+└─┴─┘
+";
+    assert_eq!(game.render(), expected);
+
+    game.step(Direction::Right);
+    assert!(game.render().starts_with("┌───┐\n│  @│\n"));
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn game_commands() {
+    use crate::game::{complete, parse, Direction, GameEvent};
+
+    assert_eq!(parse("up"), GameEvent::Move(Direction::Up));
+    assert_eq!(parse(" left "), GameEvent::Move(Direction::Left));
+    assert_eq!(parse("new"), GameEvent::New(None, None));
+    assert_eq!(parse("new 7"), GameEvent::New(None, Some(7)));
+    assert_eq!(parse("new 30 15"), GameEvent::New(Some((30, 15)), None));
+    assert_eq!(parse("new 30 15 7"), GameEvent::New(Some((30, 15)), Some(7)));
+    assert!(matches!(parse("new 0 15"), GameEvent::Message(_)));
+    assert!(matches!(parse("new big"), GameEvent::Message(_)));
+    assert!(matches!(parse("jump"), GameEvent::Message(_)));
+    assert!(matches!(parse("new \"3"), GameEvent::Message(_)));
+
+    assert_eq!(complete("re").line, "restart");
+    assert_eq!(complete("r").candidates, vec!["right", "restart"]);
+    assert_eq!(complete("new 3").line, "new 3");
+}
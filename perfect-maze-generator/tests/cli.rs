@@ -0,0 +1,30 @@
+use util::process::CliTest;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_perfect-maze-generator");
+
+#[test]
+fn print_maze() {
+    CliTest::new(BINARY)
+        .args(["--rows", "3", "--columns", "2", "--seed", "0"])
+        .run()
+        .success()
+        .stdout_eq("_____\n|   |\n| | |\n|_|_|\n\n");
+}
+
+#[test]
+fn missing_arguments() {
+    CliTest::new(BINARY)
+        .args(["--rows", "3"])
+        .run()
+        .exit_code(2)
+        .stderr_contains("--columns");
+}
+
+#[test]
+fn empty_maze() {
+    CliTest::new(BINARY)
+        .args(["--rows", "0", "--columns", "2"])
+        .run()
+        .exit_code(65)
+        .stderr_eq("Error: The maze needs at least one row and one column\n");
+}
@@ -0,0 +1,194 @@
+//! Renders the scenes drawn by the crates of the workspace, so the maze and the Game of Life
+//! share their text, vector and image output. There are two kinds of scenes:
+//!
+//! * Cells, given as a [Grid] of `bool` where `true` cells are filled, like the living cells
+//!   of a Life viewport.
+//! * [WallScene]s, where cells are separated by walls, like a maze.
+//!
+//! Both can be written as ASCII or Unicode text ([text]), as SVG ([svg]) or as pixels
+//! ([raster]), which the `png` feature writes as PNG images.
+
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use grid2d::{Grid, Position, Size};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Pixel buffers and PNG images
+pub mod raster;
+
+/// SVG documents
+pub mod svg;
+
+/// ASCII and Unicode text
+pub mod text;
+
+#[derive(Error, Debug)]
+pub enum RenderError {
+    #[error("Unable to write the image")]
+    Io(#[from] io::Error),
+
+    #[cfg(feature = "png")]
+    #[error("Unable to encode the PNG")]
+    Png(#[from] png::EncodingError),
+
+    #[error("The scaled scene is too large for the image format")]
+    TooLarge,
+}
+
+impl From<RenderError> for errors::Error {
+    fn from(error: RenderError) -> Self {
+        let category = match &error {
+            RenderError::Io(_) => errors::Error::io("Unable to write the image"),
+            _ => errors::Error::format("Unable to render the image"),
+        };
+        category.with_source(error)
+    }
+}
+
+/// A color, in RGB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0x00, 0x00, 0x00);
+    pub const WHITE: Color = Color::rgb(0xff, 0xff, 0xff);
+
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Color { red, green, blue }
+    }
+
+    /// Returns the channels, for palettes
+    pub fn channels(&self) -> [u8; 3] {
+        [self.red, self.green, self.blue]
+    }
+
+    /// Returns the perceived brightness, for grayscale images
+    pub fn luma(&self) -> u8 {
+        let luma = 299 * self.red as u32 + 587 * self.green as u32 + 114 * self.blue as u32;
+        (luma / 1000) as u8
+    }
+}
+
+impl Display for Color {
+    /// Writes the color in hexadecimal notation, e.g. `#ff8000`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+/// Inner walls of a cell of a [WallScene]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CellWalls {
+    pub right: bool,
+    pub bottom: bool,
+}
+
+/// Lines meeting at a corner of a [WallScene]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Corner {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+impl Corner {
+    /// Returns true if any wall meets at the corner
+    pub fn is_wall(&self) -> bool {
+        self.up || self.down || self.left || self.right
+    }
+}
+
+/// Cells separated by walls. The border of the scene is always closed, so the walls of the
+/// cells in the last column or row are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WallScene {
+    cells: Grid<CellWalls>,
+}
+
+impl WallScene {
+    pub fn new(cells: Grid<CellWalls>) -> Self {
+        WallScene { cells }
+    }
+
+    /// Creates a scene asking for the walls of each cell
+    pub fn from_fn(size: Size, walls: impl FnMut(Position) -> CellWalls) -> Self {
+        let cells = size.positions().map(walls).collect();
+        WallScene::new(Grid::from_vec(size, cells).expect("a wall for every cell"))
+    }
+
+    /// Returns the amount of cells
+    pub fn size(&self) -> Size {
+        self.cells.size()
+    }
+
+    /// Returns true if there is a wall on top of the cell. The row may be the height of the
+    /// scene, for the bottom border.
+    pub fn horizontal_wall(&self, position: Position) -> bool {
+        let size = self.size();
+        if position.column >= size.width || position.row > size.height {
+            return false;
+        }
+        if position.row == 0 || position.row == size.height {
+            return true;
+        }
+
+        self.cells[Position::new(position.column, position.row - 1)].bottom
+    }
+
+    /// Returns true if there is a wall to the left of the cell. The column may be the width of
+    /// the scene, for the right border.
+    pub fn vertical_wall(&self, position: Position) -> bool {
+        let size = self.size();
+        if position.column > size.width || position.row >= size.height {
+            return false;
+        }
+        if position.column == 0 || position.column == size.width {
+            return true;
+        }
+
+        self.cells[Position::new(position.column - 1, position.row)].right
+    }
+
+    /// Returns the walls meeting at the top left corner of the cell. The column and row may be
+    /// the width and height of the scene, for the corners on the right and bottom borders.
+    pub fn corner(&self, position: Position) -> Corner {
+        let Position { column, row } = position;
+        Corner {
+            up: row > 0 && self.vertical_wall(Position::new(column, row - 1)),
+            down: self.vertical_wall(position),
+            left: column > 0 && self.horizontal_wall(Position::new(column - 1, row)),
+            right: self.horizontal_wall(position),
+        }
+    }
+
+    /// Returns the scene as square blocks, where walls take the same space as cells: each cell
+    /// is surrounded by its corners and walls, in a grid of `2 * width + 1` by
+    /// `2 * height + 1` blocks. Blocks with walls are `true`.
+    pub fn blocks(&self) -> Grid<bool> {
+        let size = self.size();
+        let mut blocks = Grid::new(Size::new(2 * size.width + 1, 2 * size.height + 1), false);
+        for row in 0..=size.height {
+            for column in 0..=size.width {
+                let position = Position::new(column, row);
+                blocks[Position::new(2 * column, 2 * row)] = self.corner(position).is_wall();
+                if self.horizontal_wall(position) {
+                    blocks[Position::new(2 * column + 1, 2 * row)] = true;
+                }
+                if self.vertical_wall(position) {
+                    blocks[Position::new(2 * column, 2 * row + 1)] = true;
+                }
+            }
+        }
+
+        blocks
+    }
+}
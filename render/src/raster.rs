@@ -0,0 +1,98 @@
+//! Scenes drawn as pixels, with one byte per pixel. The bytes are gray levels for grayscale
+//! images or indices for images with a palette, like GIF frames. With the `png` feature they
+//! can be written as PNG images.
+
+#[cfg(feature = "png")]
+use std::io::Write;
+
+use grid2d::{Grid, Size};
+
+use crate::{RenderError, WallScene};
+
+/// Pixels of a rendered scene, row by row from the top left corner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// Draws each cell as a square of `scale` pixels, using the given values for empty and
+    /// filled cells
+    pub fn from_cells(cells: &Grid<bool>, scale: usize, empty: u8, filled: u8) -> Result<Self, RenderError> {
+        let (width, height) = scaled_size(cells.size(), scale)?;
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in cells.rows() {
+            let start = pixels.len();
+            for &cell in row {
+                let value = if cell { filled } else { empty };
+                pixels.resize(pixels.len() + scale, value);
+            }
+
+            // Repeat the scaled row
+            let end = pixels.len();
+            for _ in 1..scale {
+                pixels.extend_from_within(start..end);
+            }
+        }
+
+        Ok(Raster { width, height, pixels })
+    }
+
+    /// Draws the [blocks](WallScene::blocks) of the scene as squares of `scale` pixels, using
+    /// the given values for open space and walls
+    pub fn from_walls(scene: &WallScene, scale: usize, open: u8, wall: u8) -> Result<Self, RenderError> {
+        Raster::from_cells(&scene.blocks(), scale, open, wall)
+    }
+
+    /// Returns the width in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+}
+
+/// Returns the size in pixels of a scene of the given size when each cell uses `scale` pixels
+pub fn scaled_size(size: Size, scale: usize) -> Result<(usize, usize), RenderError> {
+    let width = size.width.checked_mul(scale).ok_or(RenderError::TooLarge)?;
+    let height = size.height.checked_mul(scale).ok_or(RenderError::TooLarge)?;
+    width.checked_mul(height).ok_or(RenderError::TooLarge)?;
+
+    Ok((width, height))
+}
+
+/// Creates a grayscale PNG encoder for images of the given size in pixels
+#[cfg(feature = "png")]
+pub fn png_encoder<W: Write>(width: usize, height: usize, writer: W) -> Result<png::Encoder<'static, W>, RenderError> {
+    let (width, height) = (u32::try_from(width).map_err(|_| RenderError::TooLarge)?,
+                           u32::try_from(height).map_err(|_| RenderError::TooLarge)?);
+
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    Ok(encoder)
+}
+
+/// Writes the raster as a grayscale PNG image
+#[cfg(feature = "png")]
+pub fn write_png<W: Write>(raster: &Raster, writer: W) -> Result<(), RenderError> {
+    let mut writer = png_encoder(raster.width, raster.height, writer)?.write_header()?;
+    writer.write_image_data(&raster.pixels)?;
+    writer.finish()?;
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Scenes written as SVG documents, which scale without losing quality. Each cell is a square
+//! of `scale` units, and walls are drawn as lines on the edges of the cells.
+
+use std::fmt::Write;
+
+use grid2d::{Grid, Position};
+
+use crate::{Color, WallScene};
+
+/// Options used when writing SVG documents
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// Size in units of each cell
+    pub scale: usize,
+    /// Color of the empty cells
+    pub background: Color,
+    /// Color of the filled cells and the walls
+    pub foreground: Color,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions { scale: 10, background: Color::WHITE, foreground: Color::BLACK }
+    }
+}
+
+/// Returns a document with a square for each filled cell
+pub fn cells(cells: &Grid<bool>, options: &SvgOptions) -> String {
+    let scale = options.scale;
+    let mut svg = header(cells.width() * scale, cells.height() * scale, options.background);
+
+    let _ = writeln!(svg, "<g fill=\"{}\">", options.foreground);
+    for (position, _) in cells.iter().filter(|(_, filled)| **filled) {
+        let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{scale}\" height=\"{scale}\"/>",
+                         position.column * scale, position.row * scale);
+    }
+    svg.push_str("</g>\n</svg>\n");
+
+    svg
+}
+
+/// Returns a document with a line for each wall. The scene is surrounded by a margin, so the
+/// border walls are not cut.
+pub fn walls(scene: &WallScene, options: &SvgOptions) -> String {
+    let scale = options.scale;
+    let stroke = (scale / 8).max(1);
+    let size = scene.size();
+    let mut svg = header(size.width * scale + 2 * stroke, size.height * scale + 2 * stroke, options.background);
+
+    let _ = writeln!(svg, "<g stroke=\"{}\" stroke-width=\"{stroke}\" stroke-linecap=\"square\">", options.foreground);
+    for row in 0..=size.height {
+        for column in 0..=size.width {
+            let position = Position::new(column, row);
+            let (x, y) = (column * scale + stroke, row * scale + stroke);
+            if scene.horizontal_wall(position) {
+                let _ = writeln!(svg, "<line x1=\"{x}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\"/>", x + scale);
+            }
+            if scene.vertical_wall(position) {
+                let _ = writeln!(svg, "<line x1=\"{x}\" y1=\"{y}\" x2=\"{x}\" y2=\"{}\"/>", y + scale);
+            }
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+
+    svg
+}
+
+/// Returns the opening of a document filled with the background
+fn header(width: usize, height: usize, background: Color) -> String {
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"{background}\"/>\n")
+}
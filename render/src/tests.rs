@@ -0,0 +1,90 @@
+use grid2d::{Grid, Position, Size};
+
+use crate::raster::Raster;
+use crate::svg::{self, SvgOptions};
+use crate::text::{self, Style};
+use crate::*;
+
+/// Scene of 2x2 cells with a single opening between the top cells
+fn scene() -> WallScene {
+    WallScene::from_fn(Size::new(2, 2), |position| CellWalls {
+        right: position != Position::new(0, 0),
+        bottom: true,
+    })
+}
+
+#[test]
+fn wall_scene() {
+    let scene = scene();
+    assert!(scene.horizontal_wall(Position::new(1, 0)));
+    assert!(scene.horizontal_wall(Position::new(1, 2)));
+    assert!(!scene.vertical_wall(Position::new(1, 0)));
+    assert!(scene.vertical_wall(Position::new(1, 1)));
+    assert!(scene.vertical_wall(Position::new(2, 1)));
+
+    // Outside of the scene
+    assert!(!scene.horizontal_wall(Position::new(2, 0)));
+    assert!(!scene.vertical_wall(Position::new(0, 2)));
+
+    assert_eq!(scene.corner(Position::new(1, 0)), Corner { up: false, down: false, left: true, right: true });
+    assert_eq!(scene.corner(Position::new(1, 1)), Corner { up: false, down: true, left: true, right: true });
+
+    let blocks = text::cells(&scene.blocks(), Style::Ascii);
+    assert_eq!(blocks, "xxxxx\nx   x\nxxxxx\nx x x\nxxxxx");
+}
+
+#[test]
+fn text_scenes() {
+    let scene = scene();
+    assert_eq!(text::walls(&scene, Style::Ascii), "_____\n|_ _|\n|_|_|\n");
+    assert_eq!(text::walls(&scene, Style::Unicode), "┌───┐\n│   │\n├─┬─┤\n│ │ │\n└─┴─┘\n");
+
+    let mut cells = Grid::new(Size::new(3, 2), false);
+    cells[Position::new(0, 0)] = true;
+    cells[Position::new(2, 1)] = true;
+    assert_eq!(text::cells(&cells, Style::Ascii), "x  \n  x");
+    assert_eq!(text::cells(&cells, Style::Unicode), "█  \n  █");
+}
+
+#[test]
+fn svg_scenes() {
+    let options = SvgOptions { scale: 8, ..SvgOptions::default() };
+
+    let mut cells = Grid::new(Size::new(3, 2), false);
+    cells[Position::new(2, 1)] = true;
+    let document = svg::cells(&cells, &options);
+    assert!(document.starts_with("<svg "));
+    assert!(document.contains("width=\"24\" height=\"16\""));
+    assert!(document.contains("<rect x=\"16\" y=\"8\" width=\"8\" height=\"8\"/>"));
+    assert_eq!(document.matches("<rect x=").count(), 1);
+    assert!(document.ends_with("</svg>\n"));
+
+    // Borders take 8 lines and the inner walls 3
+    let document = svg::walls(&scene(), &options);
+    assert_eq!(document.matches("<line ").count(), 11);
+    assert!(document.contains("stroke=\"#000000\""));
+}
+
+#[test]
+fn rasters() {
+    let mut cells = Grid::new(Size::new(2, 1), false);
+    cells[Position::new(1, 0)] = true;
+
+    let raster = Raster::from_cells(&cells, 2, 0, 9).unwrap();
+    assert_eq!((raster.width(), raster.height()), (4, 2));
+    assert_eq!(raster.pixels(), &[0, 0, 9, 9, 0, 0, 9, 9]);
+
+    let raster = Raster::from_walls(&scene(), 1, 0, 1).unwrap();
+    assert_eq!((raster.width(), raster.height()), (5, 5));
+    assert_eq!(&raster.pixels()[5..10], &[1, 0, 0, 0, 1]);
+
+    assert!(matches!(Raster::from_cells(&cells, usize::MAX, 0, 1), Err(RenderError::TooLarge)));
+}
+
+#[test]
+fn colors() {
+    assert_eq!(Color::rgb(0xff, 0x80, 0x00).to_string(), "#ff8000");
+    assert_eq!(Color::WHITE.luma(), 0xff);
+    assert_eq!(Color::BLACK.luma(), 0x00);
+    assert_eq!(Color::WHITE.channels(), [0xff; 3]);
+}
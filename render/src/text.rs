@@ -0,0 +1,144 @@
+//! Scenes written as text, one character per cell. Both functions write into any
+//! [std::fmt::Write], so they can be used from `Display` implementations.
+
+use std::fmt::Write;
+
+use grid2d::{Grid, Position};
+
+use crate::{Corner, WallScene};
+
+/// Characters used to write a scene
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Style {
+    /// Characters that work on any terminal
+    #[default]
+    Ascii,
+    /// Blocks and box drawing characters
+    Unicode,
+}
+
+/// Writes the cells row by row, separated by newlines, without a newline after the last one
+pub fn write_cells(f: &mut impl Write, cells: &Grid<bool>, style: Style) -> std::fmt::Result {
+    let (filled, empty) = match style {
+        Style::Ascii => ('x', ' '),
+        Style::Unicode => ('█', ' '),
+    };
+
+    for (i, row) in cells.rows().enumerate() {
+        if i != 0 {
+            f.write_char('\n')?;
+        }
+        for &cell in row {
+            f.write_char(if cell { filled } else { empty })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the walls of the scene, with a newline after each line.
+///
+/// In ASCII each cell is written with its bottom wall (`_`) followed by its right wall (`|`),
+/// under a line with the top border. In Unicode the walls are drawn between the cells, using a
+/// line for the corners and walls above each row.
+pub fn write_walls(f: &mut impl Write, scene: &WallScene, style: Style) -> std::fmt::Result {
+    match style {
+        Style::Ascii => write_ascii_walls(f, scene),
+        Style::Unicode => write_unicode_walls(f, scene),
+    }
+}
+
+/// Returns the cells written with [write_cells]
+pub fn cells(cells: &Grid<bool>, style: Style) -> String {
+    let mut text = String::new();
+    let _ = write_cells(&mut text, cells, style);
+    text
+}
+
+/// Returns the scene written with [write_walls]
+pub fn walls(scene: &WallScene, style: Style) -> String {
+    let mut text = String::new();
+    let _ = write_walls(&mut text, scene, style);
+    text
+}
+
+fn write_ascii_walls(f: &mut impl Write, scene: &WallScene) -> std::fmt::Result {
+    const H_WALL: char = '_';
+    const V_WALL: char = '|';
+    const EMPTY: char = ' ';
+
+    // Top border
+    let size = scene.size();
+    for _ in 0..2 * size.width + 1 {
+        f.write_char(H_WALL)?;
+    }
+    f.write_char('\n')?;
+
+    // Rows
+    for row in 0..size.height {
+        f.write_char(V_WALL)?;
+        for column in 0..size.width {
+            let bottom = scene.horizontal_wall(Position::new(column, row + 1));
+            f.write_char(if bottom { H_WALL } else { EMPTY })?;
+
+            let right = scene.vertical_wall(Position::new(column + 1, row));
+            f.write_char(if right { V_WALL } else { EMPTY })?;
+        }
+        f.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+fn write_unicode_walls(f: &mut impl Write, scene: &WallScene) -> std::fmt::Result {
+    let size = scene.size();
+    for row in 0..=size.height {
+        // Corners and walls above the row
+        for column in 0..=size.width {
+            let position = Position::new(column, row);
+            f.write_char(corner_char(scene.corner(position)))?;
+            if column < size.width {
+                f.write_char(if scene.horizontal_wall(position) { '─' } else { ' ' })?;
+            }
+        }
+        f.write_char('\n')?;
+
+        if row == size.height {
+            break;
+        }
+
+        // Walls to the left of the cells
+        for column in 0..=size.width {
+            f.write_char(if scene.vertical_wall(Position::new(column, row)) { '│' } else { ' ' })?;
+            if column < size.width {
+                f.write_char(' ')?;
+            }
+        }
+        f.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Returns the box drawing character joining the walls of a corner
+fn corner_char(corner: Corner) -> char {
+    let Corner { up, down, left, right } = corner;
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, true) => '─',
+        (true, true, false, false) => '│',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use errors::Error;
+
+use conway_life::headless::{self, HeadlessOptions, STANDARD_STREAM};
+use conway_life::pattern;
+use conway_life::search::SearchOptions;
+use conway_life::{Environment, Rule};
+use perfect_maze_generator::PerfectMaze;
+use render::raster::{self, Raster};
+use render::svg::{self, SvgOptions};
+use render::text::{self, Style};
+use render::Color;
+
+/// Runs the projects of the workspace from a single tool
+#[derive(Parser, Debug)]
+#[command(name = "rust-projects")]
+struct Cli {
+    #[command(flatten)]
+    shared: SharedArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Flags understood by every subcommand
+#[derive(Args, Debug)]
+struct SharedArgs {
+    /// Seed of the random generators, so a run can be repeated. A random one is used if not
+    /// given.
+    #[arg(long, short, global = true)]
+    seed: Option<u64>,
+
+    /// File where the result is written, or - to write it to the standard output (default)
+    #[arg(long, short, global = true)]
+    output: Option<PathBuf>,
+
+    /// Format of the result. Mazes are written as ascii, unicode, svg or png, and Life
+    /// patterns as rle, cells, yaml or ascii.
+    #[arg(long, short, global = true)]
+    format: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generates a perfect maze, in which for any two points only one path exists
+    Maze {
+        /// Amount of rows to use. Cannot be 0.
+        #[arg(long, short)]
+        rows: usize,
+
+        /// Amount of columns to use. Cannot be 0.
+        #[arg(long, short)]
+        columns: usize,
+    },
+
+    /// Simulates Conway's Game of Life and writes the resulting pattern
+    Life {
+        /// Pattern to load (.rle, .cells or .con), or - to read it from the standard input. A
+        /// random soup is used if not given.
+        #[arg(long, short)]
+        pattern: Option<PathBuf>,
+
+        /// Amount of generations to simulate
+        #[arg(long, short, default_value_t = 0)]
+        generations: usize,
+
+        /// Rule to use, in B/S notation (e.g. B3/S23)
+        #[arg(long, short)]
+        rule: Option<Rule>,
+    },
+}
+
+fn main() -> ExitCode {
+    errors::report(run(Cli::parse()))
+}
+
+/// Runs the subcommand
+fn run(Cli { shared, command }: Cli) -> Result<(), Error> {
+    match command {
+        Command::Maze { rows, columns } => {
+            // A seed of 0 keeps the walls in order, so it is passed as given
+            let maze = PerfectMaze::try_new(columns, rows, shared.seed)
+                .ok_or_else(|| Error::parse("The maze needs at least one row and one column"))?;
+            let scene = maze.scene();
+
+            let format = match (&shared.format, &shared.output) {
+                (Some(format), _) => format.to_ascii_lowercase(),
+                (None, Some(path)) => path.extension().and_then(|e| e.to_str()).unwrap_or("ascii").to_ascii_lowercase(),
+                (None, None) => String::from("ascii"),
+            };
+            let contents = match format.as_str() {
+                "ascii" | "txt" => text::walls(&scene, Style::Ascii).into_bytes(),
+                "unicode" => text::walls(&scene, Style::Unicode).into_bytes(),
+                "svg" => svg::walls(&scene, &SvgOptions::default()).into_bytes(),
+                "png" => {
+                    let mut image = Vec::new();
+                    let raster = Raster::from_walls(&scene, MAZE_PIXELS, Color::WHITE.luma(), Color::BLACK.luma())?;
+                    raster::write_png(&raster, &mut image)?;
+                    image
+                }
+                format => return Err(Error::format(format!("Unsupported maze format '{}'", format))),
+            };
+            write_output(shared.output.as_deref(), &contents)?;
+        }
+        Command::Life { pattern, generations, rule } => {
+            // Without a pattern a random soup is read in place of the standard input
+            let soup = pattern.is_none().then(|| pattern::to_rle(&random_soup(shared.seed)));
+            let options = HeadlessOptions {
+                pattern: Some(pattern.unwrap_or_else(|| PathBuf::from(STANDARD_STREAM))),
+                generations,
+                rule,
+                out: Some(shared.output.unwrap_or_else(|| PathBuf::from(STANDARD_STREAM))),
+                format: shared.format.as_deref().map(str::parse).transpose()?,
+                ..HeadlessOptions::default()
+            };
+
+            let input: Box<dyn Read> = match &soup {
+                Some(soup) => Box::new(soup.as_bytes()),
+                None => Box::new(io::stdin().lock()),
+            };
+            let summary = headless::run(&options, input, io::stdout().lock())?;
+
+            // The standard output may hold the pattern
+            eprintln!("{summary}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Size in pixels of the walls and cells of mazes written as PNG
+const MAZE_PIXELS: usize = 8;
+
+/// Creates the random soup used when no pattern is given
+fn random_soup(seed: Option<u64>) -> Environment {
+    let options = SearchOptions { seed: seed.unwrap_or_else(rand::random), ..SearchOptions::default() };
+    options.soup(0)
+}
+
+/// Writes the result to a file, or to the standard output if the path is missing or -
+fn write_output(path: Option<&Path>, contents: &[u8]) -> io::Result<()> {
+    match path {
+        Some(path) if path.as_os_str() != STANDARD_STREAM => fs::write(path, contents),
+        _ => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(contents)?;
+            stdout.flush()
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use util::process::CliTest;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_rust-projects");
+
+#[test]
+fn maze_command() {
+    CliTest::new(BINARY)
+        .args(["maze", "--rows", "3", "--columns", "2", "--seed", "0"])
+        .run()
+        .success()
+        .stdout_eq("_____\n|   |\n| | |\n|_|_|\n");
+}
+
+#[test]
+fn unsupported_maze_format() {
+    CliTest::new(BINARY)
+        .args(["--format", "bmp", "maze", "--rows", "3", "--columns", "2"])
+        .run()
+        .exit_code(64)
+        .stderr_contains("Unsupported maze format 'bmp'");
+}
+
+#[test]
+fn empty_maze() {
+    CliTest::new(BINARY)
+        .args(["maze", "--rows", "0", "--columns", "2"])
+        .run()
+        .exit_code(65)
+        .stderr_eq("Error: The maze needs at least one row and one column\n");
+}
+
+#[test]
+fn missing_pattern() {
+    CliTest::new(BINARY)
+        .args(["life", "--pattern", "missing.rle"])
+        .run()
+        .exit_code(74)
+        .stderr_contains("Error: Unable to read or write the pattern\n  Caused by:");
+}
+
+#[test]
+fn unicode_maze() {
+    CliTest::new(BINARY)
+        .args(["maze", "--rows", "1", "--columns", "2", "--seed", "0", "--format", "unicode"])
+        .run()
+        .success()
+        .stdout_eq("┌───┐\n│   │\n└───┘\n");
+}
+
+#[test]
+fn life_command() {
+    CliTest::new(BINARY)
+        .args(["life", "--pattern", "-", "--generations", "1", "--format", "cells"])
+        .stdin("x = 3, y = 1\n3o!")
+        .run()
+        .success()
+        .stdout_eq("O\nO\nO\n")
+        .stderr_contains("Population: 3");
+}
+
+#[test]
+fn seeded_soups() {
+    let run = || CliTest::new(BINARY)
+        .args(["life", "--seed", "42", "--generations", "10"])
+        .run()
+        .success()
+        .stdout()
+        .to_string();
+
+    assert_eq!(run(), run());
+}
@@ -0,0 +1,205 @@
+//! Editing of the command line of an application. Lines are edited by character, so the
+//! cursor never splits a multi byte character, and submitted lines are kept in a history that
+//! can be browsed like in a shell. Submitted lines are split in words with [tokenize], and the
+//! last word is completed with [Completion].
+
+/// A single line text editor that keeps a history of the submitted lines.
+#[derive(Debug, Default)]
+pub struct LineEditor {
+    line: String,
+    /// Position of the cursor, in characters
+    cursor: usize,
+    history: Vec<String>,
+    /// Position within the history while browsing it
+    history_index: Option<usize>,
+    /// Line being edited before browsing the history
+    draft: String,
+}
+
+impl LineEditor {
+    /// Returns the current line
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Returns the position of the cursor, in characters
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Returns the byte index of the given character position
+    fn byte_index(&self, position: usize) -> usize {
+        self.line.char_indices().nth(position).map_or(self.line.len(), |(i, _)| i)
+    }
+
+    /// Inserts a character at the cursor
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.line.insert(index, c);
+        self.cursor += 1;
+    }
+
+    /// Removes the character before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index(self.cursor);
+            self.line.remove(index);
+        }
+    }
+
+    /// Removes the character under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.line.chars().count() {
+            let index = self.byte_index(self.cursor);
+            self.line.remove(index);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.line.chars().count());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.line.chars().count();
+    }
+
+    /// Replaces the line with the previous entry in the history
+    pub fn previous(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.line.clone();
+                self.history.len() - 1
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+
+        self.history_index = Some(index);
+        self.set_line(self.history[index].clone());
+    }
+
+    /// Replaces the line with the next entry in the history, or the line that was being
+    /// edited before browsing the history
+    pub fn next(&mut self) {
+        match self.history_index {
+            None => {}
+            Some(index) if index + 1 < self.history.len() => {
+                self.history_index = Some(index + 1);
+                self.set_line(self.history[index + 1].clone());
+            }
+            Some(_) => {
+                self.history_index = None;
+                let draft = std::mem::take(&mut self.draft);
+                self.set_line(draft);
+            }
+        }
+    }
+
+    /// Returns the current line and clears the editor. Non empty lines are added to the
+    /// history, unless they repeat the last entry.
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.line);
+        self.cursor = 0;
+        self.history_index = None;
+        self.draft.clear();
+
+        if !line.is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+
+        line
+    }
+
+    /// Replaces the line, moving the cursor to its end
+    pub fn set_line(&mut self, line: String) {
+        self.line = line;
+        self.end();
+    }
+}
+
+/// Splits the input in words separated by whitespace. Words within double quotes can contain
+/// spaces, e.g. `place "glider gun"`.
+pub fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quoted {
+        return Err(String::from("Missing closing quote"));
+    }
+    words.extend(word);
+
+    Ok(words)
+}
+
+/// Result of completing the input
+#[derive(Debug, PartialEq)]
+pub struct Completion {
+    /// Input after completing its last word
+    pub line: String,
+    /// Every possible completion of the last word
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    /// Completes the `word` that follows `head` with the candidates found for it. A single
+    /// candidate replaces the word followed by `suffix`, and several ones by their common prefix.
+    pub fn new(head: &str, word: &str, candidates: Vec<String>, suffix: &str) -> Self {
+        let completed = match candidates.as_slice() {
+            [] => word.to_string(),
+            [single] => format!("{}{}", single, suffix),
+            // Keep the word when the candidates only share a shorter prefix, e.g. in other case
+            _ => Some(common_prefix(&candidates)).filter(|p| p.len() >= word.len()).unwrap_or_else(|| word.to_string()),
+        };
+
+        Completion { line: format!("{}{}", head, completed), candidates }
+    }
+}
+
+/// Splits the input before its last word, which is the one being completed. A word with an
+/// unclosed quote starts at the quote, so it can contain spaces, e.g. `place "gosper g`.
+pub fn split_last_word(input: &str) -> (&str, &str) {
+    let quoted = input.matches('"').count() % 2 == 1;
+    let start = if quoted { input.rfind('"') } else { input.rfind(' ').map(|index| index + 1) };
+    match start {
+        Some(index) => input.split_at(index),
+        None => ("", input),
+    }
+}
+
+/// Returns the longest prefix shared by every candidate
+pub fn common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+
+    let length = candidates.iter().skip(1).fold(first.len(), |length, candidate| {
+        first.char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, c), _)| i + c.len_utf8())
+            .min(length)
+    });
+
+    first[..length].to_string()
+}
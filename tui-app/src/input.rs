@@ -0,0 +1,189 @@
+//! Thread that reads the keyboard while the main loop of the application draws. Typed lines and
+//! key bindings are sent as commands, and ticks and frames are sent at the rates of a [Timing]
+//! shared with the main loop.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+
+use crate::editor::{Completion, LineEditor};
+use crate::keys::{self, KeyBindings};
+
+/// Least time waited for the keyboard, to avoid spinning when ticks are very fast
+const MIN_WAIT: Duration = Duration::from_millis(1);
+
+/// Events sent by the input thread, converted into the events of the application
+#[derive(Debug, PartialEq)]
+pub enum InputEvent {
+    /// A line submitted in the input, or the command bound to a key
+    Command(String),
+    /// The input was edited, given its line and the position of its cursor in characters
+    Edit(String, usize),
+    /// Information to show to the user, such as the candidates of an ambiguous completion
+    Message(String),
+    Tick,
+    Frame,
+    Quit,
+}
+
+/// Timing of the ticks and frames, shared between the main loop and the input thread. Ticks
+/// update the application and frames draw it, so they can run at different rates.
+#[derive(Debug)]
+pub struct Timing {
+    /// Time between ticks, in microseconds
+    interval: AtomicU64,
+    /// Whether a tick was sent and not processed yet. Avoids piling up ticks when processing
+    /// them is slower than the tick rate.
+    pending: AtomicBool,
+    /// Time between frames, in microseconds
+    frame_interval: AtomicU64,
+    /// Whether a frame was sent and not drawn yet
+    frame_pending: AtomicBool,
+}
+
+impl Timing {
+    pub fn new(interval: Duration, frame_interval: Duration) -> Self {
+        Timing {
+            interval: AtomicU64::new(interval.as_micros() as u64),
+            pending: AtomicBool::new(false),
+            frame_interval: AtomicU64::new(frame_interval.as_micros() as u64),
+            frame_pending: AtomicBool::new(false),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_micros(self.interval.load(Ordering::Relaxed))
+    }
+
+    pub fn set_interval(&self, interval: Duration) {
+        self.interval.store(interval.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_micros(self.frame_interval.load(Ordering::Relaxed))
+    }
+
+    pub fn set_frame_interval(&self, interval: Duration) {
+        self.frame_interval.store(interval.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Marks the last tick as processed, so the next one can be sent
+    pub fn tick_done(&self) {
+        self.pending.store(false, Ordering::Relaxed);
+    }
+
+    /// Marks the last frame as drawn, so the next one can be sent
+    pub fn frame_done(&self) {
+        self.frame_pending.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Completes the last word of the input when Tab is pressed. It runs on the input thread, so it
+/// owns whatever state it needs.
+pub type Completer = Box<dyn Fn(&str) -> Completion + Send>;
+
+/// Spawns the input thread, which runs until the receiver of the events is dropped
+pub fn spawn<E>(timing: Arc<Timing>, key_bindings: KeyBindings, complete: Completer, sender: Sender<E>) -> JoinHandle<()>
+    where E: From<InputEvent> + Send + 'static {
+    thread::spawn(move || handle_input(&timing, &key_bindings, &complete, &sender))
+}
+
+/// Handle input and events
+fn handle_input<E: From<InputEvent>>(timing: &Timing, key_bindings: &KeyBindings, complete: &Completer, sender: &Sender<E>) {
+    let send = |event: InputEvent| sender.send(E::from(event));
+    let mut last_tick = Instant::now();
+    let mut last_frame = Instant::now();
+    let mut editor = LineEditor::default();
+
+    loop {
+        // Wait until the next tick or frame, but at least a little bit to avoid spinning at
+        // max speed
+        let tick_rate = timing.interval();
+        let frame_rate = timing.frame_interval();
+        let timeout = tick_rate.saturating_sub(last_tick.elapsed())
+            .min(frame_rate.saturating_sub(last_frame.elapsed()))
+            .max(MIN_WAIT);
+
+        if event::poll(timeout).expect("Poll not working") {
+            // Send the key events
+            if let Event::Key(key) = event::read().expect("Can't read events") {
+                let binding = if key.kind == KeyEventKind::Press {
+                    keys::find_binding(key_bindings, key.code, key.modifiers, editor.line().is_empty())
+                } else {
+                    None
+                };
+
+                let result = if let Some(command) = binding {
+                    send(InputEvent::Command(command.clone()))
+                } else {
+                    match (key.code, key.kind) {
+                        (KeyCode::Esc, KeyEventKind::Press) => send(InputEvent::Quit),
+                        (KeyCode::Enter, KeyEventKind::Press) => {
+                            let input = editor.submit();
+                            if !input.is_empty() {
+                                send(InputEvent::Command(input))
+                            } else {
+                                // Ignore enter
+                                send(InputEvent::Edit(String::default(), 0))
+                            }
+                        }
+                        (KeyCode::Tab, KeyEventKind::Press) => {
+                            let completion = complete(editor.line());
+                            editor.set_line(completion.line);
+                            let result = send(InputEvent::Edit(editor.line().to_string(), editor.cursor()));
+
+                            // Show the options when the completion is ambiguous
+                            if completion.candidates.len() > 1 {
+                                result.and_then(|_| send(InputEvent::Message(completion.candidates.join("  "))))
+                            } else {
+                                result
+                            }
+                        }
+                        (code, KeyEventKind::Press) => {
+                            match code {
+                                KeyCode::Char(c) => editor.insert(c),
+                                KeyCode::Backspace => editor.backspace(),
+                                KeyCode::Delete => editor.delete(),
+                                KeyCode::Left => editor.left(),
+                                KeyCode::Right => editor.right(),
+                                KeyCode::Home => editor.home(),
+                                KeyCode::End => editor.end(),
+                                KeyCode::Up => editor.previous(),
+                                KeyCode::Down => editor.next(),
+                                _ => {}
+                            }
+                            send(InputEvent::Edit(editor.line().to_string(), editor.cursor()))
+                        }
+                        _ => Ok(())
+                    }
+                };
+
+                // Break on an error
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+
+        // Ticks and frames also stop the thread once the application is gone, without waiting
+        // for a key
+        if last_tick.elapsed() >= tick_rate && !timing.pending.swap(true, Ordering::Relaxed) {
+            if send(InputEvent::Tick).is_err() {
+                break;
+            }
+            last_tick = Instant::now();
+        }
+
+        // Frames that can't be drawn in time are skipped
+        if last_frame.elapsed() >= frame_rate && !timing.frame_pending.swap(true, Ordering::Relaxed) {
+            if send(InputEvent::Frame).is_err() {
+                break;
+            }
+            last_frame = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Commands bound to keys, e.g. `ctrl-t` or `]`, which are sent as if they were typed.
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// Commands bound to a key and its modifiers
+pub type KeyBindings = HashMap<(KeyCode, KeyModifiers), String>;
+
+/// Creates the key bindings from pairs of key names and commands, skipping invalid keys
+pub fn bindings(pairs: &[(&str, &str)]) -> KeyBindings {
+    pairs.iter()
+        .filter_map(|(key, command)| Some((parse_key(key)?, command.to_string())))
+        .collect()
+}
+
+/// Parses a key binding such as `ctrl-p`, `f2` or `[`
+pub fn parse_key(name: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let name = name.trim().to_ascii_lowercase();
+    let mut modifiers = KeyModifiers::NONE;
+    let mut key = name.as_str();
+    while let Some((modifier, rest)) = key.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        match modifier {
+            "ctrl" | "control" => modifiers.insert(KeyModifiers::CONTROL),
+            "alt" => modifiers.insert(KeyModifiers::ALT),
+            "shift" => modifiers.insert(KeyModifiers::SHIFT),
+            _ => return None,
+        }
+        key = rest;
+    }
+
+    let code = match key {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        f if f.starts_with('f') && f.len() > 1 => KeyCode::F(f[1..].parse().ok()?),
+        c if c.chars().count() == 1 => KeyCode::Char(c.chars().next()?),
+        _ => return None,
+    };
+
+    Some((code, modifiers))
+}
+
+/// Returns the command bound to a key. Character keys without modifiers are only bound while
+/// the input is empty, so they can still be typed.
+pub fn find_binding(key_bindings: &KeyBindings, code: KeyCode, mut modifiers: KeyModifiers, input_empty: bool) -> Option<&String> {
+    let is_character = matches!(code, KeyCode::Char(_));
+    if is_character {
+        // Shifted characters are already reported in upper case
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+
+    let command = key_bindings.get(&(code, modifiers))?;
+    let is_text = is_character && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    (!is_text || input_empty).then_some(command)
+}
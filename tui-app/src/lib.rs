@@ -0,0 +1,51 @@
+//! Building blocks of the text based user interfaces of the workspace, which run their main loop
+//! over a channel of their own events:
+//!
+//! * [terminal] prepares the terminal for drawing and restores it afterwards.
+//! * [input] spawns a thread that reads the keyboard, sending the lines typed in a command line
+//!   ([editor]) or the commands bound to keys ([keys]), along with ticks and frames at the rates
+//!   of a shared [Timing](input::Timing). Events are converted into the events of the
+//!   application with `From<InputEvent>`.
+//!
+//! ```ignore
+//! let mut terminal = terminal::setup()?;
+//! let (sender, receiver) = mpsc::channel::<AppEvent>();
+//! let input = input::spawn(timing.clone(), key_bindings, Box::new(complete), sender);
+//! while let AppEvent::Command(line) = receiver.recv()? { ... }
+//! terminal::cleanup(&mut terminal)?;
+//! ```
+
+use std::io;
+use std::sync::mpsc::RecvError;
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Single line editor with history, tokenizing and completion of the lines
+pub mod editor;
+
+/// Thread reading the keyboard and timing ticks and frames
+pub mod input;
+
+/// Commands bound to keys
+pub mod keys;
+
+/// Setup and cleanup of the terminal
+pub mod terminal;
+
+#[derive(Error, Debug)]
+pub enum ApplicationError {
+    #[error("Error with terminal application")]
+    Terminal(#[from] io::Error),
+
+    #[error("Error while transmitting information")]
+    Channel(#[from] RecvError),
+}
+
+impl From<ApplicationError> for errors::Error {
+    fn from(error: ApplicationError) -> Self {
+        errors::Error::io("Unable to run the terminal interface").with_source(error)
+    }
+}
@@ -0,0 +1,33 @@
+//! Prepares the terminal to be drawn by the user interface, and restores it for the following
+//! process.
+
+use std::io::{self, Stdout};
+
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+
+/// Terminal drawn by the applications
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// Set's up the terminal so it is ready to be written by the UI
+pub fn setup() -> io::Result<Tui> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+
+    Ok(terminal)
+}
+
+/// Clean's up the terminal for the following process
+pub fn cleanup(terminal: &mut Tui) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::editor::*;
+use crate::input::Timing;
+use crate::keys::*;
+
+#[test]
+fn line_editor() {
+    let mut editor = LineEditor::default();
+    for c in "lod x".chars() {
+        editor.insert(c);
+    }
+    assert_eq!(editor.cursor(), 5);
+
+    // Fix the typo in the middle of the line
+    editor.home();
+    editor.right();
+    editor.right();
+    editor.insert('a');
+    assert_eq!(editor.line(), "load x");
+    editor.end();
+    editor.backspace();
+    editor.left();
+    editor.delete();
+    assert_eq!(editor.line(), "load");
+    assert_eq!(editor.cursor(), 4);
+
+    assert_eq!(editor.submit(), "load");
+    assert_eq!(editor.line(), "");
+    assert_eq!(editor.cursor(), 0);
+}
+
+#[test]
+fn line_editor_history() {
+    let mut editor = LineEditor::default();
+    for line in ["pause", "stats", "stats"] {
+        line.chars().for_each(|c| editor.insert(c));
+        editor.submit();
+    }
+
+    // Browse back, repeated lines are only kept once
+    editor.insert('q');
+    editor.previous();
+    assert_eq!(editor.line(), "stats");
+    assert_eq!(editor.cursor(), 5);
+    editor.previous();
+    assert_eq!(editor.line(), "pause");
+    editor.previous();
+    assert_eq!(editor.line(), "pause");
+
+    // Browse forward until the draft is restored
+    editor.next();
+    assert_eq!(editor.line(), "stats");
+    editor.next();
+    assert_eq!(editor.line(), "q");
+    editor.next();
+    assert_eq!(editor.line(), "q");
+}
+
+#[test]
+fn tokenizer() {
+    assert_eq!(tokenize(r#"place "gosper glider gun" 3 -4"#).unwrap(), vec!["place", "gosper glider gun", "3", "-4"]);
+    assert_eq!(tokenize("  speed   max 4 ").unwrap(), vec!["speed", "max", "4"]);
+    assert_eq!(tokenize(r#"save "" x"#).unwrap(), vec!["save", "", "x"]);
+    assert!(tokenize("").unwrap().is_empty());
+    assert!(tokenize(r#"place "glider"#).is_err());
+}
+
+#[test]
+fn completion() {
+    assert_eq!(split_last_word("load pat"), ("load ", "pat"));
+    assert_eq!(split_last_word("lo"), ("", "lo"));
+    assert_eq!(split_last_word(r#"place "gosper g"#), ("place ", r#""gosper g"#));
+    assert_eq!(split_last_word(r#"place "gosper gun" 3"#), (r#"place "gosper gun" "#, "3"));
+
+    let commands = |word: &str| ["left", "load", "lobby"].iter()
+        .filter(|c| c.starts_with(word))
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(Completion::new("", "le", commands("le"), " ").line, "left ");
+    assert_eq!(Completion::new("", "x", commands("x"), " ").line, "x");
+
+    let ambiguous = Completion::new("", "l", commands("l"), " ");
+    assert_eq!(ambiguous.line, "l");
+    assert_eq!(ambiguous.candidates.len(), 3);
+    assert_eq!(Completion::new("go ", "lo", commands("lo"), " ").line, "go lo");
+
+    assert_eq!(common_prefix(&["glider".into(), "glider gun".into()]), "glider");
+    assert_eq!(common_prefix(&["Gosper".into(), "gosper".into()]), "");
+    assert_eq!(common_prefix(&[]), "");
+}
+
+#[test]
+fn key_bindings() {
+    assert_eq!(parse_key("ctrl-p"), Some((KeyCode::Char('p'), KeyModifiers::CONTROL)));
+    assert_eq!(parse_key(" Alt-Shift-Left"), Some((KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT)));
+    assert_eq!(parse_key("f2"), Some((KeyCode::F(2), KeyModifiers::NONE)));
+    assert_eq!(parse_key("-"), Some((KeyCode::Char('-'), KeyModifiers::NONE)));
+    assert_eq!(parse_key("hyper-x"), None);
+    assert_eq!(parse_key("fx"), None);
+
+    let bindings = bindings(&[("w", "up"), ("ctrl-n", "new"), ("down", "down"), ("meta-x", "ignored")]);
+    assert_eq!(bindings.len(), 3);
+
+    // Characters are only bound while nothing is typed
+    assert_eq!(find_binding(&bindings, KeyCode::Char('w'), KeyModifiers::NONE, true).map(String::as_str), Some("up"));
+    assert_eq!(find_binding(&bindings, KeyCode::Char('w'), KeyModifiers::SHIFT, true).map(String::as_str), Some("up"));
+    assert!(find_binding(&bindings, KeyCode::Char('w'), KeyModifiers::NONE, false).is_none());
+    assert_eq!(find_binding(&bindings, KeyCode::Char('n'), KeyModifiers::CONTROL, false).map(String::as_str), Some("new"));
+    assert_eq!(find_binding(&bindings, KeyCode::Down, KeyModifiers::NONE, false).map(String::as_str), Some("down"));
+}
+
+#[test]
+fn timing() {
+    let timing = Timing::new(Duration::from_millis(50), Duration::from_secs(1) / 30);
+    assert_eq!(timing.interval(), Duration::from_millis(50));
+
+    timing.set_interval(Duration::from_secs(1));
+    timing.set_frame_interval(Duration::from_millis(100));
+    assert_eq!(timing.interval(), Duration::from_secs(1));
+    assert_eq!(timing.frame_interval(), Duration::from_millis(100));
+}
@@ -0,0 +1,69 @@
+//! Files used as inputs of the tests, like patterns or canned responses. They live in the
+//! `tests/fixtures` directory of the crate being tested, found through the `CARGO_MANIFEST_DIR`
+//! variable that cargo sets while running the tests, and are given by their path within it.
+//!
+//! ```no_run
+//! use util::fixtures;
+//!
+//! let rle = fixtures::string("patterns/glider.rle");
+//! let rule: u32 = fixtures::parsed("rule.txt", |text| text.trim().parse());
+//! ```
+
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+
+/// Returns the root directory of the crate being tested, or the working directory outside cargo
+pub(crate) fn manifest_dir() -> PathBuf {
+    env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Returns the path of a fixture
+pub fn path(name: &str) -> PathBuf {
+    manifest_dir().join("tests").join("fixtures").join(name)
+}
+
+/// Reads a fixture
+///
+/// # Panics
+/// Will panic if the fixture can't be read.
+#[track_caller]
+pub fn bytes(name: &str) -> Vec<u8> {
+    let path = path(name);
+    fs::read(&path).unwrap_or_else(|err| panic!("Unable to read fixture {}: {}", path.display(), err))
+}
+
+/// Reads a text fixture, with its line endings turned into `\n`
+///
+/// # Panics
+/// Will panic if the fixture can't be read or is not UTF-8.
+#[track_caller]
+pub fn string(name: &str) -> String {
+    let path = path(name);
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Unable to read fixture {}: {}", path.display(), err))
+        .replace("\r\n", "\n")
+}
+
+/// Reads a text fixture and parses it, e.g. with `str::parse` or the `from_str` function of a
+/// serde format
+///
+/// # Panics
+/// Will panic if the fixture can't be read or parsed.
+#[track_caller]
+pub fn parsed<T, E: Display>(name: &str, parse: impl FnOnce(&str) -> Result<T, E>) -> T {
+    parse(&string(name)).unwrap_or_else(|err| panic!("Unable to parse fixture {}: {}", path(name).display(), err))
+}
+
+/// Reads a JSON fixture into any type that can be deserialized
+///
+/// # Panics
+/// Will panic if the fixture can't be read or is not valid for the type.
+#[cfg(feature = "json")]
+#[track_caller]
+pub fn json<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    parsed(name, serde_json::from_str)
+}
@@ -0,0 +1,124 @@
+//! Golden files: expected outputs checked into the repository, next to the tests that compare
+//! against them. They live in the `tests/golden` directory of the crate being tested, found
+//! through the `CARGO_MANIFEST_DIR` variable that cargo sets while running the tests.
+//!
+//! After an intended change of the output, run the tests with `UPDATE_GOLDEN=1` to write the new
+//! outputs, and review them in the diff of the commit.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::fixtures::manifest_dir;
+
+/// Variable that makes the assertions write the golden files instead of comparing them
+pub const UPDATE_VARIABLE: &str = "UPDATE_GOLDEN";
+
+/// Unchanged lines shown around each change of a diff
+const CONTEXT: usize = 3;
+
+/// Returns the path of a golden file of the crate being tested
+pub fn golden_path(name: &str) -> PathBuf {
+    manifest_dir().join("tests").join("golden").join(name)
+}
+
+/// Returns true if the golden files should be written instead of compared
+fn updating() -> bool {
+    env::var_os(UPDATE_VARIABLE).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Checks that the output matches the golden file with the given name, or writes the file when
+/// [UPDATE_VARIABLE] is set.
+///
+/// # Panics
+/// Will panic with a diff if the output is different, or if the golden file doesn't exist.
+///
+/// ```no_run
+/// use util::golden::assert_matches_golden;
+///
+/// assert_matches_golden("glider.txt", " x\n  x\nxxx\n");
+/// ```
+#[track_caller]
+pub fn assert_matches_golden(name: &str, actual: impl AsRef<str>) {
+    let actual = actual.as_ref();
+    let path = golden_path(name);
+
+    if updating() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("unable to create the golden directory");
+        }
+        fs::write(&path, actual).expect("unable to write the golden file");
+        return;
+    }
+
+    // Checkouts on Windows may have turned the line endings into CRLF
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(error) => panic!("Unable to read golden file {}: {}\nRun the tests with {}=1 to create it",
+                             path.display(), error, UPDATE_VARIABLE),
+    };
+    if expected != actual {
+        panic!("Output doesn't match golden file {}\n{}Run the tests with {}=1 to update it",
+               path.display(), diff(&expected, actual), UPDATE_VARIABLE);
+    }
+}
+
+/// Returns a line by line diff of two texts, with `-` before the lines only in the expected text,
+/// `+` before the lines only in the actual one and a few unchanged lines around each change.
+/// The end of each line is marked with `|`, so differences in trailing spaces are visible.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.split('\n').collect();
+    let actual: Vec<&str> = actual.split('\n').collect();
+
+    // Longest common subsequence of the lines that follow each pair of positions
+    let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            lines.push((' ', expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(('-', expected[i]));
+            i += 1;
+        } else {
+            lines.push(('+', actual[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = lines.iter().enumerate()
+        .filter(|(_, (kind, _))| *kind != ' ')
+        .map(|(index, _)| index)
+        .collect();
+    let mut output = String::new();
+    let mut last_shown = None;
+    for (index, (kind, line)) in lines.iter().enumerate() {
+        let near_change = changed.iter().any(|&change| change.abs_diff(index) <= CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| last + 1 != index) || (last_shown.is_none() && index > 0) {
+            output.push_str("...\n");
+        }
+        let _ = writeln!(output, "{} {}|", kind, line);
+        last_shown = Some(index);
+    }
+    if last_shown.is_some_and(|last| last + 1 < lines.len()) {
+        output.push_str("...\n");
+    }
+
+    output
+}
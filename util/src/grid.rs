@@ -0,0 +1,76 @@
+//! Comparison of text grids, like the rendering of a maze or a viewport of cells. Instead of two
+//! long strings, a mismatch shows both grids side by side, with the differing rows marked with
+//! `>` and the differing columns of each row marked with `^`.
+//!
+//! ```text
+//! Grids differ in 1 cell, the first at row 3 and column 3
+//!     expected | actual |
+//!   0 _____    | _____  |
+//!   1 |   |    | |   |  |
+//!   2 | | |    | | | |  |
+//! > 3 |_|_|    | |_| |  |    ^
+//! ```
+
+use std::fmt::Write;
+
+/// Compares two grids cell by cell, one character per cell and one row per line. Rows of
+/// different lengths are different, so trailing spaces matter.
+///
+/// # Panics
+/// Will panic showing both grids if they differ.
+///
+/// ```
+/// use util::grid::assert_grids_eq;
+///
+/// assert_grids_eq(" x \nxxx", &format!("{}\n{}", " x ", "xxx"));
+/// ```
+#[track_caller]
+pub fn assert_grids_eq(expected: &str, actual: &str) {
+    if let Some(diff) = grid_diff(expected, actual) {
+        panic!("{}", diff);
+    }
+}
+
+/// Returns both grids side by side if they differ
+pub fn grid_diff(expected: &str, actual: &str) -> Option<String> {
+    let expected: Vec<Vec<char>> = expected.split('\n').map(|row| row.chars().collect()).collect();
+    let actual: Vec<Vec<char>> = actual.split('\n').map(|row| row.chars().collect()).collect();
+    let rows = expected.len().max(actual.len());
+
+    // Columns that differ in each row, where missing cells differ from any other
+    let differences: Vec<Vec<usize>> = (0..rows)
+        .map(|row| {
+            let expected = expected.get(row).map(Vec::as_slice).unwrap_or_default();
+            let actual = actual.get(row).map(Vec::as_slice).unwrap_or_default();
+            (0..expected.len().max(actual.len()))
+                .filter(|&column| expected.get(column) != actual.get(column))
+                .collect()
+        })
+        .collect();
+    let count: usize = differences.iter().map(Vec::len).sum();
+    let (first_row, first_columns) = differences.iter().enumerate().find(|(_, columns)| !columns.is_empty())?;
+
+    let width = |grid: &[Vec<char>], title: &str| grid.iter().map(Vec::len).max().unwrap_or(0).max(title.len());
+    let (expected_width, actual_width) = (width(&expected, "expected"), width(&actual, "actual"));
+    let row_width = (rows - 1).to_string().len();
+
+    let mut output = String::new();
+    let _ = writeln!(output, "Grids differ in {} cell{}, the first at row {} and column {}",
+                     count, if count == 1 { "" } else { "s" }, first_row, first_columns[0]);
+    let _ = writeln!(output, "  {:row_width$} {:expected_width$} | {:actual_width$} |", "", "expected", "actual");
+    for (row, columns) in differences.iter().enumerate() {
+        let cells = |grid: &[Vec<char>]| grid.get(row).map(|row| row.iter().collect()).unwrap_or_default();
+        let (expected_row, actual_row): (String, String) = (cells(&expected), cells(&actual));
+
+        let marker = if columns.is_empty() { ' ' } else { '>' };
+        let _ = write!(output, "{} {:>row_width$} {:expected_width$} | {:actual_width$} |",
+                       marker, row, expected_row, actual_row);
+        if let Some(&last) = columns.last() {
+            let marks: String = (0..=last).map(|column| if columns.contains(&column) { '^' } else { ' ' }).collect();
+            let _ = write!(output, " {}", marks);
+        }
+        output.push('\n');
+    }
+
+    Some(output)
+}
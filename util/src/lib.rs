@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests;
+
+/// Files used as inputs of the tests.
+pub mod fixtures;
+/// Comparison of outputs against golden files checked into the repository.
+pub mod golden;
+/// Comparison of text grids, showing the differing cells side by side.
+pub mod grid;
+/// Capture of the records of the `log` and `tracing` crates.
+pub mod logs;
+/// Waiting for conditions set by other threads.
+pub mod poll;
+/// Runs of the binaries of the workspace, with assertions on their output.
+pub mod process;
+/// Seeds of property style tests that can be reproduced.
+pub mod seed;
+/// Temporary files and directories, and other helpers for the tests of the workspace crates.
+pub mod test_helper;
+/// Timing of performance sensitive code within the tests.
+pub mod timing;
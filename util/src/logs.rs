@@ -0,0 +1,261 @@
+//! Capture of the diagnostics emitted through the `log` and `tracing` crates, enabled by the
+//! features of the same names, so tests can check them. Tests run in parallel threads, so the
+//! records are kept by the thread that emits them: each test only sees its own, and records of
+//! the threads it spawns are not captured.
+//!
+//! ```ignore
+//! use util::logs::{Level, LogCapture};
+//!
+//! let logs = LogCapture::start();
+//! log::warn!("cache is full");
+//! logs.assert_logged(Level::Warn, "cache is full");
+//! ```
+
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+
+/// Severity of a record, from the most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A captured record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub level: Level,
+    /// Module or target given when emitting the record
+    pub target: String,
+    pub message: String,
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+thread_local! {
+    /// Records of the thread, missing while it doesn't capture them
+    static CAPTURED: RefCell<Option<Vec<Record>>> = const { RefCell::new(None) };
+}
+
+/// Keeps a record if its thread captures them. Without the features nothing emits records.
+#[cfg_attr(not(any(feature = "log", feature = "tracing")), allow(dead_code))]
+pub(crate) fn capture(level: Level, target: &str, message: String) {
+    CAPTURED.with(|captured| {
+        if let Some(records) = captured.borrow_mut().as_mut() {
+            records.push(Record { level, target: target.to_string(), message });
+        }
+    });
+}
+
+/// Captures the records of the current thread until dropped
+#[derive(Debug)]
+pub struct LogCapture {
+    #[cfg(feature = "tracing")]
+    _subscriber: tracing::subscriber::DefaultGuard,
+    /// The records belong to the thread that started the capture
+    _thread: PhantomData<*const ()>,
+}
+
+impl LogCapture {
+    /// Starts capturing every level of the records of the current thread, discarding the records
+    /// of any previous capture
+    pub fn start() -> Self {
+        #[cfg(feature = "log")]
+        log_capture::install();
+        CAPTURED.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+
+        LogCapture {
+            #[cfg(feature = "tracing")]
+            _subscriber: tracing_capture::install(),
+            _thread: PhantomData,
+        }
+    }
+
+    /// Returns the records captured so far
+    pub fn records(&self) -> Vec<Record> {
+        CAPTURED.with(|captured| captured.borrow().clone().unwrap_or_default())
+    }
+
+    /// Returns true if a record of the level contains the text
+    pub fn contains(&self, level: Level, text: &str) -> bool {
+        self.records().iter().any(|record| record.level == level && record.message.contains(text))
+    }
+
+    /// Checks that a record of the level contains the text
+    ///
+    /// # Panics
+    /// Will panic listing the captured records if none matches.
+    #[track_caller]
+    pub fn assert_logged(&self, level: Level, text: &str) {
+        if !self.contains(level, text) {
+            panic!("Expected a {} record with '{}'\n{}", level, text, self.listing());
+        }
+    }
+
+    /// Checks that no record of the level contains the text
+    ///
+    /// # Panics
+    /// Will panic listing the captured records if any matches.
+    #[track_caller]
+    pub fn assert_not_logged(&self, level: Level, text: &str) {
+        if self.contains(level, text) {
+            panic!("Unexpected {} record with '{}'\n{}", level, text, self.listing());
+        }
+    }
+
+    /// Returns the records one per line, for the messages of the assertions
+    fn listing(&self) -> String {
+        let records = self.records();
+        if records.is_empty() {
+            return String::from("No records were captured");
+        }
+
+        records.iter().map(|record| format!("{}\n", record)).collect()
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        CAPTURED.with(|captured| *captured.borrow_mut() = None);
+    }
+}
+
+#[cfg(feature = "log")]
+mod log_capture {
+    use std::sync::Once;
+
+    use super::{capture, Level};
+
+    /// Logger of the whole process, which keeps the records of the threads that capture them
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            let level = match record.level() {
+                log::Level::Error => Level::Error,
+                log::Level::Warn => Level::Warn,
+                log::Level::Info => Level::Info,
+                log::Level::Debug => Level::Debug,
+                log::Level::Trace => Level::Trace,
+            };
+            capture(level, record.target(), record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger;
+
+    /// Installs the logger the first time. Tests that install another logger keep theirs.
+    pub(super) fn install() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            if log::set_logger(&LOGGER).is_ok() {
+                log::set_max_level(log::LevelFilter::Trace);
+            }
+        });
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_capture {
+    use std::fmt::{Debug, Write};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::subscriber::DefaultGuard;
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::{capture, Level};
+
+    /// Subscriber of the current thread that keeps its events. Spans are ignored.
+    struct CaptureSubscriber {
+        next_span: AtomicU64,
+    }
+
+    impl Subscriber for CaptureSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let level = *event.metadata().level();
+            let level = if level == tracing::Level::ERROR {
+                Level::Error
+            } else if level == tracing::Level::WARN {
+                Level::Warn
+            } else if level == tracing::Level::INFO {
+                Level::Info
+            } else if level == tracing::Level::DEBUG {
+                Level::Debug
+            } else {
+                Level::Trace
+            };
+
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            capture(level, event.metadata().target(), visitor.message + &visitor.fields);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    /// Collects the message of an event and its other fields, written as ` name=value`
+    #[derive(Default)]
+    struct MessageVisitor {
+        message: String,
+        fields: String,
+    }
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                let _ = write!(self.message, "{:?}", value);
+            } else {
+                let _ = write!(self.fields, " {}={:?}", field.name(), value);
+            }
+        }
+    }
+
+    /// Makes the subscriber the default of the current thread until the guard is dropped
+    pub(super) fn install() -> DefaultGuard {
+        // Span ids can't be 0
+        tracing::subscriber::set_default(CaptureSubscriber { next_span: AtomicU64::new(1) })
+    }
+}
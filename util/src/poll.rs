@@ -0,0 +1,153 @@
+//! Waiting for conditions set by other threads, like a worker that finished or a server that
+//! started listening, instead of sleeping for a fixed time that is either too short on a busy
+//! machine or wastes time everywhere else.
+//!
+//! The async variants don't depend on a runtime: their delays are woken by a short lived thread,
+//! so they work with any executor.
+//!
+//! ```
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use util::poll::poll_until;
+//!
+//! let ready = Arc::new(AtomicBool::new(false));
+//! let flag = ready.clone();
+//! std::thread::spawn(move || flag.store(true, Ordering::SeqCst));
+//!
+//! poll_until(Duration::from_secs(5), Duration::from_millis(1), || ready.load(Ordering::SeqCst)).unwrap();
+//! ```
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The condition didn't hold before the timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut {
+    pub timeout: Duration,
+    /// Times the condition was checked
+    pub attempts: usize,
+}
+
+impl Display for TimedOut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Condition not met after {:?} ({} attempts)", self.timeout, self.attempts)
+    }
+}
+
+impl Error for TimedOut {}
+
+/// Checks the condition every `interval` until it holds, returning the time it took. The
+/// condition is always checked at least once, and once more when the timeout is reached.
+pub fn poll_until(timeout: Duration, interval: Duration, mut condition: impl FnMut() -> bool) -> Result<Duration, TimedOut> {
+    let start = Instant::now();
+    poll_for(timeout, interval, || condition().then_some(()))?;
+
+    Ok(start.elapsed())
+}
+
+/// Checks every `interval` until the check returns a value, like a message received without
+/// blocking, and returns it
+pub fn poll_for<T>(timeout: Duration, interval: Duration, mut check: impl FnMut() -> Option<T>) -> Result<T, TimedOut> {
+    let deadline = Instant::now() + timeout;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        if let Some(value) = check() {
+            return Ok(value);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(TimedOut { timeout, attempts });
+        }
+        thread::sleep(interval.min(deadline - now));
+    }
+}
+
+/// Async variant of [poll_until], with a condition that can await
+pub async fn poll_until_async<F: Future<Output=bool>>(timeout: Duration, interval: Duration,
+                                                      mut condition: impl FnMut() -> F) -> Result<Duration, TimedOut> {
+    let start = Instant::now();
+    poll_for_async(timeout, interval, || {
+        let check = condition();
+        async move { check.await.then_some(()) }
+    }).await?;
+
+    Ok(start.elapsed())
+}
+
+/// Async variant of [poll_for], with a check that can await
+pub async fn poll_for_async<T, F: Future<Output=Option<T>>>(timeout: Duration, interval: Duration,
+                                                            mut check: impl FnMut() -> F) -> Result<T, TimedOut> {
+    let deadline = Instant::now() + timeout;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        if let Some(value) = check().await {
+            return Ok(value);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(TimedOut { timeout, attempts });
+        }
+        Delay::new(interval.min(deadline - now)).await;
+    }
+}
+
+/// State shared by a [Delay] and the thread that ends it
+#[derive(Debug, Default)]
+struct DelayState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+/// Future that is ready after some time
+#[derive(Debug)]
+struct Delay {
+    duration: Duration,
+    /// Missing until the first poll starts the timer
+    state: Option<Arc<Mutex<DelayState>>>,
+}
+
+impl Delay {
+    fn new(duration: Duration) -> Self {
+        Delay { duration, state: None }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        let duration = self.duration;
+        let state = self.state.get_or_insert_with(|| {
+            let state = Arc::new(Mutex::new(DelayState::default()));
+            let timer = state.clone();
+            thread::spawn(move || {
+                thread::sleep(duration);
+                let mut state = timer.lock().unwrap_or_else(|err| err.into_inner());
+                state.done = true;
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+            state
+        });
+
+        let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(context.waker().clone());
+            Poll::Pending
+        }
+    }
+}
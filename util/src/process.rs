@@ -0,0 +1,288 @@
+//! End to end tests of the binaries of the workspace. A [CliTest] runs a binary with its
+//! arguments and standard input, waits for it up to a timeout and returns a [CliOutput] with
+//! fluent assertions on the exit code and the captured output.
+//!
+//! Integration tests find the binaries of their crate with `env!("CARGO_BIN_EXE_<name>")`,
+//! which cargo sets while building them; [cargo_bin] looks for them at runtime instead.
+//!
+//! ```no_run
+//! use util::process::{cargo_bin, CliTest};
+//!
+//! CliTest::new(cargo_bin("conway-life"))
+//!     .args(["--generations", "1", "--format", "cells"])
+//!     .stdin("x = 3, y = 1\n3o!")
+//!     .run()
+//!     .success()
+//!     .stdout_eq("O\nO\nO\n")
+//!     .stderr_contains("Population: 3");
+//! ```
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Time a binary may run before it is killed
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between the checks of whether a binary finished
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Returns the path of a binary of the workspace, built by cargo next to the tests
+///
+/// # Panics
+/// Will panic if the binary doesn't exist, e.g. because it belongs to another crate whose binaries
+/// were not built.
+pub fn cargo_bin(name: &str) -> PathBuf {
+    if let Some(path) = env::var_os(format!("CARGO_BIN_EXE_{}", name)) {
+        return PathBuf::from(path);
+    }
+
+    // Tests run from target/<profile>/deps, and binaries are written to target/<profile>
+    let executable = env::current_exe().expect("unable to find the test executable");
+    executable.parent()
+        .and_then(|deps| if deps.ends_with("deps") { deps.parent() } else { Some(deps) })
+        .map(|profile| profile.join(format!("{}{}", name, env::consts::EXE_SUFFIX)))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| panic!("Binary {} not found, build it with cargo build --bin {}", name, name))
+}
+
+/// A run of a binary, described with chained calls and started by [CliTest::run]
+#[derive(Debug, Clone)]
+pub struct CliTest {
+    program: PathBuf,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    current_dir: Option<PathBuf>,
+    stdin: Vec<u8>,
+    timeout: Duration,
+}
+
+impl CliTest {
+    /// Describes a run of the given binary, without arguments nor standard input
+    pub fn new(program: impl AsRef<Path>) -> Self {
+        CliTest {
+            program: program.as_ref().to_path_buf(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None,
+            stdin: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Adds an argument
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds several arguments
+    pub fn args<A: AsRef<OsStr>>(self, args: impl IntoIterator<Item=A>) -> Self {
+        args.into_iter().fold(self, |test, arg| test.arg(arg))
+    }
+
+    /// Sets an environment variable of the binary only
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.envs.push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Runs the binary in the given directory
+    pub fn current_dir(mut self, directory: impl AsRef<Path>) -> Self {
+        self.current_dir = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Writes the given contents to the standard input of the binary, which is closed afterwards
+    pub fn stdin(mut self, contents: impl AsRef<[u8]>) -> Self {
+        self.stdin = contents.as_ref().to_vec();
+        self
+    }
+
+    /// Changes the time the binary may run, [DEFAULT_TIMEOUT] by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs the binary until it exits
+    ///
+    /// # Panics
+    /// Will panic if the binary can't be started, or if it is killed for taking too long.
+    #[track_caller]
+    pub fn run(self) -> CliOutput {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args)
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(directory) = &self.current_dir {
+            command.current_dir(directory);
+        }
+        let mut child = command.spawn()
+            .unwrap_or_else(|err| panic!("Unable to start {}: {}", self.command_line(), err));
+
+        // The streams are handled in their own threads, so a full pipe doesn't block the binary
+        let mut input = child.stdin.take().expect("piped standard input");
+        let contents = self.stdin.clone();
+        let writer = thread::spawn(move || {
+            // Binaries that exit without reading everything close the pipe, which is fine
+            let _ = input.write_all(&contents);
+        });
+        let stdout = read_in_background(child.stdout.take().expect("piped standard output"));
+        let stderr = read_in_background(child.stderr.take().expect("piped standard error"));
+
+        let status = wait(&mut child, self.timeout);
+        let _ = writer.join();
+        let output = CliOutput {
+            command: self.command_line(),
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        };
+        if output.status.is_none() {
+            panic!("Killed after {:?}: {}", self.timeout, output);
+        }
+
+        output
+    }
+
+    /// Returns the binary and its arguments, for the messages of the assertions
+    fn command_line(&self) -> String {
+        let mut line = self.program.display().to_string();
+        for arg in self.args.iter() {
+            line.push(' ');
+            line.push_str(&arg.to_string_lossy());
+        }
+
+        line
+    }
+}
+
+/// Reads a stream of the binary until it is closed
+fn read_in_background(mut stream: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut contents = Vec::new();
+        let _ = stream.read_to_end(&mut contents);
+        String::from_utf8_lossy(&contents).into_owned()
+    })
+}
+
+/// Waits for the binary to exit, or kills it after the timeout
+fn wait(child: &mut Child, timeout: Duration) -> Option<ExitStatus> {
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) if start.elapsed() < timeout => thread::sleep(POLL_INTERVAL),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+}
+
+/// Exit code and output of a binary. The assertions panic showing all of them, and return the
+/// output so they can be chained.
+#[derive(Debug, Clone)]
+pub struct CliOutput {
+    command: String,
+    /// Missing if the binary was killed
+    status: Option<ExitStatus>,
+    stdout: String,
+    stderr: String,
+}
+
+impl CliOutput {
+    /// Returns the exit code, missing if the binary was ended by a signal
+    pub fn code(&self) -> Option<i32> {
+        self.status.and_then(|status| status.code())
+    }
+
+    /// Returns the standard output
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Returns the standard error
+    pub fn stderr(&self) -> &str {
+        &self.stderr
+    }
+
+    /// Checks that the binary exited successfully
+    #[track_caller]
+    pub fn success(self) -> Self {
+        self.check(self.status.is_some_and(|status| status.success()), "Expected success");
+        self
+    }
+
+    /// Checks that the binary failed
+    #[track_caller]
+    pub fn failure(self) -> Self {
+        self.check(!self.status.is_some_and(|status| status.success()), "Expected failure");
+        self
+    }
+
+    /// Checks the exit code
+    #[track_caller]
+    pub fn exit_code(self, code: i32) -> Self {
+        self.check(self.code() == Some(code), &format!("Expected exit code {}", code));
+        self
+    }
+
+    /// Checks the whole standard output
+    #[track_caller]
+    pub fn stdout_eq(self, expected: &str) -> Self {
+        self.check(self.stdout == expected, &format!("Expected standard output:\n{}", expected));
+        self
+    }
+
+    /// Checks that the standard output contains some text
+    #[track_caller]
+    pub fn stdout_contains(self, expected: &str) -> Self {
+        self.check(self.stdout.contains(expected), &format!("Expected in standard output: {}", expected));
+        self
+    }
+
+    /// Checks the whole standard error
+    #[track_caller]
+    pub fn stderr_eq(self, expected: &str) -> Self {
+        self.check(self.stderr == expected, &format!("Expected standard error:\n{}", expected));
+        self
+    }
+
+    /// Checks that the standard error contains some text
+    #[track_caller]
+    pub fn stderr_contains(self, expected: &str) -> Self {
+        self.check(self.stderr.contains(expected), &format!("Expected in standard error: {}", expected));
+        self
+    }
+
+    /// Panics with the message and the output unless the condition holds
+    #[track_caller]
+    fn check(&self, condition: bool, message: &str) {
+        if !condition {
+            panic!("{}\n{}", message, self);
+        }
+    }
+}
+
+impl Display for CliOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Command: {}", self.command)?;
+        match self.status {
+            Some(status) => writeln!(f, "Status: {}", status)?,
+            None => writeln!(f, "Status: killed")?,
+        }
+        writeln!(f, "Standard output:\n{}", self.stdout)?;
+        write!(f, "Standard error:\n{}", self.stderr)
+    }
+}
@@ -0,0 +1,80 @@
+//! Reproducible randomness for property style tests. Each test gets a seed derived from its name,
+//! so runs are repeatable, and the `TEST_SEED` variable replaces it to explore other values or to
+//! reproduce a failure. The seed is printed when the test fails.
+//!
+//! ```
+//! use util::seed::TestSeed;
+//!
+//! let seed = TestSeed::new("random_mazes");
+//! let values: Vec<u64> = seed.values().take(3).collect();
+//! assert_eq!(values, TestSeed::new("random_mazes").values().take(3).collect::<Vec<_>>());
+//! ```
+
+use std::env;
+
+/// Variable that replaces the seeds derived from the names of the tests
+pub const SEED_VARIABLE: &str = "TEST_SEED";
+
+/// Seed of a test, printed if the test panics while it exists
+#[derive(Debug, Clone)]
+pub struct TestSeed {
+    name: String,
+    seed: u64,
+}
+
+impl TestSeed {
+    /// Creates the seed of the test with the given name, unless [SEED_VARIABLE] holds another one
+    ///
+    /// # Panics
+    /// Will panic if the variable is not a number.
+    pub fn new(name: &str) -> Self {
+        let seed = match env::var(SEED_VARIABLE) {
+            Ok(value) => value.trim().parse()
+                .unwrap_or_else(|_| panic!("{} must be a number, not '{}'", SEED_VARIABLE, value)),
+            Err(_) => seed_from_name(name),
+        };
+
+        TestSeed { name: name.to_string(), seed }
+    }
+
+    /// Returns the seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns an endless sequence of values derived from the seed, which is enough for choosing
+    /// the sizes or seeds of the cases of a test without depending on a random number crate
+    pub fn values(&self) -> impl Iterator<Item=u64> {
+        // SplitMix64, also used to seed the generators of the rand crates
+        let mut state = self.seed;
+        std::iter::repeat_with(move || {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        })
+    }
+
+    /// Creates a generator of the rand crates from the seed
+    #[cfg(feature = "rand")]
+    pub fn rng<R: rand_core::SeedableRng>(&self) -> R {
+        R::seed_from_u64(self.seed)
+    }
+}
+
+impl Drop for TestSeed {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            eprintln!("Test '{}' failed with seed {}, run it again with {}={} to reproduce it",
+                      self.name, self.seed, SEED_VARIABLE, self.seed);
+        }
+    }
+}
+
+/// Hashes the name of a test into a seed (FNV-1a), which is stable across runs and platforms
+fn seed_from_name(name: &str) -> u64 {
+    name.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
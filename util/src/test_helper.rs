@@ -0,0 +1,398 @@
+//! Helpers that keep the tests from leaving anything behind. Every temporary path is unique
+//! within the system temporary directory, so tests can run in parallel, and is deleted when its
+//! handler is dropped, even if the test panics. Changes to the process, like environment
+//! variables, are undone the same way.
+//!
+//! Directories of failed tests can be kept for inspection instead, with
+//! [TempDirectoryBuilder::keep_on_panic] or by running the tests with `KEEP_TEMP_DIRS=1`.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Prefix of the temporary paths, so leftovers are easy to recognize
+const PREFIX: &str = "rust-projects";
+
+/// Variable that keeps the temporary directories of the tests that panic
+pub const KEEP_VARIABLE: &str = "KEEP_TEMP_DIRS";
+
+/// Paths created so far by this process, used to make each one unique
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a path within the parent directory that is not used by any other handler
+fn unique_path(parent: &Path, prefix: &str, extension: Option<&str>) -> PathBuf {
+    let mut name = format!("{}-{}-{}", prefix, process::id(), CREATED.fetch_add(1, Ordering::Relaxed));
+    if let Some(extension) = extension {
+        name.push('.');
+        name.push_str(extension.trim_start_matches('.'));
+    }
+
+    parent.join(name)
+}
+
+/// A temporary directory, deleted along with its contents when dropped
+#[derive(Debug)]
+pub struct TempDirectoryHandler {
+    path: PathBuf,
+    /// Don't delete the directory if dropped while panicking
+    keep_on_panic: bool,
+}
+
+impl TempDirectoryHandler {
+    /// Creates a new empty directory within the system temporary directory
+    pub fn build() -> io::Result<Self> {
+        TempDirectoryHandler::builder().build()
+    }
+
+    /// Starts describing the files of a new directory
+    ///
+    /// ```
+    /// use util::test_helper::TempDirectoryHandler;
+    ///
+    /// let directory = TempDirectoryHandler::builder()
+    ///     .file("config.toml", "tick_rate = 10")
+    ///     .files([("patterns/glider.rle", "bo$2bo$3o!"), ("patterns/block.cells", "OO\nOO")])
+    ///     .directory("snapshots")
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(directory.path().join("patterns/glider.rle").is_file());
+    /// ```
+    pub fn builder() -> TempDirectoryBuilder {
+        TempDirectoryBuilder::default()
+    }
+
+    /// Returns the path of the directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of an entry within the directory
+    pub fn join(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.path.join(relative)
+    }
+}
+
+impl Drop for TempDirectoryHandler {
+    fn drop(&mut self) {
+        if self.keep_on_panic && std::thread::panicking() {
+            eprintln!("Keeping temporary directory {}", self.path.display());
+            return;
+        }
+
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Describes the tree of files of a temporary directory, which is created at once by
+/// [TempDirectoryBuilder::build]. Paths are relative to the directory and use `/` to nest files
+/// in subdirectories, which are created as needed.
+#[derive(Debug, Default)]
+pub struct TempDirectoryBuilder {
+    files: Vec<(PathBuf, Vec<u8>)>,
+    directories: Vec<PathBuf>,
+    prefix: Option<String>,
+    parent: Option<PathBuf>,
+    keep_on_panic: bool,
+}
+
+impl TempDirectoryBuilder {
+    /// Starts the name of the directory with the given prefix instead of `rust-projects`. The name
+    /// still ends with a unique suffix.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Creates the directory within the given one instead of the system temporary directory. The
+    /// parent is created if needed, and is not deleted with the directory.
+    pub fn parent(mut self, parent: impl AsRef<Path>) -> Self {
+        self.parent = Some(parent.as_ref().to_path_buf());
+        self
+    }
+
+    /// Keeps the directory if the test panics, printing its path, so its contents can be
+    /// inspected. [KEEP_VARIABLE] does the same for every directory.
+    pub fn keep_on_panic(mut self, keep: bool) -> Self {
+        self.keep_on_panic = keep;
+        self
+    }
+
+    /// Adds a file with the given contents
+    pub fn file(mut self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+        self.files.push((path.as_ref().to_path_buf(), contents.as_ref().to_vec()));
+        self
+    }
+
+    /// Adds several files, given as pairs of path and contents
+    pub fn files<P: AsRef<Path>, C: AsRef<[u8]>>(self, files: impl IntoIterator<Item=(P, C)>) -> Self {
+        files.into_iter().fold(self, |builder, (path, contents)| builder.file(path, contents))
+    }
+
+    /// Adds an empty directory
+    pub fn directory(mut self, path: impl AsRef<Path>) -> Self {
+        self.directories.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Creates the directory with every file. Paths that would end up outside of it are rejected.
+    pub fn build(self) -> io::Result<TempDirectoryHandler> {
+        let parent = self.parent.unwrap_or_else(env::temp_dir);
+        let path = unique_path(&parent, self.prefix.as_deref().unwrap_or(PREFIX), None);
+        let keep_on_panic = self.keep_on_panic
+            || env::var_os(KEEP_VARIABLE).is_some_and(|value| !value.is_empty() && value != "0");
+        let handler = TempDirectoryHandler { path, keep_on_panic };
+        fs::create_dir_all(&handler.path)?;
+
+        for directory in self.directories.iter() {
+            fs::create_dir_all(handler.path.join(relative(directory)?))?;
+        }
+        for (path, contents) in self.files.iter() {
+            let path = handler.path.join(relative(path)?);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+
+        Ok(handler)
+    }
+}
+
+/// Checks that a path stays within the directory it is joined to
+fn relative(path: &Path) -> io::Result<&Path> {
+    if path.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+        Ok(path)
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a relative path", path.display())))
+    }
+}
+
+/// A temporary file, deleted when dropped
+#[derive(Debug)]
+pub struct TempFileHandler {
+    path: PathBuf,
+}
+
+impl TempFileHandler {
+    /// Creates a new file with the given contents
+    pub fn build(contents: impl AsRef<[u8]>) -> io::Result<Self> {
+        TempFileHandler::create(unique_path(&env::temp_dir(), PREFIX, None), contents)
+    }
+
+    /// Creates a new file with the given extension and contents. Useful when the code under test
+    /// looks at the extension, e.g. to detect the format of the file.
+    pub fn build_with_extension(extension: &str, contents: impl AsRef<[u8]>) -> io::Result<Self> {
+        TempFileHandler::create(unique_path(&env::temp_dir(), PREFIX, Some(extension)), contents)
+    }
+
+    fn create(path: PathBuf, contents: impl AsRef<[u8]>) -> io::Result<Self> {
+        fs::write(&path, contents)?;
+
+        Ok(TempFileHandler { path })
+    }
+
+    /// Returns the path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFileHandler {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Held while the environment variables are changed, since tests run in parallel threads
+static ENVIRONMENT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Held while the working directory is changed
+static DIRECTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Sets or removes environment variables until dropped, when their previous values are restored.
+/// Only one guard exists at a time, so the variables of a test are not changed by another one;
+/// chain [EnvVarGuard::set] and [EnvVarGuard::unset] to change several variables at once.
+///
+/// ```
+/// use util::test_helper::EnvVarGuard;
+///
+/// let guard = EnvVarGuard::new().set("EXAMPLE_API_KEY", "secret").unset("HTTP_PROXY");
+/// assert_eq!(std::env::var("EXAMPLE_API_KEY").unwrap(), "secret");
+/// drop(guard);
+/// assert!(std::env::var("EXAMPLE_API_KEY").is_err());
+/// ```
+#[derive(Debug)]
+pub struct EnvVarGuard {
+    /// Variables changed by the guard, with their previous values
+    previous: Vec<(OsString, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvVarGuard {
+    /// Waits until no other guard exists. A test that panicked while holding the guard doesn't
+    /// block the rest, since its variables were restored anyway.
+    pub fn new() -> Self {
+        EnvVarGuard {
+            previous: Vec::new(),
+            _lock: ENVIRONMENT_LOCK.lock().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+
+    /// Sets a variable
+    pub fn set(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.remember(key.as_ref());
+        env::set_var(key, value);
+        self
+    }
+
+    /// Removes a variable
+    pub fn unset(mut self, key: impl AsRef<OsStr>) -> Self {
+        self.remember(key.as_ref());
+        env::remove_var(key);
+        self
+    }
+
+    /// Keeps the value of a variable before its first change
+    fn remember(&mut self, key: &OsStr) {
+        if !self.previous.iter().any(|(previous, _)| previous == key) {
+            self.previous.push((key.to_os_string(), env::var_os(key)));
+        }
+    }
+}
+
+impl Default for EnvVarGuard {
+    fn default() -> Self {
+        EnvVarGuard::new()
+    }
+}
+
+impl Drop for EnvVarGuard {
+    fn drop(&mut self) {
+        for (key, value) in self.previous.drain(..).rev() {
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key),
+            }
+        }
+    }
+}
+
+/// Changes the working directory of the process until dropped, when the previous one is
+/// restored. Like [EnvVarGuard], only one guard exists at a time. Tests that use relative paths
+/// while others change the directory should hold a guard as well, with [CwdGuard::keep].
+#[derive(Debug)]
+pub struct CwdGuard {
+    previous: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl CwdGuard {
+    /// Changes the working directory, waiting until no other guard exists
+    pub fn change(directory: impl AsRef<Path>) -> io::Result<Self> {
+        let guard = CwdGuard::keep()?;
+        env::set_current_dir(directory)?;
+
+        Ok(guard)
+    }
+
+    /// Keeps the working directory as it is, waiting until no other guard exists
+    pub fn keep() -> io::Result<Self> {
+        let lock = DIRECTORY_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(CwdGuard { previous: env::current_dir()?, _lock: lock })
+    }
+
+    /// Returns the working directory before the change
+    pub fn previous(&self) -> &Path {
+        &self.previous
+    }
+}
+
+impl Drop for CwdGuard {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.previous);
+    }
+}
+
+/// A temporary home directory with the usual XDG layout, pointed to by `HOME` and the
+/// `XDG_*_HOME` variables until dropped, so code that looks for its configuration there can be
+/// tested without touching the files of the user. The variables are changed through an
+/// [EnvVarGuard], so other variables of the test must be set with [ScopedHome::with_guard].
+///
+/// ```
+/// use util::test_helper::ScopedHome;
+///
+/// let home = ScopedHome::new().unwrap();
+/// let config = home.config_file("conway-life/config.toml", "tick_rate = 10").unwrap();
+/// assert_eq!(config, home.config_dir().join("conway-life/config.toml"));
+/// assert_eq!(std::env::var_os("HOME").unwrap(), home.home().as_os_str());
+/// ```
+#[derive(Debug)]
+pub struct ScopedHome {
+    // The variables are restored before the directory is deleted
+    _variables: EnvVarGuard,
+    directory: TempDirectoryHandler,
+}
+
+impl ScopedHome {
+    /// Creates the home directory and points the variables to it
+    pub fn new() -> io::Result<Self> {
+        ScopedHome::with_guard(EnvVarGuard::new())
+    }
+
+    /// Like [ScopedHome::new], adding the variables to a guard that changes others as well
+    pub fn with_guard(guard: EnvVarGuard) -> io::Result<Self> {
+        let directory = TempDirectoryHandler::builder()
+            .prefix("home")
+            .directory(".config")
+            .directory(".local/share")
+            .directory(".local/state")
+            .directory(".cache")
+            .build()?;
+        let variables = guard
+            .set("HOME", directory.path())
+            .set("USERPROFILE", directory.path())
+            .set("XDG_CONFIG_HOME", directory.join(".config"))
+            .set("XDG_DATA_HOME", directory.join(".local/share"))
+            .set("XDG_STATE_HOME", directory.join(".local/state"))
+            .set("XDG_CACHE_HOME", directory.join(".cache"));
+
+        Ok(ScopedHome { _variables: variables, directory })
+    }
+
+    /// Returns the home directory
+    pub fn home(&self) -> &Path {
+        self.directory.path()
+    }
+
+    /// Returns the directory of the configuration files, `XDG_CONFIG_HOME`
+    pub fn config_dir(&self) -> PathBuf {
+        self.directory.join(".config")
+    }
+
+    /// Returns the directory of the data files, `XDG_DATA_HOME`
+    pub fn data_dir(&self) -> PathBuf {
+        self.directory.join(".local/share")
+    }
+
+    /// Returns the directory of the cached files, `XDG_CACHE_HOME`
+    pub fn cache_dir(&self) -> PathBuf {
+        self.directory.join(".cache")
+    }
+
+    /// Writes a file within the configuration directory, creating its parents, and returns its path
+    pub fn config_file(&self, relative_path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+        let path = self.config_dir().join(relative(relative_path.as_ref())?);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)?;
+
+        Ok(path)
+    }
+}
@@ -0,0 +1,403 @@
+use std::env;
+use std::fs;
+
+use crate::test_helper::*;
+
+#[test]
+fn temp_directory() {
+    let directory = TempDirectoryHandler::build().unwrap();
+    let path = directory.path().to_path_buf();
+    assert!(path.is_dir());
+    assert_eq!(fs::read_dir(&path).unwrap().count(), 0);
+
+    // The contents are deleted along with the directory
+    fs::create_dir(path.join("nested")).unwrap();
+    fs::write(path.join("nested").join("file.txt"), "contents").unwrap();
+    drop(directory);
+    assert!(!path.exists());
+}
+
+#[test]
+fn temp_directory_tree() {
+    let directory = TempDirectoryHandler::builder()
+        .file("top.txt", "top")
+        .files([("nested/deeper/a.rle", "3o!"), ("nested/b.cells", "OO")])
+        .file("bytes.bin", [0u8, 1, 2])
+        .directory("empty/child")
+        .build()
+        .unwrap();
+
+    assert_eq!(fs::read_to_string(directory.join("top.txt")).unwrap(), "top");
+    assert_eq!(fs::read_to_string(directory.join("nested/deeper/a.rle")).unwrap(), "3o!");
+    assert_eq!(fs::read_to_string(directory.join("nested/b.cells")).unwrap(), "OO");
+    assert_eq!(fs::read(directory.join("bytes.bin")).unwrap(), vec![0, 1, 2]);
+    assert_eq!(fs::read_dir(directory.join("empty/child")).unwrap().count(), 0);
+
+    // Files can't escape the directory
+    assert!(TempDirectoryHandler::builder().file("../escaped.txt", "").build().is_err());
+    assert!(TempDirectoryHandler::builder().file("/tmp/absolute.txt", "").build().is_err());
+}
+
+#[test]
+fn temp_file() {
+    let file = TempFileHandler::build("first line\n").unwrap();
+    let path = file.path().to_path_buf();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first line\n");
+    drop(file);
+    assert!(!path.exists());
+
+    // Extensions are kept, and every file is unique
+    let rle = TempFileHandler::build_with_extension("rle", "3o!").unwrap();
+    let other = TempFileHandler::build_with_extension(".rle", b"").unwrap();
+    assert_eq!(rle.path().extension().unwrap(), "rle");
+    assert_eq!(other.path().extension().unwrap(), "rle");
+    assert_ne!(rle.path(), other.path());
+    assert!(fs::read(other.path()).unwrap().is_empty());
+
+    // Files removed by the test itself are not a problem
+    fs::remove_file(rle.path()).unwrap();
+}
+
+#[test]
+fn environment_variables() {
+    const SET: &str = "UTIL_TEST_HELPER_SET";
+    const UNSET: &str = "UTIL_TEST_HELPER_UNSET";
+
+    // Only this test uses the variables, so they can be set without a guard
+    env::set_var(UNSET, "before");
+    {
+        let _guard = EnvVarGuard::new().set(SET, "first").set(SET, "second").unset(UNSET);
+        assert_eq!(env::var(SET).unwrap(), "second");
+        assert!(env::var_os(UNSET).is_none());
+    }
+
+    // The values before the first change are restored
+    assert!(env::var_os(SET).is_none());
+    assert_eq!(env::var(UNSET).unwrap(), "before");
+    env::remove_var(UNSET);
+
+    // A panicking test doesn't keep the others from changing the variables
+    let _ = std::thread::spawn(|| {
+        let _guard = EnvVarGuard::new().set(SET, "panic");
+        panic!("test failure");
+    }).join();
+    assert!(env::var_os(SET).is_none());
+    drop(EnvVarGuard::new());
+}
+
+#[test]
+fn working_directory() {
+    let directory = TempDirectoryHandler::build().unwrap();
+    let original = CwdGuard::keep().unwrap().previous().to_path_buf();
+
+    {
+        let guard = CwdGuard::change(directory.path()).unwrap();
+        assert_eq!(guard.previous(), original);
+        assert_eq!(env::current_dir().unwrap(), directory.path().canonicalize().unwrap());
+
+        // Relative paths go to the new directory
+        fs::write("relative.txt", "contents").unwrap();
+        assert!(directory.path().join("relative.txt").is_file());
+    }
+    assert_eq!(env::current_dir().unwrap(), original);
+
+    // Missing directories are rejected, leaving the working directory alone
+    assert!(CwdGuard::change(directory.path().join("missing")).is_err());
+    assert_eq!(env::current_dir().unwrap(), original);
+}
+
+#[test]
+fn golden_files() {
+    use crate::golden::*;
+
+    let directory = TempDirectoryHandler::build().unwrap();
+    let guard = EnvVarGuard::new()
+        .set("CARGO_MANIFEST_DIR", directory.path())
+        .set(UPDATE_VARIABLE, "1");
+    assert_eq!(golden_path("grid.txt"), directory.join("tests/golden/grid.txt"));
+
+    // Golden files are written while updating, and compared otherwise
+    assert_matches_golden("grid.txt", "xx\nx \n");
+    assert_eq!(fs::read_to_string(directory.join("tests/golden/grid.txt")).unwrap(), "xx\nx \n");
+    let guard = guard.set(UPDATE_VARIABLE, "0");
+    assert_matches_golden("grid.txt", "xx\nx \n");
+
+    let mismatch = std::panic::catch_unwind(|| assert_matches_golden("grid.txt", "xx\nxx\n"));
+    let message = *mismatch.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("- x |\n+ xx|\n"), "{}", message);
+    assert!(message.contains(UPDATE_VARIABLE));
+    assert!(std::panic::catch_unwind(|| assert_matches_golden("missing.txt", "")).is_err());
+    drop(guard);
+
+    // Only the lines around the changes are shown
+    let expected = (0..20).map(|line| line.to_string()).collect::<Vec<_>>().join("\n");
+    let actual = expected.replace("10", "ten");
+    assert_eq!(diff(&expected, &actual), "...\n  7|\n  8|\n  9|\n- 10|\n+ ten|\n  11|\n  12|\n  13|\n...\n");
+    assert_eq!(diff("a\nb", "a\nb\nc"), "  a|\n  b|\n+ c|\n");
+    assert_eq!(diff("same", "same"), "");
+}
+
+#[test]
+fn grid_differences() {
+    use crate::grid::*;
+
+    assert!(grid_diff("xx\n x", "xx\n x").is_none());
+    assert_grids_eq("", "");
+
+    let diff = grid_diff("_____\n|   |\n| | |\n|_|_|", "_____\n|   |\n| | |\n|_| |").unwrap();
+    assert_eq!(diff, "Grids differ in 1 cell, the first at row 3 and column 3\n\
+                      \x20   expected | actual |\n\
+                      \x20 0 _____    | _____  |\n\
+                      \x20 1 |   |    | |   |  |\n\
+                      \x20 2 | | |    | | | |  |\n\
+                      > 3 |_|_|    | |_| |  |    ^\n");
+
+    // Missing rows and cells are differences as well
+    let diff = grid_diff("x \nx", "x").unwrap();
+    assert!(diff.starts_with("Grids differ in 2 cells, the first at row 0 and column 1\n"), "{}", diff);
+    assert!(diff.ends_with("> 1 x        |        | ^\n"), "{}", diff);
+
+    let mismatch = std::panic::catch_unwind(|| assert_grids_eq("x", "o"));
+    assert!(mismatch.unwrap_err().downcast::<String>().unwrap().contains("> 0 x        | o      | ^"));
+}
+
+#[test]
+fn test_seeds() {
+    use crate::seed::*;
+
+    let guard = EnvVarGuard::new().unset(SEED_VARIABLE);
+    let first = TestSeed::new("first");
+    assert_eq!(first.seed(), TestSeed::new("first").seed());
+    assert_ne!(first.seed(), TestSeed::new("second").seed());
+    let values: Vec<u64> = first.values().take(100).collect();
+    assert_eq!(values, first.values().take(100).collect::<Vec<_>>());
+    assert!(values.iter().all(|value| values.iter().filter(|&other| other == value).count() == 1));
+
+    // The variable replaces every seed
+    let guard = guard.set(SEED_VARIABLE, " 1234 ");
+    assert_eq!(TestSeed::new("first").seed(), 1234);
+    assert_eq!(TestSeed::new("second").seed(), 1234);
+    let _guard = guard.set(SEED_VARIABLE, "random");
+    assert!(std::panic::catch_unwind(|| TestSeed::new("first")).is_err());
+}
+
+#[test]
+fn temp_directory_options() {
+    let parent = TempDirectoryHandler::build().unwrap();
+    let directory = TempDirectoryHandler::builder()
+        .prefix("storage")
+        .parent(parent.join("nested"))
+        .build()
+        .unwrap();
+    assert_eq!(directory.path().parent().unwrap(), parent.join("nested"));
+    assert!(directory.path().file_name().unwrap().to_str().unwrap().starts_with("storage-"));
+    assert!(directory.path().is_dir());
+
+    // Directories are only kept when panicking
+    let kept = TempDirectoryHandler::builder().parent(parent.path()).keep_on_panic(true).build().unwrap();
+    let path = kept.path().to_path_buf();
+    let _ = std::thread::spawn(move || {
+        let _kept = kept;
+        panic!("test failure");
+    }).join();
+    assert!(path.is_dir());
+
+    let kept = TempDirectoryHandler::builder().keep_on_panic(true).build().unwrap();
+    let path = kept.path().to_path_buf();
+    drop(kept);
+    assert!(!path.exists());
+
+    // The variable keeps every directory
+    let guard = EnvVarGuard::new().set(KEEP_VARIABLE, "1");
+    let kept = TempDirectoryHandler::builder().parent(parent.path()).build().unwrap();
+    drop(guard);
+    let path = kept.path().to_path_buf();
+    let _ = std::thread::spawn(move || {
+        let _kept = kept;
+        panic!("test failure");
+    }).join();
+    assert!(path.is_dir());
+}
+
+#[test]
+#[cfg(unix)]
+fn process_runs() {
+    use std::time::Duration;
+    use crate::process::*;
+
+    let output = CliTest::new("sh")
+        .args(["-c", "cat; echo \"$GREETING\" >&2; exit 3"])
+        .env("GREETING", "hello")
+        .stdin("first\nsecond\n")
+        .run()
+        .failure()
+        .exit_code(3)
+        .stdout_eq("first\nsecond\n")
+        .stdout_contains("second")
+        .stderr_eq("hello\n");
+    assert_eq!(output.code(), Some(3));
+
+    let directory = TempDirectoryHandler::builder().file("listed.txt", "").build().unwrap();
+    CliTest::new("ls").current_dir(directory.path()).run().success().stdout_contains("listed.txt");
+
+    // Failed assertions show the output
+    let mismatch = std::panic::catch_unwind(|| CliTest::new("echo").arg("unexpected").run().stdout_contains("expected output"));
+    let message = *mismatch.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("Command: echo unexpected\n"), "{}", message);
+    assert!(message.contains("Standard output:\nunexpected\n"), "{}", message);
+
+    // Binaries that take too long are killed
+    let start = std::time::Instant::now();
+    let slow = std::panic::catch_unwind(|| CliTest::new("sleep").arg("10").timeout(Duration::from_millis(100)).run());
+    assert!(slow.is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn timing() {
+    use std::time::Duration;
+    use crate::timing::*;
+
+    let mut calls = 0;
+    let timing = time_block(2, 4, || calls += 1);
+    assert_eq!(calls, 6);
+    assert_eq!(timing.samples().len(), 4);
+    assert!(timing.min() <= timing.median() && timing.median() <= timing.max());
+
+    let timing = assert_faster_than("sleep", Duration::from_secs(5), || std::thread::sleep(Duration::from_millis(1)));
+    assert!(timing.min() >= Duration::from_millis(1));
+    assert_eq!(timing.samples().len(), MEASURED_RUNS);
+
+    let slow = std::panic::catch_unwind(|| assert_faster_than("slow", Duration::ZERO, || ()));
+    assert!(slow.unwrap_err().downcast::<String>().unwrap().starts_with("slow is slower than 0ns: median"));
+}
+
+#[test]
+fn fixture_files() {
+    use crate::fixtures;
+
+    let directory = TempDirectoryHandler::builder()
+        .file("tests/fixtures/patterns/blinker.cells", "OOO\r\n")
+        .file("tests/fixtures/size.txt", "42\n")
+        .file("tests/fixtures/image.bin", [0u8, 255])
+        .build()
+        .unwrap();
+    let _guard = EnvVarGuard::new().set("CARGO_MANIFEST_DIR", directory.path());
+
+    assert_eq!(fixtures::path("size.txt"), directory.join("tests/fixtures/size.txt"));
+    assert_eq!(fixtures::string("patterns/blinker.cells"), "OOO\n");
+    assert_eq!(fixtures::bytes("image.bin"), vec![0, 255]);
+    assert_eq!(fixtures::parsed("size.txt", |text| text.trim().parse::<u32>()), 42);
+
+    let missing = std::panic::catch_unwind(|| fixtures::string("missing.txt"));
+    assert!(missing.unwrap_err().downcast::<String>().unwrap().starts_with("Unable to read fixture"));
+    let invalid = std::panic::catch_unwind(|| fixtures::parsed("patterns/blinker.cells", str::parse::<u32>));
+    assert!(invalid.unwrap_err().downcast::<String>().unwrap().starts_with("Unable to parse fixture"));
+}
+
+#[test]
+fn log_capture() {
+    use crate::logs::*;
+
+    // Records are only kept while capturing
+    capture(Level::Info, "ignored", String::from("before"));
+    let logs = LogCapture::start();
+    capture(Level::Warn, "util::cache", String::from("cache is full"));
+    capture(Level::Debug, "util::cache", String::from("evicted 3 entries"));
+    assert_eq!(logs.records(), vec![
+        Record { level: Level::Warn, target: String::from("util::cache"), message: String::from("cache is full") },
+        Record { level: Level::Debug, target: String::from("util::cache"), message: String::from("evicted 3 entries") },
+    ]);
+    logs.assert_logged(Level::Warn, "full");
+    logs.assert_not_logged(Level::Error, "full");
+    logs.assert_not_logged(Level::Info, "before");
+
+    // Other threads have their own records
+    std::thread::spawn(|| capture(Level::Error, "other", String::from("elsewhere"))).join().unwrap();
+    assert!(!logs.contains(Level::Error, "elsewhere"));
+
+    let missing = std::panic::catch_unwind(|| LogCapture::start().assert_logged(Level::Error, "failure"));
+    assert!(missing.unwrap_err().downcast::<String>().unwrap().ends_with("No records were captured"));
+    drop(logs);
+}
+
+#[test]
+fn polling() {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+    use std::time::Duration;
+    use crate::poll::*;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let writer = counter.clone();
+    thread::spawn(move || {
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(5));
+            writer.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    assert!(poll_until(Duration::from_secs(5), Duration::from_millis(1), || counter.load(Ordering::SeqCst) == 3).is_ok());
+
+    // Values can be returned, and conditions that never hold time out
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(42).unwrap());
+    assert_eq!(poll_for(Duration::from_secs(5), Duration::from_millis(1), || receiver.try_recv().ok()), Ok(42));
+    let timed_out = poll_until(Duration::from_millis(20), Duration::from_millis(5), || false).unwrap_err();
+    assert_eq!(timed_out.timeout, Duration::from_millis(20));
+    assert!(timed_out.attempts >= 2);
+
+    // Minimal executor, parking the thread until the future is woken
+    struct Unpark(Thread);
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Arc::new(Unpark(thread::current())).into();
+        let mut context = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    let checks = AtomicUsize::new(0);
+    let elapsed = block_on(poll_until_async(Duration::from_secs(5), Duration::from_millis(2), || {
+        let done = checks.fetch_add(1, Ordering::SeqCst) == 3;
+        async move { done }
+    }));
+    assert!(elapsed.unwrap() >= Duration::from_millis(6));
+    assert_eq!(checks.load(Ordering::SeqCst), 4);
+    let timed_out = block_on(poll_for_async(Duration::from_millis(10), Duration::from_millis(3), || async { None::<()> }));
+    assert!(timed_out.is_err());
+}
+
+#[test]
+fn scoped_home() {
+    let original = env::var_os("HOME");
+    let home = ScopedHome::with_guard(EnvVarGuard::new().set("UTIL_TEST_HELPER_HOME", "set")).unwrap();
+    assert_eq!(env::var_os("HOME").unwrap(), home.home().as_os_str());
+    assert_eq!(env::var_os("XDG_CONFIG_HOME").unwrap(), home.config_dir().as_os_str());
+    assert_eq!(env::var_os("XDG_DATA_HOME").unwrap(), home.data_dir().as_os_str());
+    assert_eq!(env::var_os("XDG_CACHE_HOME").unwrap(), home.cache_dir().as_os_str());
+    assert_eq!(env::var("UTIL_TEST_HELPER_HOME").unwrap(), "set");
+    assert!(home.data_dir().is_dir() && home.cache_dir().is_dir());
+
+    let config = home.config_file("app/config.toml", "key = 1").unwrap();
+    assert_eq!(fs::read_to_string(&config).unwrap(), "key = 1");
+    assert!(home.config_file("../outside.toml", "").is_err());
+
+    let path = home.home().to_path_buf();
+    drop(home);
+    assert!(!path.exists());
+    assert_eq!(env::var_os("HOME"), original);
+    assert!(env::var_os("UTIL_TEST_HELPER_HOME").is_none());
+}
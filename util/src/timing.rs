@@ -0,0 +1,100 @@
+//! Timing of performance sensitive code within the unit tests, for guarding against regressions
+//! without a full benchmark. The code runs a few times to warm up the caches, and then the median
+//! of several runs is compared, which is less affected by a busy machine than a single run.
+//!
+//! Tests are usually built without optimizations, so limits should be generous: they catch an
+//! algorithm that became quadratic, not a few percent. The benches of each crate measure the rest.
+//!
+//! ```
+//! use std::time::Duration;
+//! use util::timing::{assert_faster_than, time_block};
+//!
+//! let timing = time_block(2, 5, || (0..1000u64).sum::<u64>());
+//! assert_eq!(timing.samples().len(), 5);
+//! assert_faster_than("sum", Duration::from_secs(1), || (0..1000u64).sum::<u64>());
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Runs discarded before measuring
+pub const WARMUP_RUNS: usize = 3;
+
+/// Runs measured by [assert_faster_than]
+pub const MEASURED_RUNS: usize = 11;
+
+/// Durations of the measured runs of some code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    /// Sorted from the fastest run
+    samples: Vec<Duration>,
+}
+
+impl Timing {
+    /// Returns the durations of the runs, from the fastest
+    pub fn samples(&self) -> &[Duration] {
+        &self.samples
+    }
+
+    /// Returns the duration of the run in the middle
+    pub fn median(&self) -> Duration {
+        // The two runs in the middle, which are the same one if there is an odd amount of runs
+        let length = self.samples.len();
+        (self.samples[(length - 1) / 2] + self.samples[length / 2]) / 2
+    }
+
+    /// Returns the duration of the fastest run
+    pub fn min(&self) -> Duration {
+        self.samples[0]
+    }
+
+    /// Returns the duration of the slowest run
+    pub fn max(&self) -> Duration {
+        self.samples[self.samples.len() - 1]
+    }
+}
+
+impl Display for Timing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "median {:?} (min {:?}, max {:?}, {} runs)", self.median(), self.min(), self.max(), self.samples.len())
+    }
+}
+
+/// Runs the code `warmup` times, and then measures `runs` more. Results are passed through
+/// [black_box], so the compiler doesn't remove the code.
+///
+/// # Panics
+/// Will panic if `runs` is 0.
+pub fn time_block<T>(warmup: usize, runs: usize, mut code: impl FnMut() -> T) -> Timing {
+    assert_ne!(runs, 0, "at least one run must be measured");
+
+    for _ in 0..warmup {
+        black_box(code());
+    }
+    let mut samples: Vec<Duration> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(code());
+            start.elapsed()
+        })
+        .collect();
+    samples.sort();
+
+    Timing { samples }
+}
+
+/// Checks that the median run of the code takes less than the limit, printing the timing
+///
+/// # Panics
+/// Will panic if the code is slower.
+#[track_caller]
+pub fn assert_faster_than<T>(name: &str, limit: Duration, code: impl FnMut() -> T) -> Timing {
+    let timing = time_block(WARMUP_RUNS, MEASURED_RUNS, code);
+    eprintln!("{}: {}", name, timing);
+    if timing.median() >= limit {
+        panic!("{} is slower than {:?}: {}", name, limit, timing);
+    }
+
+    timing
+}
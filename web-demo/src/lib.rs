@@ -0,0 +1,113 @@
+//! Demo of the workspace libraries running in a browser: a maze is generated to fill the
+//! canvas, and its walls come to life as the cells of Conway's Game of Life. Built with
+//! `wasm-pack build web-demo --target web`, and used by `web-demo/index.html`.
+//!
+//! The module also exports the [Simulation] of conway-life, used by `conway-life/web/index.html`,
+//! so the WebAssembly artifacts of the workspace are only built from this crate.
+//!
+//! Nothing in the demo panics on bad input from the page: sizes and rules are validated and
+//! reported back as errors.
+
+use conway_life::{Environment, Rule, SimCell, Viewport};
+use perfect_maze_generator::PerfectMaze;
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+pub use conway_life::web::Simulation;
+
+#[cfg(test)]
+mod tests;
+
+/// A Life environment seeded with mazes, along with the viewport drawn on the canvas
+#[wasm_bindgen]
+pub struct Demo {
+    environment: Environment,
+    viewport: Viewport,
+    generation: usize,
+}
+
+#[wasm_bindgen]
+impl Demo {
+    /// Creates an empty demo, showing the given amount of cells centered at the origin
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize) -> Result<Demo, JsError> {
+        let viewport = centered_viewport(width, height).ok_or_else(|| JsError::new("The canvas is too large or empty"))?;
+        Ok(Demo { environment: Environment::default(), viewport, generation: 0 })
+    }
+
+    /// Replaces the environment with a maze filling the viewport, with its walls as the living
+    /// cells. A seed of 0 keeps every wall in order.
+    pub fn generate_maze(&mut self, seed: u32) -> Result<(), JsError> {
+        let mut environment = maze_environment(&self.viewport, seed.into())
+            .ok_or_else(|| JsError::new("The canvas is too small for a maze"))?;
+        environment.set_rule(self.environment.rule());
+
+        self.environment = environment;
+        self.generation = 0;
+        Ok(())
+    }
+
+    /// Changes the rule, given in B/S notation
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsError> {
+        self.environment.set_rule(rule.parse::<Rule>()?);
+        Ok(())
+    }
+
+    /// Simulates the given amount of generations
+    pub fn step(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.environment.simulate();
+        }
+        self.generation += generations;
+    }
+
+    /// Returns the amount of generations simulated since the last maze
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the amount of living cells
+    pub fn population(&self) -> usize {
+        self.environment.get_living_count()
+    }
+
+    /// Draws the viewport on the canvas, with each cell being `scale` pixels wide
+    pub fn draw(&mut self, context: &CanvasRenderingContext2d, scale: usize, living: &str, background: &str) {
+        self.environment.fill_viewport(&mut self.viewport);
+
+        let scale_pixels = scale as f64;
+        context.set_fill_style(&JsValue::from_str(background));
+        context.fill_rect(0.0, 0.0, self.viewport.width() as f64 * scale_pixels,
+                          self.viewport.height() as f64 * scale_pixels);
+
+        context.set_fill_style(&JsValue::from_str(living));
+        for point in self.viewport.points::<u32>(scale).unwrap_or_default() {
+            context.fill_rect(point.x as f64, point.y as f64, scale_pixels, scale_pixels);
+        }
+    }
+}
+
+/// Returns a viewport of the given amount of cells centered at the origin, or None if it would
+/// be empty or its corners don't fit in the plane
+fn centered_viewport(width: usize, height: usize) -> Option<Viewport> {
+    let x = i32::try_from(width / 2).ok()?;
+    let y = i32::try_from(height / 2).ok()?;
+    let fits = width != 0 && height != 0
+        && i32::try_from(width).is_ok() && i32::try_from(height).is_ok()
+        && width.checked_mul(height).is_some();
+
+    fits.then(|| Viewport::new(-x, y, width, height))
+}
+
+/// Creates an environment with the walls of the largest maze that fits in the viewport as its
+/// living cells, or None if the viewport is too small. Each wall and cell of the maze takes a
+/// single cell of the environment.
+fn maze_environment(viewport: &Viewport, seed: u64) -> Option<Environment> {
+    let maze = PerfectMaze::try_new((viewport.width() - 1) / 2, (viewport.height() - 1) / 2, Some(seed))?;
+    let blocks = maze.scene().blocks();
+    let rows: Vec<&[bool]> = blocks.rows().collect();
+
+    let mut environment = Environment::default();
+    environment.set_from_grid(SimCell::new(viewport.x(), viewport.y()), &rows);
+    Some(environment)
+}
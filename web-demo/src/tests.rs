@@ -0,0 +1,48 @@
+use crate::*;
+
+#[test]
+fn demo_viewport() {
+    let viewport = centered_viewport(21, 10).unwrap();
+    assert_eq!((viewport.x(), viewport.y()), (-10, 5));
+    assert_eq!((viewport.width(), viewport.height()), (21, 10));
+
+    assert!(centered_viewport(0, 10).is_none());
+    assert!(centered_viewport(usize::MAX, 1).is_none());
+}
+
+#[test]
+fn simulation_size() {
+    let mut simulation = Simulation::try_new(21, 10).unwrap();
+    assert_eq!(simulation.population(), 0);
+
+    // The viewport stays in place instead of overflowing
+    simulation.pan(i32::MAX, 0);
+    simulation.pan(0, i32::MIN);
+    simulation.toggle(0, 0);
+    simulation.pan(-1, 1);
+    simulation.toggle(1, 1);
+    assert_eq!(simulation.population(), 0);
+
+    assert!(Simulation::try_new(0, 10).is_none());
+    assert!(Simulation::try_new(10, 0).is_none());
+    assert!(Simulation::try_new(usize::MAX, 1).is_none());
+    assert!(Simulation::try_new(1 << 31, 1).is_none());
+}
+
+#[test]
+fn maze_walls() {
+    // A 10x4 maze fills the viewport, leaving the last row empty
+    let viewport = centered_viewport(21, 10).unwrap();
+    let environment = maze_environment(&viewport, 7).unwrap();
+    let blocks = PerfectMaze::new(10, 4, Some(7)).scene().blocks();
+    let walls = blocks.cells().iter().filter(|wall| **wall).count();
+    assert_eq!(environment.get_living_count(), walls);
+
+    // Corners of the maze
+    assert!(environment.get_cell(&SimCell::new(-10, 5)));
+    assert!(environment.get_cell(&SimCell::new(10, -3)));
+    assert!(!environment.get_cell(&SimCell::new(10, -4)));
+
+    // No maze fits
+    assert!(maze_environment(&centered_viewport(2, 10).unwrap(), 7).is_none());
+}